@@ -0,0 +1,290 @@
+//! Optional addons from the update manifest, installed as Mod Organizer 2 mods.
+//!
+//! Every addon gets its own folder in MO2's `mods`, named after the addon, and
+//! is added on top of the profile's mod list. What the launcher installed is
+//! recorded in [`INSTALLED_ADDONS_FILE`] in the MO2 instance folder, so mods
+//! the user installed by hand are never touched.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+
+use crate::{
+    fs_util,
+    manifest::ManifestAddon,
+    mo2::Mo2Instance,
+    modlist::{self, ModEntry, ModList, ModState},
+};
+
+pub const INSTALLED_ADDONS_FILE: &str = "launcher_addons.toml";
+
+#[derive(Debug)]
+pub enum AddonError {
+    UnknownAddon(String),
+    NotInstalled(String),
+    MissingDependencies { addon: String, missing: Vec<String> },
+    Incompatible { addon: String, installed: Vec<String> },
+    RequiredBy { addon: String, dependents: Vec<String> },
+    FileSystemError(String),
+    ArchiveError(String),
+}
+
+impl fmt::Display for AddonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddonError::UnknownAddon(id) => write!(f, "There is no addon '{}' in the catalog", id),
+            AddonError::NotInstalled(id) => write!(f, "The addon '{}' is not installed", id),
+            AddonError::MissingDependencies { addon, missing } => {
+                write!(f, "{} requires {}; install it first", addon, missing.join(", "))
+            }
+            AddonError::Incompatible { addon, installed } => {
+                write!(f, "{} can't be used together with {}; remove it first", addon, installed.join(", "))
+            }
+            AddonError::RequiredBy { addon, dependents } => {
+                write!(f, "{} is required by {}; remove it first", addon, dependents.join(", "))
+            }
+            AddonError::FileSystemError(msg) => write!(f, "File system error: {}", msg),
+            AddonError::ArchiveError(msg) => write!(f, "Archive error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AddonError {}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledAddon {
+    pub version: Version,
+    /// Folder in MO2's `mods`.
+    pub folder: String,
+}
+
+/// Contents of [`INSTALLED_ADDONS_FILE`], keyed by addon id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstalledAddons {
+    #[serde(default, rename = "addon")]
+    pub addons: BTreeMap<String, InstalledAddon>,
+}
+
+impl InstalledAddons {
+    pub fn path(instance_dir: &Path) -> PathBuf {
+        instance_dir.join(INSTALLED_ADDONS_FILE)
+    }
+
+    /// Reads the list from an MO2 instance folder; a missing file means nothing is installed.
+    pub fn load(instance_dir: &Path) -> io::Result<Self> {
+        match fs::read_to_string(Self::path(instance_dir)) {
+            Ok(content) => toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(InstalledAddons::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, instance_dir: &Path) -> io::Result<()> {
+        let content = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs_util::write_atomic(&Self::path(instance_dir), content.as_bytes())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&InstalledAddon> {
+        self.addons.get(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddonStatus {
+    Available,
+    Installed,
+    /// Installed, and the catalog has a newer version.
+    Outdated,
+}
+
+pub fn status(addon: &ManifestAddon, installed: &InstalledAddons) -> AddonStatus {
+    match installed.get(&addon.id) {
+        None => AddonStatus::Available,
+        Some(current) if current.version < addon.version => AddonStatus::Outdated,
+        Some(_) => AddonStatus::Installed,
+    }
+}
+
+/// The MO2 mod folder for an addon: its name, or its id when the name isn't a valid folder name.
+pub fn folder_name(addon: &ManifestAddon) -> String {
+    let name = addon.name.trim();
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.ends_with('.')
+        && !name.chars().any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control());
+    if valid { name.to_string() } else { addon.id.clone() }
+}
+
+/// Checks that the addon's dependencies are installed and that nothing installed
+/// conflicts with it, in either direction. Installing over an older version is allowed.
+pub fn check_install<'a>(catalog: &'a [ManifestAddon], installed: &InstalledAddons, id: &str) -> Result<&'a ManifestAddon, AddonError> {
+    let addon = find(catalog, id)?;
+    let missing: Vec<String> =
+        addon.requires.iter().filter(|required| installed.get(required).is_none()).map(|required| display_name(catalog, required)).collect();
+    if !missing.is_empty() {
+        return Err(AddonError::MissingDependencies { addon: addon.name.clone(), missing });
+    }
+
+    let incompatible: Vec<String> = installed
+        .addons
+        .keys()
+        .filter(|other| *other != &addon.id)
+        .filter(|other| {
+            addon.conflicts.contains(other) || catalog.iter().any(|entry| &entry.id == *other && entry.conflicts.contains(&addon.id))
+        })
+        .map(|other| display_name(catalog, other))
+        .collect();
+    if !incompatible.is_empty() {
+        return Err(AddonError::Incompatible { addon: addon.name.clone(), installed: incompatible });
+    }
+    Ok(addon)
+}
+
+/// Checks that no other installed addon requires this one.
+pub fn check_remove(catalog: &[ManifestAddon], installed: &InstalledAddons, id: &str) -> Result<(), AddonError> {
+    if installed.get(id).is_none() {
+        return Err(AddonError::NotInstalled(id.to_string()));
+    }
+    let dependents: Vec<String> = installed
+        .addons
+        .keys()
+        .filter(|other| *other != id)
+        .filter(|other| catalog.iter().any(|entry| &entry.id == *other && entry.requires.iter().any(|required| required == id)))
+        .map(|other| display_name(catalog, other))
+        .collect();
+    if !dependents.is_empty() {
+        return Err(AddonError::RequiredBy { addon: display_name(catalog, id), dependents });
+    }
+    Ok(())
+}
+
+/// Extracts a downloaded addon archive into its MO2 mod folder, replacing an
+/// installed version, and enables it on top of `profile`'s mod list.
+/// Returns the mod folder.
+pub fn install(
+    instance: &Mo2Instance,
+    profile: Option<&str>,
+    addon: &ManifestAddon,
+    archive: &Path,
+    installed: &mut InstalledAddons,
+) -> Result<PathBuf, AddonError> {
+    let folder = installed.get(&addon.id).map(|current| current.folder.clone()).unwrap_or_else(|| folder_name(addon));
+    let target = instance.mods_dir.join(&folder);
+    if installed.get(&addon.id).is_none() && target.exists() {
+        return Err(AddonError::FileSystemError(format!("{} already exists and wasn't installed by the launcher", target.display())));
+    }
+    let staging = fs_util::with_suffix(&target, ".installing");
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| fs_error(&staging, e))?;
+    }
+    if let Err(e) = extract(archive, &staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    if target.exists() {
+        fs::remove_dir_all(&target).map_err(|e| fs_error(&target, e))?;
+    }
+    fs::rename(&staging, &target).map_err(|e| fs_error(&target, e))?;
+
+    installed.addons.insert(addon.id.clone(), InstalledAddon { version: addon.version.clone(), folder: folder.clone() });
+    installed.save(&instance.dir).map_err(|e| fs_error(&InstalledAddons::path(&instance.dir), e))?;
+
+    // Updates keep the place and state the user gave the mod
+    if let Some(profile) = profile {
+        let path = modlist::modlist_path(&instance.profile_dir(profile));
+        let mut list = if path.exists() { ModList::load(&path).map_err(|e| fs_error(&path, e))? } else { ModList::parse("") };
+        if list.get(&folder).is_none() {
+            list.entries.insert(0, ModEntry { name: folder, state: ModState::Enabled });
+            list.save(&path).map_err(|e| fs_error(&path, e))?;
+        }
+    }
+    Ok(target)
+}
+
+/// Deletes an installed addon's mod folder and drops it from every profile's mod list.
+pub fn remove(instance: &Mo2Instance, id: &str, installed: &mut InstalledAddons) -> Result<(), AddonError> {
+    let current = installed.addons.remove(id).ok_or_else(|| AddonError::NotInstalled(id.to_string()))?;
+    let target = instance.mods_dir.join(&current.folder);
+    if target.exists() {
+        fs::remove_dir_all(&target).map_err(|e| fs_error(&target, e))?;
+    }
+    installed.save(&instance.dir).map_err(|e| fs_error(&InstalledAddons::path(&instance.dir), e))?;
+
+    for profile in instance.profiles() {
+        let path = modlist::modlist_path(&instance.profile_dir(&profile));
+        let Ok(mut list) = ModList::load(&path) else {
+            continue;
+        };
+        let before = list.entries.len();
+        list.entries.retain(|entry| entry.name != current.folder);
+        if list.entries.len() != before {
+            list.save(&path).map_err(|e| fs_error(&path, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Extracts `archive` into `dir`. Archives that wrap everything in a single
+/// folder other than `gamedata` have it stripped, so the mod folder starts at
+/// `gamedata` either way.
+fn extract(archive: &Path, dir: &Path) -> Result<(), AddonError> {
+    let file = File::open(archive).map_err(|e| fs_error(archive, e))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| AddonError::ArchiveError(format!("Failed to open {}: {}", archive.display(), e)))?;
+
+    let entries: Vec<(PathBuf, bool)> = (0..zip.len())
+        .filter_map(|i| zip.by_index(i).ok().and_then(|entry| entry.enclosed_name().map(|name| (name.to_path_buf(), entry.is_dir()))))
+        .collect();
+    let root = |name: &Path| name.components().next().map(|root| root.as_os_str().to_os_string());
+    let single_root = entries.first().and_then(|(name, _)| root(name)).filter(|first| {
+        !first.eq_ignore_ascii_case("gamedata")
+            && entries.iter().all(|(name, is_dir)| root(name).as_ref() == Some(first) && (*is_dir || name.components().count() > 1))
+    });
+
+    fs::create_dir_all(dir).map_err(|e| fs_error(dir, e))?;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| AddonError::ArchiveError(format!("Failed to read {}: {}", archive.display(), e)))?;
+        let Some(name) = entry.enclosed_name().map(Path::to_path_buf) else {
+            continue;
+        };
+        let relative = match &single_root {
+            Some(root) => name.strip_prefix(root).map(Path::to_path_buf).unwrap_or(name),
+            None => name,
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let outpath = dir.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&outpath).map_err(|e| fs_error(&outpath, e))?;
+            continue;
+        }
+        if let Some(parent) = outpath.parent() {
+            fs::create_dir_all(parent).map_err(|e| fs_error(parent, e))?;
+        }
+        let mut outfile = File::create(&outpath).map_err(|e| fs_error(&outpath, e))?;
+        io::copy(&mut entry, &mut outfile).map_err(|e| fs_error(&outpath, e))?;
+    }
+    Ok(())
+}
+
+fn find<'a>(catalog: &'a [ManifestAddon], id: &str) -> Result<&'a ManifestAddon, AddonError> {
+    catalog.iter().find(|addon| addon.id == id).ok_or_else(|| AddonError::UnknownAddon(id.to_string()))
+}
+
+fn display_name(catalog: &[ManifestAddon], id: &str) -> String {
+    catalog.iter().find(|addon| addon.id == id).map(|addon| addon.name.clone()).unwrap_or_else(|| id.to_string())
+}
+
+fn fs_error(path: &Path, e: io::Error) -> AddonError {
+    AddonError::FileSystemError(format!("{}: {}", path.display(), e))
+}
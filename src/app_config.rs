@@ -1,7 +1,22 @@
-use std::fs;
+use std::{collections::BTreeMap, env, fmt, fs, path::Path, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    config_location::ConfigLocation,
+    fs_util,
+    game::ExecutableEntry,
+    launch_args::{self, LaunchFlags},
+    layers::{self, ConfigLayers},
+    migrations::{self, SCHEMA_VERSION},
+    mo2::Mo2Config,
+    save_backup::SaveBackupConfig,
+    shader_cache::ShaderCacheConfig,
+    runner::RunnerConfig,
+    system_info::CpuFeatures,
+};
+
+/// Direct3D renderer the game is started with; each one has its own executable.
 #[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Renderer {
     DX8,
@@ -10,6 +25,7 @@ pub enum Renderer {
     DX11,
 }
 
+/// Shadow map resolution passed to the game as `-smapNNNN`.
 #[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ShadowMapSize {
     Size1536,
@@ -19,55 +35,494 @@ pub enum ShadowMapSize {
     Size4096,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AppConfig {
+/// What the launcher does once the game has started.
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub enum AfterLaunch {
+    /// Close the launcher right away.
+    #[default]
+    Exit,
+    /// Stay minimized and come back when the game exits.
+    Minimize,
+    /// Close the window but keep watching the game in the background.
+    Hide,
+}
+
+/// Name shown for the settings stored at the top level of the config.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Renderer and command-line choices that make up a launch profile.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LaunchSettings {
     pub renderer: Renderer,
     pub use_avx: bool,
     pub shadow_map: ShadowMapSize,
     pub debug: bool,
     pub prefetch_sounds: bool,
+    pub flags: LaunchFlags,
+    /// Free-form arguments appended after the flags; quotes group words.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub extra_args: String,
+}
+
+/// Launcher settings stored in `launcherconfig.toml`, see [`AppConfig::location`].
+/// Missing fields take their default values.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Format of the file, see [`crate::migrations`].
+    pub schema_version: u32,
+    /// Settings of the default profile, kept at the top level for older launchers.
+    #[serde(flatten)]
+    pub launch: LaunchSettings,
+    /// Profile used for launching; the default profile when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Named profiles, stored as `[profiles.<name>]`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, LaunchSettings>,
+    /// Installed modpack version, compared against patch versions by the updater.
+    /// Unknown rather than the default when missing.
+    #[serde(default)]
     pub version: Option<String>,
+    /// URL of the patch list consumed by [`crate::Updater`].
     pub update_url: Option<String>,
+    /// Base64 Ed25519 key; when set, only patches signed with it are installed.
+    pub update_public_key: Option<String>,
+    /// Modpack overrides for the executable started per renderer.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub executables: Vec<ExecutableEntry>,
+    /// How the game is started; Wine or Proton on Linux.
+    pub runner: RunnerConfig,
+    /// Launching through Mod Organizer 2.
+    pub mo2: Mo2Config,
+    /// When save backups are taken and how many are kept.
+    pub save_backups: SaveBackupConfig,
+    /// When the shader cache is cleared automatically.
+    pub shader_cache: ShaderCacheConfig,
+    pub after_launch: AfterLaunch,
+    /// Where each value came from; only set by [`AppConfig::load`].
+    #[serde(skip)]
+    pub layers: Option<Arc<ConfigLayers>>,
 }
 
+/// Name of the file the launcher settings are stored in, see [`AppConfig::location`].
+pub const CONFIG_FILE: &str = "launcherconfig.toml";
+
+#[derive(Debug)]
 pub enum AppConfigError {
-    ReadFailed,
-    BadStructure,
-    WriteFailed,
+    ReadFailed(String),
+    /// The file isn't valid TOML or holds invalid values. The position is
+    /// 1-based and missing when the error can't be tied to one place.
+    BadStructure { message: String, line: Option<usize>, column: Option<usize> },
+    /// A value from the modpack defaults, an environment variable or `--set` is invalid.
+    BadOverride { source: String, message: String },
+    WriteFailed(String),
+}
+
+impl AppConfigError {
+    fn bad_structure(content: Option<&str>, error: toml::de::Error) -> Self {
+        // A span over the whole document doesn't say anything about where the error is
+        let span = error.span().filter(|span| !(span.start == 0 && content.is_some_and(|c| span.end >= c.trim_end().len())));
+        let position = content.zip(span).map(|(content, span)| {
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+            (line, column)
+        });
+        AppConfigError::BadStructure {
+            message: error.message().trim().to_string(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+        }
+    }
+}
+
+impl fmt::Display for AppConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppConfigError::ReadFailed(e) => write!(f, "Failed to read {}: {}", CONFIG_FILE, e),
+            AppConfigError::BadStructure { message, line: Some(line), column: Some(column) } => {
+                write!(f, "{} is damaged at line {}, column {}: {}", CONFIG_FILE, line, column, message)
+            }
+            AppConfigError::BadStructure { message, .. } => write!(f, "{} is damaged: {}", CONFIG_FILE, message),
+            AppConfigError::BadOverride { source, message } => write!(f, "Invalid setting in {}: {}", source, message),
+            AppConfigError::WriteFailed(e) => write!(f, "Failed to write {}: {}", CONFIG_FILE, e),
+        }
+    }
+}
+
+impl std::error::Error for AppConfigError {}
+
+#[derive(Debug, PartialEq)]
+pub enum ProfileError {
+    NotFound(String),
+    AlreadyExists(String),
+    InvalidName(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::NotFound(name) => write!(f, "Profile '{}' does not exist", name),
+            ProfileError::AlreadyExists(name) => write!(f, "Profile '{}' already exists", name),
+            ProfileError::InvalidName(name) => write!(f, "'{}' cannot be used as a profile name", name),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl fmt::Display for Renderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Renderer::DX8 => write!(f, "DirectX 8"),
+            Renderer::DX9 => write!(f, "DirectX 9"),
+            Renderer::DX10 => write!(f, "DirectX 10"),
+            Renderer::DX11 => write!(f, "DirectX 11"),
+        }
+    }
+}
+
+impl fmt::Display for AfterLaunch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AfterLaunch::Exit => write!(f, "Close launcher"),
+            AfterLaunch::Minimize => write!(f, "Minimize launcher"),
+            AfterLaunch::Hide => write!(f, "Hide launcher"),
+        }
+    }
+}
+
+impl fmt::Display for ShadowMapSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShadowMapSize::Size1536 => write!(f, "1536"),
+            ShadowMapSize::Size2048 => write!(f, "2048"),
+            ShadowMapSize::Size2560 => write!(f, "2560"),
+            ShadowMapSize::Size3072 => write!(f, "3072"),
+            ShadowMapSize::Size4096 => write!(f, "4096"),
+        }
+    }
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            launch: LaunchSettings::default(),
+            profile: None,
+            profiles: BTreeMap::new(),
+            version: Some("1.0.0".to_string()),
+            update_url: None,
+            update_public_key: None,
+            executables: Vec::new(),
+            runner: RunnerConfig::default(),
+            mo2: Mo2Config::default(),
+            save_backups: SaveBackupConfig::default(),
+            shader_cache: ShaderCacheConfig::default(),
+            after_launch: AfterLaunch::Exit,
+            layers: None,
+        }
+    }
+}
+
+impl Default for LaunchSettings {
+    fn default() -> Self {
+        LaunchSettings::for_cpu(&CpuFeatures::detect())
+    }
+}
+
+impl LaunchSettings {
+    /// Default settings, with the AVX build selected when `cpu` can run it.
+    pub fn for_cpu(cpu: &CpuFeatures) -> Self {
         Self {
             renderer: Renderer::DX10,
             shadow_map: ShadowMapSize::Size2048,
             debug: true,
-            use_avx: true,
+            use_avx: cpu.supports_avx_build(),
             prefetch_sounds: false,
-            version: Some("1.0.0".to_string()),
-            update_url: None,
+            flags: LaunchFlags::default(),
+            extra_args: String::new(),
+        }
+    }
+
+    /// Whether the AVX build is selected but `cpu` can't run it, so launching would crash.
+    pub fn avx_unsupported(&self, cpu: &CpuFeatures) -> bool {
+        self.use_avx && !cpu.supports_avx_build()
+    }
+
+    /// Command-line arguments passed to the game executable for these settings.
+    pub fn launch_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = Vec::new();
+        let shadows_arg: String = match self.shadow_map {
+            ShadowMapSize::Size1536 => "-smap1536".to_string(),
+            ShadowMapSize::Size2048 => "-smap2048".to_string(),
+            ShadowMapSize::Size2560 => "-smap2560".to_string(),
+            ShadowMapSize::Size3072 => "-smap3072".to_string(),
+            ShadowMapSize::Size4096 => "-smap4096".to_string(),
+        };
+        args.push(shadows_arg);
+        if self.debug {
+            args.push("-dbg".to_string());
         }
+
+        if self.prefetch_sounds {
+            args.push("-prefetch_sounds".to_string());
+        }
+
+        args.extend(self.flags.args());
+        args.extend(launch_args::split_args(&self.extra_args));
+        args
     }
 }
 
 impl AppConfig {
+    /// Where `launcherconfig.toml` is for the game folder in the current directory.
+    pub fn location() -> ConfigLocation {
+        ConfigLocation::detect(Path::new("."))
+    }
+
+    /// Reads `launcherconfig.toml` from [`AppConfig::location`] and layers it
+    /// with the modpack defaults and environment overrides, see [`crate::layers`].
+    /// A missing file leaves just the defaults; a file older launchers left in
+    /// the game folder is imported first.
+    ///
+    /// Files from older launchers are upgraded and rewritten, keeping the
+    /// original as `launcherconfig.toml.v<N>.bak`. When the file is damaged but
+    /// `launcherconfig.toml.bak` is fine, the backup is restored and the damaged
+    /// file is kept as `launcherconfig.toml.broken`.
     pub fn load() -> Result<Self, AppConfigError> {
-        if let Ok(file_data) = fs::read_to_string("launcherconfig.toml") {
-            if let Ok(config) = toml::from_str::<AppConfig>(&file_data) {
-                Ok(config)
-            } else {
-                Err(AppConfigError::BadStructure)
+        AppConfig::load_with(&[])
+    }
+
+    /// [`AppConfig::load`] with `key = value` overrides from the command line on top.
+    pub fn load_with(overrides: &[(String, String)]) -> Result<Self, AppConfigError> {
+        let location = AppConfig::location();
+        location.import_legacy(Path::new(".")).map_err(|e| AppConfigError::ReadFailed(format!("failed to import the game folder's config: {}", e)))?;
+        let user = AppConfig::read_user_table(location.path())?;
+        let (mut config, layers) = layers::resolve(Path::new("."), user, env::vars(), overrides)?;
+        config.layers = Some(Arc::new(layers));
+        Ok(config)
+    }
+
+    fn read_user_table(path: &Path) -> Result<toml::Table, AppConfigError> {
+        let err = match AppConfig::read(path) {
+            Ok(table) => return Ok(table),
+            Err(err) => err,
+        };
+        let Ok(table) = AppConfig::read(&fs_util::with_suffix(path, ".bak")) else {
+            return if path.exists() { Err(err) } else { Ok(toml::Table::new()) };
+        };
+        if path.exists() {
+            let _ = fs::rename(path, fs_util::with_suffix(path, ".broken"));
+        }
+        let _ = write_table(path, &table);
+        Ok(table)
+    }
+
+    fn read(path: &Path) -> Result<toml::Table, AppConfigError> {
+        let file_data = fs::read_to_string(path).map_err(|e| AppConfigError::ReadFailed(e.to_string()))?;
+        let (table, migrated_from) = AppConfig::parse_table(&file_data)?;
+        if let Some(from) = migrated_from {
+            let backup = fs_util::with_suffix(path, &format!(".v{}.bak", from));
+            // Never rewrite the old file without a backup
+            if fs::copy(path, backup).is_ok() {
+                let _ = write_table(path, &table);
             }
-        } else {
-            Err(AppConfigError::ReadFailed)
         }
+        Ok(table)
     }
 
-    pub fn write(&self) -> Result<(), AppConfigError> {
-        let string_config = toml::to_string(self).unwrap();
-        if fs::write("launcherconfig.toml", string_config).is_err() {
-            return Err(AppConfigError::WriteFailed);
+    /// Parses a config file, migrating it to the current schema. Also returns
+    /// the schema version it was migrated from, if it was.
+    pub fn parse(content: &str) -> Result<(Self, Option<u32>), AppConfigError> {
+        let (table, migrated_from) = AppConfig::parse_table(content)?;
+        let config = table.try_into::<AppConfig>().map_err(|e| AppConfigError::bad_structure(None, e))?;
+        Ok((config, migrated_from))
+    }
+
+    /// The migrated table of a config file, checked to deserialize.
+    fn parse_table(content: &str) -> Result<(toml::Table, Option<u32>), AppConfigError> {
+        let mut table: toml::Table = toml::from_str(content).map_err(|e| AppConfigError::bad_structure(Some(content), e))?;
+        let migrated_from = migrations::migrate(&mut table);
+        match migrated_from {
+            // Migrated values no longer line up with the file, so errors carry no position
+            Some(_) => {
+                toml::Value::Table(table.clone()).try_into::<AppConfig>().map_err(|e| AppConfigError::bad_structure(None, e))?;
+            }
+            None => {
+                toml::from_str::<AppConfig>(content).map_err(|e| {
+                    // Errors inside the flattened launch settings point at the whole document,
+                    // parsing them on their own gives the real position
+                    let e = toml::from_str::<LaunchSettings>(content).err().unwrap_or(e);
+                    AppConfigError::bad_structure(Some(content), e)
+                })?;
+            }
+        }
+        Ok((table, migrated_from))
+    }
+
+    /// Changes one dotted key, e.g. `runner.kind`, parsing `raw` as a TOML value.
+    pub fn set_value(&mut self, key: &str, raw: &str) -> Result<toml::Value, AppConfigError> {
+        let mut table = layers::to_table(self)?;
+        let value = layers::parse_value(raw);
+        let invalid = |message: String| AppConfigError::BadOverride { source: format!("'{}'", key), message };
+        if !layers::insert(&mut table, key, value.clone()) {
+            return Err(invalid("the key does not point into a table".to_string()));
+        }
+        let updated: AppConfig = toml::Value::Table(table).try_into().map_err(|e| invalid(e.message().trim().to_string()))?;
+        if !layers::is_known_key(&updated, key) {
+            return Err(invalid("it is not a configuration key".to_string()));
+        }
+        let mut updated = AppConfig { layers: self.layers.clone(), ..updated };
+        if let Some(name) = updated.profile.clone().filter(|_| key == "profile") {
+            // Same rules as picking the profile in the launcher
+            updated.select_profile(&name).map_err(|e| invalid(format!("{} (available: {})", e, updated.profile_names().join(", "))))?;
+        }
+        *self = updated;
+        Ok(value)
+    }
+
+    /// Command-line arguments for the active profile.
+    pub fn launch_args(&self) -> Vec<String> {
+        self.active().launch_args()
+    }
+
+    /// Name of the active profile.
+    pub fn profile_name(&self) -> &str {
+        match &self.profile {
+            Some(name) if self.profiles.contains_key(name) => name,
+            _ => DEFAULT_PROFILE,
+        }
+    }
+
+    /// The default profile followed by the named ones.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names = vec![DEFAULT_PROFILE.to_string()];
+        names.extend(self.profiles.keys().cloned());
+        names
+    }
+
+    /// Settings of the active profile. An unknown `profile` falls back to the default one.
+    pub fn active(&self) -> &LaunchSettings {
+        self.profile.as_ref().and_then(|name| self.profiles.get(name)).unwrap_or(&self.launch)
+    }
+
+    pub fn active_mut(&mut self) -> &mut LaunchSettings {
+        match self.profile.as_ref().and_then(|name| self.profiles.get_mut(name)) {
+            Some(settings) => settings,
+            None => &mut self.launch,
+        }
+    }
+
+    fn settings(&self, name: &str) -> Result<&LaunchSettings, ProfileError> {
+        if name == DEFAULT_PROFILE {
+            return Ok(&self.launch);
+        }
+        self.profiles.get(name).ok_or_else(|| ProfileError::NotFound(name.to_string()))
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<(), ProfileError> {
+        if name.trim().is_empty() || name.trim() != name {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        if name.eq_ignore_ascii_case(DEFAULT_PROFILE) || self.profiles.contains_key(name) {
+            return Err(ProfileError::AlreadyExists(name.to_string()));
         }
         Ok(())
     }
+
+    /// Makes `name` the active profile.
+    pub fn select_profile(&mut self, name: &str) -> Result<(), ProfileError> {
+        self.settings(name)?;
+        self.profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+        Ok(())
+    }
+
+    /// Adds a profile with the launcher's default settings.
+    pub fn create_profile(&mut self, name: &str) -> Result<(), ProfileError> {
+        self.check_new_name(name)?;
+        self.profiles.insert(name.to_string(), LaunchSettings::default());
+        Ok(())
+    }
+
+    /// Adds `name` as a copy of the profile `from`.
+    pub fn duplicate_profile(&mut self, from: &str, name: &str) -> Result<(), ProfileError> {
+        let settings = self.settings(from)?.clone();
+        self.check_new_name(name)?;
+        self.profiles.insert(name.to_string(), settings);
+        Ok(())
+    }
+
+    /// Renames a named profile, keeping it active if it was.
+    pub fn rename_profile(&mut self, from: &str, name: &str) -> Result<(), ProfileError> {
+        if from == DEFAULT_PROFILE {
+            return Err(ProfileError::InvalidName(from.to_string()));
+        }
+        if !self.profiles.contains_key(from) {
+            return Err(ProfileError::NotFound(from.to_string()));
+        }
+        self.check_new_name(name)?;
+        if let Some(settings) = self.profiles.remove(from) {
+            self.profiles.insert(name.to_string(), settings);
+        }
+        if self.profile.as_deref() == Some(from) {
+            self.profile = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Deletes a named profile; the default profile becomes active if it was.
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), ProfileError> {
+        if name == DEFAULT_PROFILE {
+            return Err(ProfileError::InvalidName(name.to_string()));
+        }
+        self.profiles.remove(name).ok_or_else(|| ProfileError::NotFound(name.to_string()))?;
+        if self.profile.as_deref() == Some(name) {
+            self.profile = None;
+        }
+        Ok(())
+    }
+
+    /// Saves the configuration to `launcherconfig.toml` at [`AppConfig::location`].
+    /// The file is replaced atomically and the previous version is kept as
+    /// `launcherconfig.toml.bak`. For a layered config only the user's own
+    /// values are written, not the defaults or overrides.
+    ///
+    /// A file from a newer launcher is never written: its schema version and
+    /// the settings this launcher doesn't know would be lost.
+    pub fn write(&self) -> Result<(), AppConfigError> {
+        if self.schema_version > SCHEMA_VERSION {
+            return Err(AppConfigError::WriteFailed(format!(
+                "it was written by a newer launcher (schema {}, this one knows up to {}); update the launcher to change settings",
+                self.schema_version, SCHEMA_VERSION
+            )));
+        }
+        let string_config = match &self.layers {
+            Some(layers) => {
+                let mut user = layers.user_table(&layers::to_table(self)?);
+                user.insert("schema_version".to_string(), toml::Value::Integer(SCHEMA_VERSION.into()));
+                toml::to_string(&user)
+            }
+            None => toml::to_string(self),
+        };
+        write_string(AppConfig::location().path(), &string_config.map_err(|e| AppConfigError::WriteFailed(e.to_string()))?)
+    }
+}
+
+fn write_table(path: &Path, table: &toml::Table) -> Result<(), AppConfigError> {
+    write_string(path, &toml::to_string(table).map_err(|e| AppConfigError::WriteFailed(e.to_string()))?)
+}
+
+fn write_string(path: &Path, string_config: &str) -> Result<(), AppConfigError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| AppConfigError::WriteFailed(e.to_string()))?;
+    }
+    // A damaged file must not replace the last good backup
+    let previous_is_valid = fs::read_to_string(path).is_ok_and(|content| AppConfig::parse(&content).is_ok());
+    if previous_is_valid {
+        fs::copy(path, fs_util::with_suffix(path, ".bak")).map_err(|e| AppConfigError::WriteFailed(e.to_string()))?;
+    }
+    fs_util::write_atomic(path, string_config.as_bytes()).map_err(|e| AppConfigError::WriteFailed(e.to_string()))
 }
@@ -0,0 +1,237 @@
+//! Builds `patch-X.Y.Z.zip` archives by diffing two modpack trees and records
+//! them in the update manifest consumed by the launcher's updater.
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use anomaly_launcher::{
+    fs_util,
+    manifest::{self, Manifest, ManifestPatch, PatchContents, PATCH_INFO_FILE},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::{Args, Parser, Subcommand};
+use ed25519_dalek::SigningKey;
+use rand_core::OsRng;
+use semver::Version;
+use zip::{write::FileOptions, CompressionMethod, DateTime, ZipWriter};
+
+#[derive(Parser)]
+#[command(name = "patch-publisher", version, about = "Generate Dead Reckoning patches and update manifests")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new signing key pair
+    Keygen {
+        /// File the secret key is written to
+        #[arg(long, default_value = "publisher.key")]
+        out: PathBuf,
+    },
+    /// Diff two modpack trees, write the patch archive and update the manifest
+    Build(Box<BuildArgs>),
+}
+
+#[derive(Args)]
+struct BuildArgs {
+    /// Modpack tree of the previous release
+    #[arg(long)]
+    old: PathBuf,
+    /// Modpack tree of the new release
+    #[arg(long)]
+    new: PathBuf,
+    /// Version of the new release
+    #[arg(long)]
+    version: Version,
+    /// Version of the previous release, recorded in the patch
+    #[arg(long)]
+    from: Option<Version>,
+    /// Secret key used to sign the patch
+    #[arg(long)]
+    key: Option<PathBuf>,
+    /// Folder the patch archive is written to
+    #[arg(long, default_value = "dist")]
+    out: PathBuf,
+    /// Manifest to update, created when missing
+    #[arg(long, default_value = "dist/manifest.toml")]
+    manifest: PathBuf,
+    /// URL prefix the patch archive will be served from; by default the
+    /// archive is expected next to the manifest
+    #[arg(long)]
+    base_url: Option<String>,
+    /// Relative paths to leave out of the diff (repeatable)
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Mark the release as unable to load saves made before it
+    #[arg(long)]
+    breaks_saves: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Keygen { out } => keygen(&out),
+        Command::Build(args) => build(*args),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
+
+fn keygen(out: &Path) -> Result<(), String> {
+    let key = SigningKey::generate(&mut OsRng);
+    // create_new refuses an existing key; on Unix only the owner can read the new one
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(out).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => format!("{} already exists", out.display()),
+        _ => format!("Failed to create {}: {}", out.display(), e),
+    })?;
+    file.write_all(BASE64.encode(key.to_bytes()).as_bytes()).map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    println!("Secret key written to {}. Keep it out of the modpack.", out.display());
+    println!("Public key for update_public_key in launcherconfig.toml:");
+    println!("{}", BASE64.encode(key.verifying_key().to_bytes()));
+    Ok(())
+}
+
+fn build(options: BuildArgs) -> Result<(), String> {
+    let old_files = hash_tree(&options.old, &options.exclude)?;
+    let new_files = hash_tree(&options.new, &options.exclude)?;
+
+    let mut contents = PatchContents {
+        version: Some(options.version.clone()),
+        from: options.from.clone(),
+        ..Default::default()
+    };
+    for (path, hash) in &new_files {
+        if old_files.get(path) != Some(hash) {
+            contents.files.insert(path.clone(), hash.clone());
+        }
+    }
+    contents.deleted = old_files.keys().filter(|path| !new_files.contains_key(*path)).cloned().collect();
+
+    if contents.files.is_empty() && contents.deleted.is_empty() {
+        return Err("The trees are identical, nothing to publish".to_string());
+    }
+    eprintln!("{} changed or added, {} deleted", contents.files.len(), contents.deleted.len());
+
+    fs::create_dir_all(&options.out).map_err(|e| format!("Failed to create {}: {}", options.out.display(), e))?;
+    let file_name = format!("patch-{}.zip", options.version);
+    let patch_path = options.out.join(&file_name);
+    write_archive(&patch_path, &options.new, &contents)?;
+
+    let sha256 = manifest::sha256_file(&patch_path).map_err(|e| format!("Failed to hash {}: {}", patch_path.display(), e))?;
+    let signature = match &options.key {
+        Some(key_path) => {
+            let secret = fs::read_to_string(key_path).map_err(|e| format!("Failed to read {}: {}", key_path.display(), e))?;
+            Some(manifest::sign_digest(&secret, &sha256)?)
+        }
+        None => {
+            eprintln!("warning: no --key given, the patch is not signed");
+            None
+        }
+    };
+
+    let mut update_manifest = match fs::read_to_string(&options.manifest) {
+        Ok(content) => Manifest::parse(&content).map_err(|e| format!("Failed to parse {}: {}", options.manifest.display(), e.message().trim()))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Manifest::default(),
+        Err(err) => return Err(format!("Failed to read {}: {}", options.manifest.display(), err)),
+    };
+    let url = match &options.base_url {
+        Some(base) => format!("{}/{}", base.trim_end_matches('/'), file_name),
+        None => file_name.clone(),
+    };
+    update_manifest.upsert(ManifestPatch {
+        version: options.version.clone(),
+        url,
+        sha256: Some(sha256.clone()),
+        signature,
+        breaks_saves: options.breaks_saves,
+    });
+
+    let manifest_text = update_manifest.to_toml().map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    if let Some(parent) = options.manifest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs_util::write_atomic(&options.manifest, manifest_text.as_bytes()).map_err(|e| format!("Failed to write {}: {}", options.manifest.display(), e))?;
+
+    println!("{}  {}", sha256, patch_path.display());
+    println!("Manifest updated: {}", options.manifest.display());
+    Ok(())
+}
+
+/// Hashes every file below `root`, keyed by its `/`-separated relative path.
+fn hash_tree(root: &Path, exclude: &[String]) -> Result<BTreeMap<String, String>, String> {
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let mut files = BTreeMap::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+            let path = entry.path();
+            let relative = relative_name(root, &path);
+            if exclude.iter().any(|prefix| relative == *prefix || relative.starts_with(&format!("{}/", prefix))) {
+                continue;
+            }
+            // Following links could loop forever, and an archive can't hold them anyway
+            let file_type = entry.file_type().map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if file_type.is_symlink() {
+                return Err(format!("{} is a symbolic link, replace it with the file or exclude it", path.display()));
+            }
+            if file_type.is_dir() {
+                pending.push(path);
+            } else {
+                let hash = manifest::sha256_file(&path).map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+                files.insert(relative, hash);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn relative_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Writes files in sorted order with fixed timestamps so the same trees always produce the same archive.
+fn write_archive(path: &Path, root: &Path, contents: &PatchContents) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default())
+        .unix_permissions(0o644);
+
+    let info = toml::to_string_pretty(contents).map_err(|e| format!("Failed to serialize patch info: {}", e))?;
+    zip.start_file(PATCH_INFO_FILE, options).map_err(|e| e.to_string())?;
+    zip.write_all(info.as_bytes()).map_err(|e| e.to_string())?;
+
+    for name in contents.files.keys() {
+        zip.start_file(name.as_str(), options).map_err(|e| e.to_string())?;
+        let mut source = File::open(root.join(name)).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+        io::copy(&mut source, &mut zip).map_err(|e| format!("Failed to compress {}: {}", name, e))?;
+    }
+
+    zip.finish().map_err(|e| format!("Failed to finish {}: {}", path.display(), e))?;
+    Ok(())
+}
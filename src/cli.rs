@@ -0,0 +1,987 @@
+use std::{
+    env,
+    io::{self, Write},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use semver::Version;
+use serde_json::{json, Value};
+
+use anomaly_launcher::{
+    addons::{self, AddonStatus, InstalledAddons},
+    conflicts, crash_analyzer, diagnostics as bundle, layers, modlist,
+    save_backup::{self, BackupReason},
+    saves,
+    session::SESSION_LOG,
+    shader_cache,
+    system_info::CpuFeatures,
+    AppConfig, CrashRules, Game, GameError, Layer, Mo2Instance, ModList, Renderer, SaveBreakChoice,
+    ShaderCacheError, UpdateProgress, Updater, UpdaterError,
+};
+
+/// Exit codes returned by the command-line interface.
+/// Code 2 is left to clap, which uses it for invalid arguments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitStatus {
+    Success = 0,
+    Failure = 1,
+    ConfigError = 3,
+    NotFound = 4,
+    NetworkError = 5,
+    GameCrashed = 6,
+    UpdatesAvailable = 10,
+}
+
+#[derive(Parser)]
+#[command(name = "AnomalyLauncher", version, about = "Dead Reckoning launcher for S.T.A.L.K.E.R. Anomaly")]
+pub struct Cli {
+    /// Print the result as a single JSON object on stdout
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Override a configuration value for this run only, e.g. `--set renderer=DX11`
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_override)]
+    overrides: Vec<(String, String)>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Launch the game with the configured renderer and flags
+    Launch {
+        /// Wait for the game to exit and report its exit code and runtime
+        #[arg(long)]
+        wait: bool,
+        /// Print the command line without starting the game
+        #[arg(long, conflicts_with = "wait")]
+        dry_run: bool,
+        /// Launch profile to use instead of the selected one
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Download and apply every patch newer than the installed version
+    Update {
+        /// Install patches that break existing saves without stopping
+        #[arg(long)]
+        allow_save_break: bool,
+    },
+    /// List patches newer than the installed version without installing them
+    Check,
+    /// Verify that the game folder and configuration are usable
+    Verify,
+    /// Show the size of the shader cache per renderer
+    ShaderCache,
+    /// Delete the shader cache
+    ClearShaderCache {
+        /// Only delete the cache of one renderer: DX8, DX9, DX10 or DX11
+        #[arg(long, value_parser = parse_renderer)]
+        renderer: Option<Renderer>,
+    },
+    /// Show the detected processor features
+    SystemInfo,
+    /// Zip the latest logs, crash dumps and settings for a bug report
+    Diagnostics,
+    /// Explain the crash in an X-Ray log, the latest one by default
+    AnalyzeLog { path: Option<PathBuf> },
+    /// Show the Mod Organizer 2 instance the game is launched through
+    Mo2,
+    /// List gamedata files and DLTX sections that several mods change
+    Conflicts {
+        /// Also save the report to the diagnostics folder
+        #[arg(long)]
+        export: bool,
+    },
+    /// Compare the MO2 profile's mod list with the modpack's reference list
+    Modlist {
+        /// Replace the mod list with the reference list, disabling mods added on top
+        #[arg(long)]
+        restore: bool,
+    },
+    /// List, delete, export or import saves
+    Saves {
+        #[command(subcommand)]
+        action: SavesAction,
+    },
+    /// Back up the saves or restore a backup
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// List, install or remove the optional addons from the update server
+    Addons {
+        #[command(subcommand)]
+        action: AddonAction,
+    },
+    /// Read or change the launcher configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print one value, or the whole configuration when no key is given
+    Get {
+        key: Option<String>,
+        /// Also show which layer each value comes from
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Set a value; it is parsed as TOML and falls back to a plain string
+    Set { key: String, value: String },
+    /// Show where launcherconfig.toml is stored
+    Path,
+}
+
+#[derive(Subcommand)]
+enum SavesAction {
+    /// Show the saves, newest first, with the modpack version they were made with
+    List,
+    /// Delete a save and its thumbnail
+    Delete { name: String },
+    /// Zip a save and its thumbnail
+    Export {
+        name: String,
+        /// Archive to write, `<name>.zip` in the current directory by default
+        path: Option<PathBuf>,
+    },
+    /// Copy a .scop save or an exported archive into the game
+    Import { path: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum BackupAction {
+    /// Show the save backups, newest first
+    List,
+    /// Back up the saves now
+    Create,
+    /// Replace the saves with a backup; the current saves are backed up first
+    Restore {
+        /// File name of the backup, as shown by `backup list`
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AddonAction {
+    /// Show the addon catalog and what is installed
+    List,
+    /// Install an addon into Mod Organizer 2, or update it
+    Install { id: String },
+    /// Delete an installed addon
+    Remove { id: String },
+}
+
+struct Output {
+    json: bool,
+    overrides: Vec<(String, String)>,
+}
+
+impl Output {
+    fn success(&self, text: &str, data: Value) -> ExitStatus {
+        self.finish(ExitStatus::Success, text, data)
+    }
+
+    fn finish(&self, status: ExitStatus, text: &str, data: Value) -> ExitStatus {
+        if self.json {
+            let mut object = json!({ "ok": status == ExitStatus::Success || status == ExitStatus::UpdatesAvailable, "exit_code": status as i32 });
+            if let (Some(object), Value::Object(data)) = (object.as_object_mut(), data) {
+                object.extend(data);
+            }
+            println!("{}", object);
+        } else if !text.is_empty() {
+            println!("{}", text);
+        }
+        status
+    }
+
+    fn error(&self, status: ExitStatus, message: &str) -> ExitStatus {
+        if self.json {
+            println!("{}", json!({ "ok": false, "exit_code": status as i32, "error": message }));
+        } else {
+            eprintln!("error: {}", message);
+        }
+        status
+    }
+}
+
+/// Parses the command line and runs the requested subcommand.
+pub fn run() -> i32 {
+    attach_console();
+    let cli = Cli::parse();
+    let out = Output { json: cli.json, overrides: cli.overrides };
+
+    let status = match cli.command {
+        Command::Launch { wait, dry_run, profile } => with_config(&out, |config| launch(&out, config, wait, dry_run, profile.as_deref())),
+        Command::Update { allow_save_break } => with_config(&out, |config| update(&out, config, allow_save_break)),
+        Command::Check => with_config(&out, |config| check(&out, config)),
+        Command::Verify => verify(&out),
+        Command::ShaderCache => with_config(&out, |config| shader_cache_usage(&out, config)),
+        Command::ClearShaderCache { renderer } => with_config(&out, |config| clear_shader_cache(&out, config, renderer)),
+        Command::SystemInfo => system_info(&out),
+        Command::Diagnostics => with_config(&out, |config| diagnostics(&out, config)),
+        Command::AnalyzeLog { path } => analyze_log(&out, path),
+        Command::Mo2 => with_config(&out, |config| mo2(&out, config)),
+        Command::Modlist { restore } => with_config(&out, |config| mod_list(&out, config, restore)),
+        Command::Conflicts { export } => with_config(&out, |config| mod_conflicts(&out, config, export)),
+        Command::Saves { action } => with_config(&out, |config| saves_command(&out, config, action)),
+        Command::Backup { action } => with_config(&out, |config| backup(&out, config, action)),
+        Command::Addons { action } => with_config(&out, |config| addons(&out, config, action)),
+        Command::Config { action: ConfigAction::Get { key, origin } } => with_config(&out, |config| config_get(&out, config, key.as_deref(), origin)),
+        Command::Config { action: ConfigAction::Set { key, value } } => config_set(&out, &key, &value),
+        Command::Config { action: ConfigAction::Path } => config_path(&out),
+    };
+    status as i32
+}
+
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // Release builds use the GUI subsystem, so output only shows up after attaching
+    // to the console of the shell that started us.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn parse_override(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err("expected KEY=VALUE".to_string()),
+    }
+}
+
+fn parse_renderer(arg: &str) -> Result<Renderer, String> {
+    shader_cache::RENDERERS
+        .into_iter()
+        .find(|renderer| format!("{:?}", renderer).eq_ignore_ascii_case(arg) || shader_cache::folder(*renderer) == arg)
+        .ok_or_else(|| "expected DX8, DX9, DX10 or DX11".to_string())
+}
+
+/// Runs `command` with the configuration, or reports why it couldn't be loaded.
+fn with_config(out: &Output, command: impl FnOnce(AppConfig) -> ExitStatus) -> ExitStatus {
+    match AppConfig::load_with(&out.overrides) {
+        Ok(config) => command(config),
+        Err(err) => out.error(ExitStatus::ConfigError, &err.to_string()),
+    }
+}
+
+fn launch(out: &Output, mut config: AppConfig, wait: bool, dry_run: bool, profile: Option<&str>) -> ExitStatus {
+    if let Some(profile) = profile {
+        if let Err(err) = config.select_profile(profile) {
+            let names = config.profile_names().join(", ");
+            return out.error(ExitStatus::ConfigError, &format!("{} (available: {})", err, names));
+        }
+    }
+    if config.active().avx_unsupported(&CpuFeatures::detect()) {
+        return out.error(
+            ExitStatus::ConfigError,
+            "the AVX build is selected but this processor does not support AVX; run `config set use_avx false`",
+        );
+    }
+    let game = Game::new(&config);
+    let args = config.launch_args();
+    if dry_run {
+        let command_line = game.command_line(&args);
+        return out.success(&command_line, json!({ "profile": config.profile_name(), "executable": game.path(), "args": args, "command_line": command_line }));
+    }
+    if let Some((save, record)) = saves::outdated_latest_save(Path::new(".")) {
+        eprintln!("warning: the latest save '{}' was made before version {}, which can't load it", save.name, record.version);
+    }
+    match shader_cache::prepare_launch(Path::new("."), &config.mo2, config.active().renderer, &config.shader_cache) {
+        Ok(true) => eprintln!("The renderer has changed, the shader cache has been cleared"),
+        Ok(false) => {}
+        Err(err) => eprintln!("warning: {}", err),
+    }
+    eprintln!("Launching {} {}", game.path().display(), args.join(" "));
+
+    match game.spawn(args.clone()) {
+        Ok(session) if wait => {
+            let record = match session.wait() {
+                Ok(record) => record,
+                Err(err) => return out.error(ExitStatus::Failure, &format!("failed to wait for the game: {}", err)),
+            };
+            let _ = record.append_to_log(Path::new(SESSION_LOG));
+            let _ = saves::track(Path::new("."), config.version.as_deref());
+            let data = json!({
+                "executable": game.path(),
+                "args": args,
+                "program": record.program,
+                "exit_code": record.exit_code,
+                "runtime_secs": record.runtime.as_secs(),
+                "crashed": record.is_abnormal(),
+            });
+            if record.is_abnormal() {
+                out.finish(ExitStatus::GameCrashed, &format!("The game crashed ({}).", record.summary()), data)
+            } else {
+                out.success(&format!("The game exited normally ({}).", record.summary()), data)
+            }
+        },
+        Ok(_) => out.success(
+            "Game started.",
+            json!({ "profile": config.profile_name(), "executable": game.path(), "args": args }),
+        ),
+        Err(GameError::ExecutableNotFound) => out.error(
+            ExitStatus::NotFound,
+            &format!("game executable not found: {}", game.path().display()),
+        ),
+        Err(GameError::Unknown(err)) => out.error(ExitStatus::Failure, &format!("failed to launch the game: {}", err)),
+    }
+}
+
+fn report_progress(progress: UpdateProgress) {
+    let mut stderr = io::stderr();
+    match progress {
+        UpdateProgress::CheckingForUpdates => {
+            let _ = writeln!(stderr, "Checking for updates...");
+        }
+        UpdateProgress::UpdatesAvailable(patches) => {
+            let _ = writeln!(stderr, "Update list contains {} patch(es)", patches.len());
+        }
+        UpdateProgress::BackingUpSaves => {
+            let _ = writeln!(stderr, "Backing up saves...");
+        }
+        UpdateProgress::Downloading { version, progress, .. } => {
+            let _ = write!(stderr, "\rDownloading {}: {:>3.0}%", version, progress * 100.0);
+            if progress >= 1.0 {
+                let _ = writeln!(stderr);
+            }
+        }
+        UpdateProgress::Extracting { current, total, version } => {
+            let _ = write!(stderr, "\rExtracting {}: {}/{}", version, current, total);
+            if current == total {
+                let _ = writeln!(stderr);
+            }
+        }
+        UpdateProgress::Complete => {
+            let _ = writeln!(stderr, "Update complete.");
+        }
+        UpdateProgress::Error(err) => {
+            let _ = writeln!(stderr, "Update error: {}", err);
+        }
+    }
+}
+
+fn updater_error(out: &Output, err: UpdaterError) -> ExitStatus {
+    let status = match err {
+        UpdaterError::NetworkError(_) => ExitStatus::NetworkError,
+        UpdaterError::NoUpdateUrlConfigured | UpdaterError::VersionParseError(_) => ExitStatus::ConfigError,
+        _ => ExitStatus::Failure,
+    };
+    out.error(status, &err.to_string())
+}
+
+fn update(out: &Output, config: AppConfig, allow_save_break: bool) -> ExitStatus {
+    let mut updater = match Updater::new(config.clone()) {
+        Ok(updater) => updater,
+        Err(err) => return updater_error(out, err),
+    };
+
+    let mut blocked = None;
+    let result = updater.update_with_confirmation(report_progress, |warning| {
+        if allow_save_break {
+            return SaveBreakChoice::Update;
+        }
+        blocked = Some(warning.clone());
+        SaveBreakChoice::Cancel
+    });
+    if let (Err(UpdaterError::Cancelled), Some(warning)) = (&result, &blocked) {
+        let versions: Vec<String> = warning.versions.iter().map(Version::to_string).collect();
+        let names: Vec<&str> = warning.saves.iter().map(|save| save.name.as_str()).collect();
+        return out.error(
+            ExitStatus::Failure,
+            &format!(
+                "version {} can't load existing saves ({}); back them up with `backup create` and rerun with --allow-save-break",
+                versions.join(", "),
+                names.join(", ")
+            ),
+        );
+    }
+    match result {
+        Ok(new_version) => {
+            let mut updated_config = config;
+            updated_config.version = Some(new_version.clone());
+            if let Err(err) = updated_config.write() {
+                return out.error(ExitStatus::ConfigError, &format!("updated to {} but: {}", new_version, err));
+            }
+            out.success(
+                &format!("Updated to version {}", new_version),
+                json!({ "updated": true, "version": new_version }),
+            )
+        }
+        Err(UpdaterError::NoUpdatesAvailable) => out.success(
+            "You are already running the latest version.",
+            json!({ "updated": false, "version": config.version }),
+        ),
+        Err(err) => updater_error(out, err),
+    }
+}
+
+fn check(out: &Output, config: AppConfig) -> ExitStatus {
+    let current_version = match config.version.as_deref().map(Version::parse) {
+        Some(Ok(version)) => version,
+        _ => return out.error(ExitStatus::ConfigError, "installed version is missing or invalid"),
+    };
+    let updater = match Updater::new(config) {
+        Ok(updater) => updater,
+        Err(err) => return updater_error(out, err),
+    };
+    report_progress(UpdateProgress::CheckingForUpdates);
+
+    let patches = match updater.check_for_updates() {
+        Ok(patches) => patches,
+        Err(UpdaterError::NoUpdatesAvailable) => Vec::new(),
+        Err(err) => return updater_error(out, err),
+    };
+    let pending: Vec<_> = patches.into_iter().filter(|patch| patch.version > current_version).collect();
+
+    let data = json!({
+        "current_version": current_version.to_string(),
+        "updates": pending.iter().map(|p| json!({ "version": p.version.to_string(), "url": p.download_url, "breaks_saves": p.breaks_saves })).collect::<Vec<_>>(),
+    });
+    if pending.is_empty() {
+        return out.success("You are already running the latest version.", data);
+    }
+
+    let mut text = format!("{} update(s) available for {}:", pending.len(), current_version);
+    for patch in &pending {
+        text.push_str(&format!("\n  {}  {}", patch.version, patch.download_url));
+        if patch.breaks_saves {
+            text.push_str("  (breaks saves)");
+        }
+    }
+    out.finish(ExitStatus::UpdatesAvailable, &text, data)
+}
+
+fn verify(out: &Output) -> ExitStatus {
+    let mut checks: Vec<(&str, bool, String)> = Vec::new();
+    let mut status = ExitStatus::Success;
+
+    let location = AppConfig::location();
+    let found = location.path().exists();
+    let config = match AppConfig::load_with(&out.overrides) {
+        Ok(config) if found => {
+            checks.push(("config", true, format!("{} is valid", location.path().display())));
+            config
+        }
+        Ok(config) => {
+            checks.push(("config", true, format!("{} not found, using defaults", location.path().display())));
+            config
+        }
+        Err(err) => {
+            checks.push(("config", false, err.to_string()));
+            status = ExitStatus::ConfigError;
+            AppConfig::default()
+        }
+    };
+
+    match config.version.as_deref().map(Version::parse) {
+        Some(Ok(version)) => checks.push(("version", true, format!("installed version {}", version))),
+        _ => {
+            checks.push(("version", false, "installed version is missing or not a semantic version".to_string()));
+            if status == ExitStatus::Success {
+                status = ExitStatus::ConfigError;
+            }
+        }
+    }
+
+    let cpu = CpuFeatures::detect();
+    if config.active().avx_unsupported(&cpu) {
+        checks.push(("cpu", false, "AVX build selected but the processor does not support AVX".to_string()));
+        if status == ExitStatus::Success {
+            status = ExitStatus::ConfigError;
+        }
+    } else {
+        checks.push(("cpu", true, format!("AVX {}", if cpu.avx { "supported" } else { "not supported" })));
+    }
+
+    let game = Game::new(&config);
+    if let Some(profile) = game.mo2_profile() {
+        if let Some(instance) = Mo2Instance::detect(&env::current_dir().unwrap_or_default(), &config.mo2) {
+            let found = instance.profiles().iter().any(|name| name == profile);
+            checks.push(("mo2", found, format!("{} with profile '{}'", instance.dir.display(), profile)));
+            if !found && status == ExitStatus::Success {
+                status = ExitStatus::ConfigError;
+            }
+        }
+    }
+    if game.path().exists() {
+        checks.push(("executable", true, game.path().display().to_string()));
+    } else {
+        checks.push(("executable", false, format!("{} is missing", game.path().display())));
+        status = ExitStatus::NotFound;
+    }
+
+    for dir in ["appdata", "db"] {
+        if Path::new(dir).is_dir() {
+            checks.push((dir, true, format!("'{}' folder found", dir)));
+        } else {
+            checks.push((dir, false, format!("'{}' folder is missing", dir)));
+            status = ExitStatus::NotFound;
+        }
+    }
+
+    let text = checks
+        .iter()
+        .map(|(name, ok, detail)| format!("[{}] {}: {}", if *ok { " OK " } else { "FAIL" }, name, detail))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let data = json!({
+        "checks": checks.iter().map(|(name, ok, detail)| json!({ "name": name, "ok": ok, "detail": detail })).collect::<Vec<_>>(),
+    });
+    out.finish(status, &text, data)
+}
+
+fn shader_cache_usage(out: &Output, config: AppConfig) -> ExitStatus {
+    let usage = match shader_cache::usage(Path::new("."), &config.mo2) {
+        Ok(usage) => usage,
+        Err(ShaderCacheError::NotFound) => return out.error(ExitStatus::NotFound, "shader cache folder not found"),
+        Err(err) => return out.error(ExitStatus::Failure, &err.to_string()),
+    };
+    let mut text = format!("Shader cache: {} KB", usage.total() / 1024);
+    for (renderer, size) in &usage.renderers {
+        text.push_str(&format!("\n  {:<12} {:>8} KB", renderer.to_string(), size / 1024));
+    }
+    for location in &usage.locations {
+        text.push_str(&format!("\n  in {}", location.display()));
+    }
+    let data = json!({
+        "total": usage.total(),
+        "renderers": usage.renderers.iter().map(|(renderer, size)| json!({ "renderer": renderer, "size": size })).collect::<Vec<_>>(),
+        "other": usage.other,
+        "locations": usage.locations,
+    });
+    out.success(&text, data)
+}
+
+fn clear_shader_cache(out: &Output, config: AppConfig, renderer: Option<Renderer>) -> ExitStatus {
+    match shader_cache::clear(Path::new("."), &config.mo2, renderer) {
+        Ok(freed) => {
+            let text = match renderer {
+                Some(renderer) => format!("{} shader cache has been deleted ({} KB freed).", renderer, freed / 1024),
+                None => format!("Shader cache has been deleted ({} KB freed).", freed / 1024),
+            };
+            out.success(&text, json!({ "renderer": renderer, "freed": freed }))
+        }
+        Err(ShaderCacheError::NotFound) => out.error(ExitStatus::NotFound, "shader cache folder not found"),
+        Err(ShaderCacheError::RemoveFailed(err)) => out.error(ExitStatus::Failure, &format!("failed to delete shader cache: {}", err)),
+    }
+}
+
+fn system_info(out: &Output) -> ExitStatus {
+    let cpu = CpuFeatures::detect();
+    let mut text = cpu.brand.clone().unwrap_or_else(|| "Unknown processor".to_string());
+    for (name, supported) in cpu.list() {
+        text.push_str(&format!("\n  {:<8} {}", name, if supported { "yes" } else { "no" }));
+    }
+    out.success(&text, json!({ "cpu": cpu }))
+}
+
+fn diagnostics(out: &Output, config: AppConfig) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let log = bundle::latest_log(&root);
+    match bundle::create_bundle(&root, &config) {
+        Ok(path) => out.success(
+            &format!("Diagnostic bundle saved to {}", path.display()),
+            json!({ "bundle": path, "latest_log": log }),
+        ),
+        Err(err) => out.error(ExitStatus::Failure, &format!("failed to create diagnostic bundle: {}", err)),
+    }
+}
+
+fn analyze_log(out: &Output, path: Option<PathBuf>) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let Some(path) = path.or_else(|| bundle::latest_log(&root)) else {
+        return out.error(ExitStatus::NotFound, "no logs found in appdata/logs");
+    };
+    let content = match fs::read(&path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+        Err(err) => return out.error(ExitStatus::NotFound, &format!("failed to read {}: {}", path.display(), err)),
+    };
+    let rules = match CrashRules::load(&root) {
+        Ok(rules) => rules,
+        Err(err) => return out.error(ExitStatus::ConfigError, &err.to_string()),
+    };
+
+    let Some(report) = crash_analyzer::parse_log(&content) else {
+        return out.success(
+            &format!("{} does not contain a FATAL ERROR.", path.display()),
+            json!({ "log": path, "report": null, "diagnosis": null }),
+        );
+    };
+    let diagnosis = rules.diagnose(&report);
+
+    let mut text = String::new();
+    let location = report.line.map(|line| format!(":{}", line)).unwrap_or_default();
+    let fields = [
+        ("Expression", report.expression.clone()),
+        ("Function", report.function.clone()),
+        ("File", report.file.as_ref().map(|file| format!("{}{}", file, location))),
+        ("Description", report.description.clone()),
+        ("Arguments", report.arguments.clone()),
+        ("Script", report.script.as_ref().map(|(file, line)| format!("{}:{}", file, line))),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value {
+            text.push_str(&format!("{:<12} {}\n", name, value));
+        }
+    }
+    for frame in &report.script_stack {
+        text.push_str(&format!("  {}\n", frame));
+    }
+    match &diagnosis {
+        Some(diagnosis) => {
+            text.push_str(&format!("\nLikely cause: {}", diagnosis.cause));
+            if !diagnosis.fix.is_empty() {
+                text.push_str(&format!("\nFix: {}", diagnosis.fix));
+            }
+        }
+        None => text.push_str("\nNo known cause matches this crash."),
+    }
+    out.success(&text, json!({ "log": path, "report": report, "diagnosis": diagnosis }))
+}
+
+fn mo2(out: &Output, config: AppConfig) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let Some(instance) = Mo2Instance::detect(&root, &config.mo2) else {
+        return out.error(ExitStatus::NotFound, "no Mod Organizer 2 instance found next to the game");
+    };
+    let profiles = instance.profiles();
+    let profile = Game::new(&config).mo2_profile().map(str::to_string);
+
+    let mut text = format!("Instance: {}", instance.dir.display());
+    match &profile {
+        Some(profile) => text.push_str(&format!("\nLaunching with profile: {}", profile)),
+        None => text.push_str("\nLaunching through MO2 is disabled (mo2.enabled = false)"),
+    }
+    text.push_str("\nProfiles:");
+    for name in &profiles {
+        text.push_str(&format!("\n  {}", name));
+    }
+    text.push_str("\nExecutables:");
+    for executable in &instance.executables {
+        text.push_str(&format!("\n  {:<24} {}", executable.title, executable.binary));
+    }
+    out.success(&text, json!({ "instance": instance, "profiles": profiles, "launch_profile": profile }))
+}
+
+fn mod_list(out: &Output, config: AppConfig, restore: bool) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let Some(instance) = Mo2Instance::detect(&root, &config.mo2) else {
+        return out.error(ExitStatus::NotFound, "no Mod Organizer 2 instance found next to the game");
+    };
+    let Some(profile) = instance.resolve_profile(config.mo2.profile.as_deref()) else {
+        return out.error(ExitStatus::NotFound, "the Mod Organizer 2 instance has no profiles");
+    };
+    let path = modlist::modlist_path(&instance.profile_dir(&profile));
+    let list = match ModList::load(&path) {
+        Ok(list) => list,
+        Err(err) => return out.error(ExitStatus::NotFound, &format!("failed to read {}: {}", path.display(), err)),
+    };
+    let reference = match ModList::load(&root.join(modlist::REFERENCE_MODLIST)) {
+        Ok(reference) => reference,
+        Err(err) => return out.error(ExitStatus::NotFound, &format!("failed to read {}: {}", modlist::REFERENCE_MODLIST, err)),
+    };
+    let deviations = modlist::compare(&list, &reference);
+
+    if restore {
+        if deviations.is_empty() {
+            return out.success("The mod list already matches the reference list.", json!({ "profile": profile, "restored": false }));
+        }
+        return match modlist::restore(&list, &reference).save(&path) {
+            Ok(()) => out.success(
+                &format!("Restored the reference mod list for profile '{}' ({} difference(s) undone).", profile, deviations.len()),
+                json!({ "profile": profile, "restored": true, "deviations": deviations }),
+            ),
+            Err(err) => out.error(ExitStatus::Failure, &format!("failed to write {}: {}", path.display(), err)),
+        };
+    }
+
+    let active = list.entries.iter().filter(|entry| entry.is_active()).count();
+    let mut text = format!("Profile '{}': {} of {} mods active", profile, active, list.entries.len());
+    if deviations.is_empty() {
+        text.push_str("\nThe mod list matches the reference list.");
+    }
+    for deviation in &deviations {
+        text.push_str(&format!("\n  {}", deviation));
+    }
+    out.success(&text, json!({ "profile": profile, "mods": list.entries, "deviations": deviations }))
+}
+
+fn saves_command(out: &Output, config: AppConfig, action: SavesAction) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let _ = saves::track(&root, config.version.as_deref());
+    let list = match saves::list(&root) {
+        Ok(list) => list,
+        Err(err) => return out.error(ExitStatus::Failure, &format!("failed to read the saves: {}", err)),
+    };
+    let find = |name: &str| list.iter().find(|save| save.name == name).cloned();
+
+    match action {
+        SavesAction::List => {
+            if list.is_empty() {
+                return out.success("There are no saves yet.", json!({ "saves": [] }));
+            }
+            let mut text = format!("{} save(s):", list.len());
+            for save in &list {
+                text.push_str(&format!(
+                    "\n  {}  {:<10} {:>8} KB  {}",
+                    save.modified.format("%Y-%m-%d %H:%M"),
+                    save.version.as_deref().unwrap_or("unknown"),
+                    save.size / 1024,
+                    save.name
+                ));
+            }
+            out.success(&text, json!({ "saves": list }))
+        }
+        SavesAction::Delete { name } => {
+            let Some(save) = find(&name) else {
+                return out.error(ExitStatus::NotFound, &format!("no save named {}", name));
+            };
+            match saves::delete(&save) {
+                Ok(()) => out.success(&format!("Deleted {}", name), json!({ "deleted": name })),
+                Err(err) => out.error(ExitStatus::Failure, &format!("failed to delete {}: {}", name, err)),
+            }
+        }
+        SavesAction::Export { name, path } => {
+            let Some(save) = find(&name) else {
+                return out.error(ExitStatus::NotFound, &format!("no save named {}", name));
+            };
+            let path = path.unwrap_or_else(|| PathBuf::from(format!("{}.zip", name)));
+            match saves::export(&save, &path) {
+                Ok(()) => out.success(&format!("Exported {} to {}", name, path.display()), json!({ "save": save, "archive": path })),
+                Err(err) => out.error(ExitStatus::Failure, &format!("failed to export {}: {}", name, err)),
+            }
+        }
+        SavesAction::Import { path } => match saves::import(&root, &path) {
+            Ok(name) => out.success(&format!("Imported {}", name), json!({ "imported": name })),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => out.error(ExitStatus::NotFound, &format!("failed to import {}: {}", path.display(), err)),
+            Err(err) => out.error(ExitStatus::Failure, &format!("failed to import {}: {}", path.display(), err)),
+        },
+    }
+}
+
+fn backup(out: &Output, config: AppConfig, action: BackupAction) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    match action {
+        BackupAction::List => {
+            let backups = save_backup::list(&root);
+            if backups.is_empty() {
+                return out.success("There are no save backups yet.", json!({ "backups": [] }));
+            }
+            let mut text = format!("{} save backup(s):", backups.len());
+            for backup in &backups {
+                text.push_str(&format!(
+                    "\n  {}  {:<10} {:<15} {:>8} KB  {}",
+                    backup.created.format("%Y-%m-%d %H:%M"),
+                    backup.version.as_deref().unwrap_or("unknown"),
+                    backup.reason.to_string(),
+                    backup.size / 1024,
+                    backup.name()
+                ));
+            }
+            out.success(&text, json!({ "backups": backups }))
+        }
+        BackupAction::Create => {
+            let backup = match save_backup::create(&root, config.version.as_deref(), BackupReason::Manual) {
+                Ok(Some(backup)) => backup,
+                Ok(None) => return out.error(ExitStatus::NotFound, &format!("no saves found in {}", save_backup::SAVES_DIR)),
+                Err(err) => return out.error(ExitStatus::Failure, &format!("failed to back up the saves: {}", err)),
+            };
+            let deleted = match save_backup::prune(&root, &config.save_backups) {
+                Ok(deleted) => deleted,
+                Err(err) => return out.error(ExitStatus::Failure, &format!("failed to delete old backups: {}", err)),
+            };
+            let mut text = format!("Saves backed up to {}", backup.path.display());
+            if !deleted.is_empty() {
+                text.push_str(&format!("\nDeleted {} old backup(s)", deleted.len()));
+            }
+            out.success(&text, json!({ "backup": backup, "deleted": deleted }))
+        }
+        BackupAction::Restore { name } => {
+            let Some(backup) = save_backup::list(&root).into_iter().find(|backup| backup.name() == name) else {
+                return out.error(ExitStatus::NotFound, &format!("no save backup named {}", name));
+            };
+            match save_backup::restore_with_backup(&root, &backup.path, config.version.as_deref(), &config.save_backups) {
+                Ok(()) => out.success(&format!("Restored the saves from {}", name), json!({ "restored": backup })),
+                Err(err) => out.error(ExitStatus::Failure, &format!("failed to restore {}: {}", name, err)),
+            }
+        }
+    }
+}
+
+fn addons(out: &Output, config: AppConfig, action: AddonAction) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let Some(instance) = Mo2Instance::detect(&root, &config.mo2) else {
+        return out.error(ExitStatus::NotFound, "addons are installed into Mod Organizer 2, but no instance was found next to the game");
+    };
+    let mut installed = match InstalledAddons::load(&instance.dir) {
+        Ok(installed) => installed,
+        Err(err) => return out.error(ExitStatus::Failure, &format!("failed to read {}: {}", addons::INSTALLED_ADDONS_FILE, err)),
+    };
+    let updater = match Updater::new(config.clone()) {
+        Ok(updater) => updater,
+        Err(err) => return updater_error(out, err),
+    };
+    let catalog = match updater.fetch_manifest() {
+        Ok(manifest) => manifest.addons,
+        Err(err) => return updater_error(out, err),
+    };
+
+    match action {
+        AddonAction::List => {
+            if catalog.is_empty() {
+                return out.success("The update server doesn't offer any addons.", json!({ "addons": [] }));
+            }
+            let mut text = String::from("Addons:");
+            let mut list = Vec::new();
+            for addon in &catalog {
+                let status = addons::status(addon, &installed);
+                let label = match (status, installed.get(&addon.id)) {
+                    (AddonStatus::Outdated, Some(current)) => format!("update available, {} installed", current.version),
+                    (AddonStatus::Installed, _) => "installed".to_string(),
+                    _ => "not installed".to_string(),
+                };
+                text.push_str(&format!("\n  {:<20} {} {} ({})", addon.id, addon.name, addon.version, label));
+                list.push(json!({ "addon": addon, "status": status, "installed": installed.get(&addon.id) }));
+            }
+            out.success(&text, json!({ "addons": list }))
+        }
+        AddonAction::Install { id } => {
+            let addon = match addons::check_install(&catalog, &installed, &id) {
+                Ok(addon) => addon,
+                Err(err) => return out.error(ExitStatus::Failure, &err.to_string()),
+            };
+            if addons::status(addon, &installed) == AddonStatus::Installed {
+                return out.success(&format!("{} {} is already installed.", addon.name, addon.version), json!({ "id": id, "installed": false }));
+            }
+            let archive = match updater.download_addon(addon, &mut |_| {}) {
+                Ok(archive) => archive,
+                Err(err) => return updater_error(out, err),
+            };
+            let profile = instance.resolve_profile(config.mo2.profile.as_deref());
+            let result = addons::install(&instance, profile.as_deref(), addon, &archive, &mut installed);
+            let _ = fs::remove_file(&archive);
+            match result {
+                Ok(folder) => out.success(
+                    &format!("Installed {} {} into {}", addon.name, addon.version, folder.display()),
+                    json!({ "id": id, "installed": true, "version": addon.version.to_string(), "folder": folder, "profile": profile }),
+                ),
+                Err(err) => out.error(ExitStatus::Failure, &err.to_string()),
+            }
+        }
+        AddonAction::Remove { id } => {
+            if let Err(err) = addons::check_remove(&catalog, &installed, &id) {
+                return out.error(ExitStatus::Failure, &err.to_string());
+            }
+            match addons::remove(&instance, &id, &mut installed) {
+                Ok(()) => out.success(&format!("Removed {}", id), json!({ "id": id, "removed": true })),
+                Err(err) => out.error(ExitStatus::Failure, &err.to_string()),
+            }
+        }
+    }
+}
+
+fn mod_conflicts(out: &Output, config: AppConfig, export: bool) -> ExitStatus {
+    let root = env::current_dir().unwrap_or_default();
+    let sources = conflicts::detect_sources(&root, &config.mo2);
+    if sources.is_empty() {
+        return out.error(ExitStatus::NotFound, "no mods found: neither an MO2 profile nor a 'mods' folder with gamedata");
+    }
+    let report = match conflicts::scan(&sources) {
+        Ok(report) => report,
+        Err(err) => return out.error(ExitStatus::Failure, &format!("failed to scan the mods: {}", err)),
+    };
+
+    let mut text = report.to_string();
+    let mut exported = None;
+    if export {
+        match conflicts::export(&root, &report) {
+            Ok(path) => {
+                text.push_str(&format!("\nReport saved to {}", path.display()));
+                exported = Some(path);
+            }
+            Err(err) => return out.error(ExitStatus::Failure, &format!("failed to save the report: {}", err)),
+        }
+    }
+    out.success(text.trim_end(), json!({ "report": report, "exported": exported }))
+}
+
+fn config_get(out: &Output, config: AppConfig, key: Option<&str>, origin: bool) -> ExitStatus {
+    let table = match toml::Value::try_from(&config) {
+        Ok(table) => table,
+        Err(err) => return out.error(ExitStatus::ConfigError, &err.to_string()),
+    };
+
+    let value = match key {
+        Some(key) => match layers::lookup(&table, key) {
+            Some(value) => value,
+            None => return out.error(ExitStatus::ConfigError, &format!("key '{}' is not set", key)),
+        },
+        None => &table,
+    };
+    let data = serde_json::to_value(value).unwrap_or(Value::Null);
+
+    if origin {
+        let Some(layers) = &config.layers else {
+            return out.error(ExitStatus::Failure, "the configuration was not loaded from its layers");
+        };
+        // Every value under the key with the layer it came from
+        let origins: Vec<(&String, Layer)> = layers
+            .origins()
+            .iter()
+            .filter(|(path, _)| key.is_none_or(|key| *path == key || path.starts_with(&format!("{}.", key))))
+            .filter(|(path, _)| layers::lookup(&table, path).is_some())
+            .map(|(path, layer)| (path, *layer))
+            .collect();
+        let text = origins
+            .iter()
+            .map(|(path, layer)| format!("{} = {}  # {}", path, layers::lookup(&table, path).map(|v| v.to_string()).unwrap_or_default(), layer))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let origin_map: serde_json::Map<String, Value> = origins.iter().map(|(path, layer)| (path.to_string(), json!(layer))).collect();
+        return out.success(&text, json!({ "key": key, "value": data, "origins": origin_map }));
+    }
+
+    let text = match value {
+        toml::Value::String(s) => s.clone(),
+        toml::Value::Table(_) => toml::to_string_pretty(value).unwrap_or_default(),
+        other => other.to_string(),
+    };
+    out.success(text.trim_end(), json!({ "key": key, "value": data }))
+}
+
+fn config_path(out: &Output) -> ExitStatus {
+    let location = AppConfig::location();
+    let mode = if location.is_portable() { "portable" } else { "per-user" };
+    out.success(
+        &format!("{} ({})", location.path().display(), mode),
+        json!({ "path": location.path(), "portable": location.is_portable(), "exists": location.path().exists() }),
+    )
+}
+
+fn config_set(out: &Output, key: &str, raw_value: &str) -> ExitStatus {
+    // Overrides from --set must not end up in the file
+    let mut config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(err) => return out.error(ExitStatus::ConfigError, &err.to_string()),
+    };
+    let value = match config.set_value(key, raw_value) {
+        Ok(value) => value,
+        Err(err) => return out.error(ExitStatus::ConfigError, &err.to_string()),
+    };
+    if let Err(err) = config.write() {
+        return out.error(ExitStatus::ConfigError, &err.to_string());
+    }
+
+    let data = serde_json::to_value(&value).unwrap_or(Value::Null);
+    out.success(&format!("{} = {}", key, value), json!({ "key": key, "value": data }))
+}
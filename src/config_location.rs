@@ -0,0 +1,110 @@
+//! Where `launcherconfig.toml` lives. By default it is kept per user, in the
+//! platform config directory under a folder named after the install, so
+//! read-only installs work and users sharing a game folder don't share
+//! settings. A `launcher.portable` file in the game folder keeps it next to
+//! the game instead.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::app_config::CONFIG_FILE;
+
+/// Marker file in the game folder that turns on portable mode.
+pub const PORTABLE_MARKER: &str = "launcher.portable";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigLocation {
+    /// `launcherconfig.toml` in the game folder.
+    Portable(PathBuf),
+    /// `launcherconfig.toml` in the user's config directory.
+    PerUser(PathBuf),
+}
+
+impl ConfigLocation {
+    /// Location for the game folder `root` and the current user.
+    pub fn detect(root: &Path) -> Self {
+        ConfigLocation::resolve(root, user_config_dir().as_deref())
+    }
+
+    /// Location for `root` with `user_dir` as the user's config directory.
+    /// Without one the config stays in the game folder.
+    pub fn resolve(root: &Path, user_dir: Option<&Path>) -> Self {
+        match user_dir {
+            Some(user_dir) if !root.join(PORTABLE_MARKER).exists() => {
+                ConfigLocation::PerUser(user_dir.join("installs").join(install_key(root)).join(CONFIG_FILE))
+            }
+            _ => ConfigLocation::Portable(root.join(CONFIG_FILE)),
+        }
+    }
+
+    /// Path of `launcherconfig.toml`.
+    pub fn path(&self) -> &Path {
+        match self {
+            ConfigLocation::Portable(path) | ConfigLocation::PerUser(path) => path,
+        }
+    }
+
+    pub fn is_portable(&self) -> bool {
+        matches!(self, ConfigLocation::Portable(_))
+    }
+
+    /// Copies a `launcherconfig.toml` left in the game folder by older
+    /// launchers to the per-user location, unless there is one already.
+    /// The original stays for other users of the install. Returns whether
+    /// anything was copied.
+    pub fn import_legacy(&self, root: &Path) -> io::Result<bool> {
+        let legacy = root.join(CONFIG_FILE);
+        let ConfigLocation::PerUser(path) = self else {
+            return Ok(false);
+        };
+        if path.exists() || !legacy.is_file() {
+            return Ok(false);
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::copy(&legacy, path)?;
+        Ok(true)
+    }
+}
+
+/// The launcher's folder in the platform config directory: `%APPDATA%` on
+/// Windows, `~/Library/Application Support` on macOS and `$XDG_CONFIG_HOME`
+/// or `~/.config` elsewhere.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    if cfg!(windows) {
+        non_empty("APPDATA").map(|dir| dir.join("DeadReckoningLauncher"))
+    } else if cfg!(target_os = "macos") {
+        non_empty("HOME").map(|home| home.join("Library/Application Support/DeadReckoningLauncher"))
+    } else {
+        non_empty("XDG_CONFIG_HOME")
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| non_empty("HOME").map(|home| home.join(".config")))
+            .map(|dir| dir.join("dead-reckoning-launcher"))
+    }
+}
+
+/// Folder name for an install: its own name, for people browsing the
+/// directory, and a hash of the full path, so two copies never collide.
+fn install_key(root: &Path) -> String {
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut path = root.to_string_lossy().to_string();
+    if cfg!(windows) {
+        path = path.to_lowercase();
+    }
+    let hash = format!("{:x}", Sha256::digest(path.as_bytes()));
+    let name: String = root
+        .file_name()
+        .map(|name| name.to_string_lossy().chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect())
+        .unwrap_or_default();
+    if name.is_empty() {
+        hash[..16].to_string()
+    } else {
+        format!("{}-{}", name, &hash[..16])
+    }
+}
@@ -0,0 +1,205 @@
+//! Conflicts between mods that ship the same `gamedata` files.
+//!
+//! Mods are applied from the lowest priority to the highest, each overwriting
+//! the files of the ones before it. DLTX patch files (`mod_*.ltx`) don't
+//! overwrite each other unless their names match, but when several of them
+//! change the same section, the one sorting last by file name wins.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::{
+    diagnostics::DIAGNOSTICS_DIR,
+    fs_util,
+    mo2::{Mo2Config, Mo2Instance},
+    modlist::{self, ModList, ModState},
+};
+
+/// A mod's `gamedata` folder.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModSource {
+    pub name: String,
+    pub gamedata: PathBuf,
+}
+
+/// Mods of the MO2 profile the game is launched with, lowest priority first.
+/// Without MO2, the subfolders of `mods` in the game folder in name order.
+pub fn detect_sources(root: &Path, config: &Mo2Config) -> Vec<ModSource> {
+    if let Some(instance) = Mo2Instance::detect(root, config) {
+        if let Some(profile) = instance.resolve_profile(config.profile.as_deref()) {
+            if let Ok(list) = ModList::load(&modlist::modlist_path(&instance.profile_dir(&profile))) {
+                return mo2_sources(&instance.mods_dir, &list);
+            }
+        }
+    }
+    folder_sources(&root.join("mods"))
+}
+
+/// Enabled mods of an MO2 mod list, lowest priority first.
+pub fn mo2_sources(mods_dir: &Path, list: &ModList) -> Vec<ModSource> {
+    list.by_priority()
+        .filter(|(_, entry)| entry.state == ModState::Enabled)
+        .map(|(_, entry)| ModSource { name: entry.name.clone(), gamedata: mods_dir.join(&entry.name).join("gamedata") })
+        .filter(|source| source.gamedata.is_dir())
+        .collect()
+}
+
+/// Subfolders of `dir` that have a `gamedata` folder, in name order, so
+/// numbered folders like `010-weapons` overwrite lower numbers.
+pub fn folder_sources(dir: &Path) -> Vec<ModSource> {
+    let mut sources: Vec<ModSource> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| ModSource { name: entry.file_name().to_string_lossy().to_string(), gamedata: entry.path().join("gamedata") })
+                .filter(|source| source.gamedata.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    sources.sort_by_key(|source| source.name.to_lowercase());
+    sources
+}
+
+/// A file shipped by more than one mod.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileConflict {
+    /// Path under `gamedata`, with `/` separators.
+    pub path: String,
+    /// Mods shipping the file, lowest priority first; the last one wins.
+    pub mods: Vec<String>,
+}
+
+impl FileConflict {
+    pub fn winner(&self) -> &str {
+        self.mods.last().map(String::as_str).unwrap_or_default()
+    }
+}
+
+/// A section changed by DLTX files of more than one mod.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionConflict {
+    pub section: String,
+    /// (mod, file) pairs in the order DLTX applies them; the last one wins.
+    pub patches: Vec<(String, String)>,
+}
+
+impl SectionConflict {
+    pub fn winner(&self) -> &str {
+        self.patches.last().map(|(name, _)| name.as_str()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConflictReport {
+    pub mods: Vec<String>,
+    pub files: Vec<FileConflict>,
+    pub sections: Vec<SectionConflict>,
+}
+
+/// Walks the `gamedata` folders of `sources`, given lowest priority first.
+pub fn scan(sources: &[ModSource]) -> io::Result<ConflictReport> {
+    // Keyed by lowercase path, the game doesn't care about case
+    let mut owners: BTreeMap<String, (String, Vec<usize>)> = BTreeMap::new();
+    for (index, source) in sources.iter().enumerate() {
+        for path in fs_util::relative_files(&source.gamedata)? {
+            owners.entry(path.to_lowercase()).or_insert_with(|| (path.clone(), Vec::new())).1.push(index);
+        }
+    }
+
+    let files = owners
+        .values()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(path, mods)| FileConflict { path: path.clone(), mods: mods.iter().map(|&i| sources[i].name.clone()).collect() })
+        .collect();
+
+    // DLTX reads the winning copy of every mod_*.ltx, in file name order
+    let mut patches: Vec<(String, String, usize)> = owners
+        .values()
+        .filter(|(path, _)| is_dltx_patch(path))
+        .filter_map(|(path, mods)| mods.last().map(|&winner| (file_name(path).to_lowercase(), path.clone(), winner)))
+        .collect();
+    patches.sort();
+    let mut sections: BTreeMap<String, (String, Vec<(String, String)>)> = BTreeMap::new();
+    for (_, path, index) in patches {
+        let source = &sources[index];
+        let content = fs::read(source.gamedata.join(&path)).map(|bytes| String::from_utf8_lossy(&bytes).to_string()).unwrap_or_default();
+        for section in dltx_sections(&content) {
+            let entry = sections.entry(section.to_lowercase()).or_insert_with(|| (section.clone(), Vec::new()));
+            entry.1.push((source.name.clone(), path.clone()));
+        }
+    }
+    let sections = sections
+        .into_values()
+        .filter(|(_, patches)| {
+            let first = &patches[0].0;
+            patches.iter().any(|(name, _)| name != first)
+        })
+        .map(|(section, patches)| SectionConflict { section, patches })
+        .collect();
+
+    Ok(ConflictReport { mods: sources.iter().map(|source| source.name.clone()).collect(), files, sections })
+}
+
+/// Writes the report as `diagnostics/conflicts-<date>-<time>.txt` in `root`.
+pub fn export(root: &Path, report: &ConflictReport) -> io::Result<PathBuf> {
+    let dir = root.join(DIAGNOSTICS_DIR);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("conflicts-{}.txt", Local::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, report.to_string())?;
+    Ok(path)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn is_dltx_patch(path: &str) -> bool {
+    let name = file_name(path).to_lowercase();
+    name.starts_with("mod_") && name.ends_with(".ltx")
+}
+
+/// Names of the sections a DLTX file defines or changes: `[name]`,
+/// `![name]`, `@[name]` and `!![name]`, with any parent list after `:`.
+pub fn dltx_sections(content: &str) -> Vec<String> {
+    let mut sections: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.split(';').next().unwrap_or_default().trim();
+        let line = line.trim_start_matches(['!', '@']);
+        let Some(name) = line.strip_prefix('[').and_then(|rest| rest.split(']').next()).map(str::trim) else {
+            continue;
+        };
+        if !name.is_empty() && !sections.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+            sections.push(name.to_string());
+        }
+    }
+    sections
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Scanned {} mod(s)", self.mods.len())?;
+        writeln!(f)?;
+        writeln!(f, "Overwritten files: {}", self.files.len())?;
+        for conflict in &self.files {
+            let losers = &conflict.mods[..conflict.mods.len() - 1];
+            writeln!(f, "  {}", conflict.path)?;
+            writeln!(f, "    winner: {} (overwrites {})", conflict.winner(), losers.join(", "))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Sections patched by several mods: {}", self.sections.len())?;
+        for conflict in &self.sections {
+            writeln!(f, "  [{}]", conflict.section)?;
+            for (name, path) in &conflict.patches {
+                writeln!(f, "    {}: {}", name, path)?;
+            }
+            writeln!(f, "    winner: {}", conflict.winner())?;
+        }
+        Ok(())
+    }
+}
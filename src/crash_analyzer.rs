@@ -0,0 +1,219 @@
+use std::{fmt, fs, io, path::Path};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Rule file shipped with the modpack, checked before the built-in rules.
+pub const CRASH_RULES_FILE: &str = "crash_rules.toml";
+
+const BUILTIN_RULES: &str = include_str!("../assets/crash_rules.toml");
+
+/// The `FATAL ERROR` block of an X-Ray log.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CrashReport {
+    pub expression: Option<String>,
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub description: Option<String>,
+    /// `Arguments` with its continuation lines joined by newlines.
+    pub arguments: Option<String>,
+    /// Script file and line the error points to, e.g. `ui_options.script:123`.
+    pub script: Option<(String, u32)>,
+    /// Frames of the last Lua `stack traceback:` before the crash.
+    pub script_stack: Vec<String>,
+}
+
+/// Which part of a [`CrashReport`] a rule's pattern is matched against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleField {
+    /// Every field, one per line.
+    #[default]
+    Any,
+    Expression,
+    Function,
+    File,
+    Description,
+    Arguments,
+    Stack,
+}
+
+/// One `[[rule]]` of a rule file. `cause` and `fix` may refer to capture groups as `$1` or `${name}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrashRule {
+    pub name: String,
+    #[serde(default)]
+    pub field: RuleField,
+    pub pattern: String,
+    pub cause: String,
+    #[serde(default)]
+    pub fix: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RuleFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<CrashRule>,
+}
+
+/// A rule that matched a crash, with its captures filled in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnosis {
+    pub rule: String,
+    pub cause: String,
+    pub fix: String,
+}
+
+#[derive(Debug)]
+pub enum CrashRulesError {
+    ReadFailed(String),
+    BadStructure(String),
+    BadPattern { rule: String, error: String },
+}
+
+impl fmt::Display for CrashRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrashRulesError::ReadFailed(e) => write!(f, "Failed to read {}: {}", CRASH_RULES_FILE, e),
+            CrashRulesError::BadStructure(e) => write!(f, "{} is malformed: {}", CRASH_RULES_FILE, e),
+            CrashRulesError::BadPattern { rule, error } => write!(f, "Invalid pattern in rule '{}': {}", rule, error),
+        }
+    }
+}
+
+impl std::error::Error for CrashRulesError {}
+
+/// Compiled rules, tried in order until one matches.
+pub struct CrashRules {
+    rules: Vec<(CrashRule, Regex)>,
+}
+
+impl CrashRules {
+    pub fn parse(content: &str) -> Result<Self, CrashRulesError> {
+        let file: RuleFile = toml::from_str(content).map_err(|e| CrashRulesError::BadStructure(e.to_string()))?;
+        let mut rules = Vec::with_capacity(file.rules.len());
+        for rule in file.rules {
+            let regex = Regex::new(&rule.pattern)
+                .map_err(|e| CrashRulesError::BadPattern { rule: rule.name.clone(), error: e.to_string() })?;
+            rules.push((rule, regex));
+        }
+        Ok(CrashRules { rules })
+    }
+
+    /// Rules that ship with the launcher.
+    pub fn builtin() -> Self {
+        CrashRules::parse(BUILTIN_RULES).expect("built-in crash rules are valid")
+    }
+
+    /// The modpack's `crash_rules.toml` from `root` followed by the built-in rules.
+    pub fn load(root: &Path) -> Result<Self, CrashRulesError> {
+        let mut rules = match fs::read_to_string(root.join(CRASH_RULES_FILE)) {
+            Ok(content) => CrashRules::parse(&content)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => CrashRules { rules: Vec::new() },
+            Err(e) => return Err(CrashRulesError::ReadFailed(e.to_string())),
+        };
+        rules.rules.extend(CrashRules::builtin().rules);
+        Ok(rules)
+    }
+
+    pub fn diagnose(&self, report: &CrashReport) -> Option<Diagnosis> {
+        self.rules.iter().find_map(|(rule, regex)| {
+            let text = report.field_text(rule.field);
+            let captures = regex.captures(&text)?;
+            let mut cause = String::new();
+            captures.expand(&rule.cause, &mut cause);
+            let mut fix = String::new();
+            captures.expand(&rule.fix, &mut fix);
+            Some(Diagnosis { rule: rule.name.clone(), cause, fix })
+        })
+    }
+}
+
+impl CrashReport {
+    fn field_text(&self, field: RuleField) -> String {
+        let value = |v: &Option<String>| v.clone().unwrap_or_default();
+        match field {
+            RuleField::Expression => value(&self.expression),
+            RuleField::Function => value(&self.function),
+            RuleField::File => value(&self.file),
+            RuleField::Description => value(&self.description),
+            RuleField::Arguments => value(&self.arguments),
+            RuleField::Stack => self.script_stack.join("\n"),
+            RuleField::Any => [
+                value(&self.expression),
+                value(&self.function),
+                value(&self.file),
+                value(&self.description),
+                value(&self.arguments),
+                self.script_stack.join("\n"),
+            ]
+            .join("\n"),
+        }
+    }
+}
+
+/// Extracts the `FATAL ERROR` block from the text of an X-Ray log, if the log has one.
+pub fn parse_log(log: &str) -> Option<CrashReport> {
+    let lines: Vec<&str> = log.lines().collect();
+    let start = lines.iter().position(|line| line.contains("FATAL ERROR"))?;
+    let mut report = CrashReport::default();
+
+    let mut in_arguments = false;
+    for line in &lines[start + 1..] {
+        let trimmed = line.trim();
+        if trimmed.starts_with("stack trace") {
+            break;
+        }
+        let Some(entry) = trimmed.strip_prefix("[error]") else {
+            // Arguments often run over several lines, e.g. a Lua error followed by its traceback
+            if in_arguments && !trimmed.is_empty() {
+                if let Some(arguments) = report.arguments.as_mut() {
+                    arguments.push('\n');
+                    arguments.push_str(line.trim_end());
+                }
+            }
+            continue;
+        };
+        let Some((key, value)) = entry.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        in_arguments = false;
+        match key.trim() {
+            "Expression" => report.expression = Some(value),
+            "Function" => report.function = Some(value),
+            "File" => report.file = Some(value),
+            "Line" => report.line = value.parse().ok(),
+            "Description" => report.description = Some(value),
+            "Arguments" => {
+                report.arguments = Some(value);
+                in_arguments = true;
+            }
+            _ => {}
+        }
+    }
+
+    report.script_stack = script_traceback(&lines[..start])
+        .or_else(|| report.arguments.as_deref().and_then(|arguments| script_traceback(&arguments.lines().collect::<Vec<_>>())))
+        .unwrap_or_default();
+
+    let location = Regex::new(r"([^\s\\/:'\x22]+\.script):(\d+)").expect("valid pattern");
+    report.script = [report.arguments.clone().unwrap_or_default(), report.script_stack.join("\n")]
+        .iter()
+        .find_map(|text| location.captures(text))
+        .and_then(|captures| Some((captures[1].to_string(), captures[2].parse().ok()?)));
+
+    Some(report)
+}
+
+/// Frames of the last `stack traceback:` in `lines`.
+fn script_traceback(lines: &[&str]) -> Option<Vec<String>> {
+    let header = lines.iter().rposition(|line| line.trim() == "stack traceback:")?;
+    let frames = lines[header + 1..]
+        .iter()
+        .take_while(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty())
+        .map(|line| line.trim().to_string())
+        .collect();
+    Some(frames)
+}
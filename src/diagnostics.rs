@@ -0,0 +1,170 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::Local;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    app_config::{AppConfig, CONFIG_FILE},
+    config_location::ConfigLocation,
+    crash_analyzer::{self, CrashRules},
+    mo2::{Mo2Config, Mo2Instance},
+    session::SESSION_LOG,
+    system_info::CpuFeatures,
+};
+
+/// Folder in the game directory that diagnostic bundles are written to.
+pub const DIAGNOSTICS_DIR: &str = "diagnostics";
+
+/// How many of the newest logs and crash dumps go into a bundle.
+const MAX_LOGS: usize = 3;
+const MAX_DUMPS: usize = 2;
+
+/// Lines after `FATAL ERROR` shown when the log has no `stack trace:` block.
+const MAX_SECTION_LINES: usize = 200;
+
+pub fn logs_dir(root: &Path) -> PathBuf {
+    root.join("appdata").join("logs")
+}
+
+/// Files in `dir` with one of `extensions`, newest first.
+fn newest_files(dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        })
+        .map(|path| (fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH), path))
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The most recently written X-Ray log in `appdata/logs`.
+pub fn latest_log(root: &Path) -> Option<PathBuf> {
+    newest_files(&logs_dir(root), &["log"]).into_iter().next()
+}
+
+/// Line range of the `FATAL ERROR` block of an X-Ray log, through the end of its stack trace.
+pub fn fatal_error_section(lines: &[&str]) -> Option<Range<usize>> {
+    let start = lines.iter().position(|line| line.contains("FATAL ERROR"))?;
+    let limit = (start + MAX_SECTION_LINES).min(lines.len());
+
+    let Some(trace) = (start..limit).find(|&i| lines[i].trim_start().starts_with("stack trace")) else {
+        return Some(start..limit);
+    };
+    // The stack trace ends at the first blank line after its frames
+    let mut end = trace + 1;
+    while end < lines.len() && lines[end].trim().is_empty() {
+        end += 1;
+    }
+    while end < lines.len() && !lines[end].trim().is_empty() {
+        end += 1;
+    }
+    Some(start..end)
+}
+
+/// Names of the installed mods: the mod folders of the detected MO2 instance, otherwise the top level of `gamedata`.
+pub fn installed_mods(root: &Path, mo2: &Mo2Config) -> Vec<String> {
+    let dir = Mo2Instance::detect(root, mo2)
+        .map(|instance| instance.mods_dir)
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(|| root.join("gamedata"));
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn summary(root: &Path, config: &AppConfig) -> String {
+    let cpu = CpuFeatures::detect();
+    let mut text = String::new();
+    text.push_str(&format!("Created: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S %z")));
+    text.push_str(&format!("Launcher version: {}\n", env!("CARGO_PKG_VERSION")));
+    text.push_str(&format!("Modpack version: {}\n", config.version.as_deref().unwrap_or("unknown")));
+    text.push_str(&format!("Profile: {}\n", config.profile_name()));
+    text.push_str(&format!("Renderer: {}\n", config.active().renderer));
+    text.push_str(&format!("Use AVX: {}\n", config.active().use_avx));
+    text.push_str(&format!("Launch arguments: {}\n", config.launch_args().join(" ")));
+    text.push_str(&format!("Runner: {}\n", config.runner.kind));
+    text.push_str(&format!("OS: {} {}\n", std::env::consts::OS, std::env::consts::ARCH));
+    text.push_str(&format!("CPU: {}\n", cpu.brand.as_deref().unwrap_or("unknown")));
+    for (name, supported) in cpu.list() {
+        text.push_str(&format!("  {}: {}\n", name, if supported { "yes" } else { "no" }));
+    }
+    text.push_str(&format!("Game folder: {}\n", root.display()));
+
+    let report = latest_log(root)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| crash_analyzer::parse_log(&String::from_utf8_lossy(&bytes)));
+    if let Some(report) = report {
+        let rules = CrashRules::load(root).unwrap_or_else(|_| CrashRules::builtin());
+        match rules.diagnose(&report) {
+            Some(diagnosis) => text.push_str(&format!("Crash: {} ({})\n", diagnosis.cause, diagnosis.rule)),
+            None => text.push_str("Crash: no known cause\n"),
+        }
+    }
+    text
+}
+
+/// Zips the newest logs and crash dumps, `user.ltx`, the launcher config and
+/// session log, plus a summary and the mod list, into `diagnostics/`.
+pub fn create_bundle(root: &Path, config: &AppConfig) -> io::Result<PathBuf> {
+    let out_dir = root.join(DIAGNOSTICS_DIR);
+    fs::create_dir_all(&out_dir)?;
+    let bundle_path = out_dir.join(format!("diagnostics-{}.zip", Local::now().format("%Y%m%d-%H%M%S")));
+
+    let mut zip = ZipWriter::new(File::create(&bundle_path)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("summary.txt", options)?;
+    zip.write_all(summary(root, config).as_bytes())?;
+    zip.start_file("mods.txt", options)?;
+    zip.write_all(installed_mods(root, &config.mo2).join("\n").as_bytes())?;
+
+    let logs = logs_dir(root);
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for path in newest_files(&logs, &["log"]).into_iter().take(MAX_LOGS) {
+        files.push((path.clone(), format!("logs/{}", path.file_name().unwrap_or_default().to_string_lossy())));
+    }
+    for dir in [logs.as_path(), root] {
+        for path in newest_files(dir, &["mdmp", "dmp"]).into_iter().take(MAX_DUMPS) {
+            files.push((path.clone(), format!("dumps/{}", path.file_name().unwrap_or_default().to_string_lossy())));
+        }
+    }
+    files.push((root.join("appdata").join("user.ltx"), "user.ltx".to_string()));
+    files.push((ConfigLocation::detect(root).path().to_path_buf(), CONFIG_FILE.to_string()));
+    files.push((root.join(SESSION_LOG), SESSION_LOG.to_string()));
+
+    for (path, name) in files {
+        // Files the game still holds open or never wrote are left out
+        let Ok(mut source) = File::open(&path) else {
+            continue;
+        };
+        zip.start_file(name, options)?;
+        io::copy(&mut source, &mut zip)?;
+    }
+
+    zip.finish()?;
+    Ok(bundle_path)
+}
@@ -0,0 +1,67 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// `path` with `suffix` appended to the file name, e.g. `launcherconfig.toml.bak`.
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Copies `path` to `backup` unless a backup is already there, so repeated
+/// saves keep the file as it was before the launcher first changed it.
+pub fn backup_once(path: &Path, backup: &Path) -> io::Result<()> {
+    if path.exists() && !backup.exists() {
+        fs::copy(path, backup)?;
+    }
+    Ok(())
+}
+
+/// Every file below `dir`, as `/`-separated paths relative to it.
+pub fn relative_files(dir: &Path) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    Ok(files)
+}
+
+fn collect_files(base: &Path, dir: &Path, files: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(base, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(base) {
+            let parts: Vec<String> = relative.components().map(|part| part.as_os_str().to_string_lossy().to_string()).collect();
+            files.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces `path` with `contents` so that it holds either the old or the new
+/// data even if the process dies midway: the data is written and synced to a
+/// temporary file next to it, which is then renamed over `path`.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = with_suffix(path, ".tmp");
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // The rename itself is only durable once the directory entry is on disk
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
@@ -1,42 +1,188 @@
-use std::{env, path::PathBuf, process::Command};
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
-use crate::Renderer;
+use serde::{Deserialize, Serialize};
 
+use crate::{
+    app_config::{AppConfig, Renderer},
+    diagnostics, launch_args,
+    mo2::{self, Mo2Instance},
+    runner::{RunnerConfig, RunnerKind},
+    session::Session,
+};
+
+/// Executables shipped with Anomaly, relative to the game folder.
+const DEFAULT_EXECUTABLES: [(Renderer, bool, &str); 8] = [
+    (Renderer::DX8, false, "bin/AnomalyDX8.exe"),
+    (Renderer::DX8, true, "bin/AnomalyDX8AVX.exe"),
+    (Renderer::DX9, false, "bin/AnomalyDX9.exe"),
+    (Renderer::DX9, true, "bin/AnomalyDX9AVX.exe"),
+    (Renderer::DX10, false, "bin/AnomalyDX10.exe"),
+    (Renderer::DX10, true, "bin/AnomalyDX10AVX.exe"),
+    (Renderer::DX11, false, "bin/AnomalyDX11.exe"),
+    (Renderer::DX11, true, "bin/AnomalyDX11AVX.exe"),
+];
+
+/// Overrides the executable started for a renderer, set as `[[executables]]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutableEntry {
+    pub renderer: Renderer,
+    pub avx: bool,
+    /// Path relative to the game folder; both `/` and `\` are accepted as separators.
+    pub path: String,
+}
+
+/// Relative path of the executable for a renderer, taking `overrides` before the built-in table.
+pub fn executable_path(overrides: &[ExecutableEntry], renderer: Renderer, avx: bool) -> String {
+    if let Some(entry) = overrides.iter().find(|e| e.renderer == renderer && e.avx == avx) {
+        return entry.path.clone();
+    }
+    DEFAULT_EXECUTABLES
+        .iter()
+        .find(|(r, a, _)| *r == renderer && *a == avx)
+        .map(|(_, _, path)| path.to_string())
+        .unwrap_or_default()
+}
+
+/// Joins a `/` or `\` separated relative path onto `root` using the native separator.
+pub fn join_relative(root: &Path, relative: &str) -> PathBuf {
+    relative
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .fold(root.to_path_buf(), |path, part| path.join(part))
+}
+
+#[derive(Debug)]
 pub enum GameError {
     ExecutableNotFound,
     Unknown(String),
 }
 
-pub struct Game(PathBuf);
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::ExecutableNotFound => write!(f, "Game executable not found"),
+            GameError::Unknown(msg) => write!(f, "Failed to start the game: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Game executable selected for a renderer, and the runner that starts it.
+pub struct Game {
+    /// Game directory the executable was resolved in.
+    root: PathBuf,
+    path: PathBuf,
+    runner: RunnerConfig,
+    /// `ModOrganizer.exe` and the MO2 profile the game is started through.
+    mo2: Option<(PathBuf, String)>,
+}
+
 impl Game {
-    pub fn new(dx_level: Renderer, use_avx: bool) -> Self {
-        let mut cwd = env::current_dir().unwrap();
-        match (dx_level, use_avx) {
-            (Renderer::DX8, true) => cwd.push("bin\\AnomalyDX8.exe"),
-            (Renderer::DX8, false) => cwd.push("bin\\AnomalyDX8AVX.exe"),
-            (Renderer::DX9, true) => cwd.push("bin\\AnomalyDX9.exe"),
-            (Renderer::DX9, false) => cwd.push("bin\\AnomalyDX9AVX.exe"),
-            (Renderer::DX10, true) => cwd.push("bin\\AnomalyDX10.exe"),
-            (Renderer::DX10, false) => cwd.push("bin\\AnomalyDX10AVX.exe"),
-            (Renderer::DX11, true) => cwd.push("bin\\AnomalyDX11.exe"),
-            (Renderer::DX11, false) => cwd.push("bin\\AnomalyDX11AVX.exe"),
-        };
-        Game(cwd)
+    /// Resolves the executable for the configured renderer in the current
+    /// directory, started through Mod Organizer 2 when an instance is found.
+    pub fn new(config: &AppConfig) -> Self {
+        let cwd = env::current_dir().unwrap_or_default();
+        let settings = config.active();
+        let game = Game::resolve(&cwd, &config.executables, settings.renderer, settings.use_avx).with_runner(config.runner.clone());
+        let instance = if config.mo2.enabled { Mo2Instance::detect(&cwd, &config.mo2) } else { None };
+        match instance {
+            Some(instance) => game.with_mo2(&instance, config.mo2.profile.as_deref()),
+            None => game,
+        }
+    }
+
+    /// Resolves the executable in `root`. When the AVX build is requested but
+    /// missing, the regular build of the same renderer is used if it exists.
+    pub fn resolve(root: &Path, overrides: &[ExecutableEntry], renderer: Renderer, use_avx: bool) -> Self {
+        let mut path = join_relative(root, &executable_path(overrides, renderer, use_avx));
+        if use_avx && !path.exists() {
+            let fallback = join_relative(root, &executable_path(overrides, renderer, false));
+            if fallback.exists() {
+                path = fallback;
+            }
+        }
+        Game {
+            root: root.to_path_buf(),
+            path,
+            runner: RunnerConfig { kind: RunnerKind::Native, ..Default::default() },
+            mo2: None,
+        }
+    }
+
+    pub fn with_runner(mut self, runner: RunnerConfig) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Starts the game through `instance` with `profile`, or the profile selected in MO2.
+    pub fn with_mo2(mut self, instance: &Mo2Instance, profile: Option<&str>) -> Self {
+        let profile = instance.resolve_profile(profile).unwrap_or_else(|| "Default".to_string());
+        self.mo2 = Some((instance.executable(), profile));
+        self
+    }
+
+    /// MO2 profile the game is started with, when it goes through MO2.
+    pub fn mo2_profile(&self) -> Option<&str> {
+        self.mo2.as_ref().map(|(_, profile)| profile.as_str())
     }
 
+    /// Full path of the executable that [`Game::launch`] starts.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The command [`Game::launch`] spawns.
+    pub fn command(&self, args: &[String]) -> Command {
+        match &self.mo2 {
+            Some((mo2_exe, profile)) => {
+                let run = mo2::run_args(profile, &self.runner.windows_path(&self.path), &launch_args::join_args(args));
+                self.runner.command(mo2_exe, &run)
+            }
+            None => self.runner.command(&self.path, args),
+        }
+    }
+
+    /// The spawned command as a single line, for showing to the user.
+    pub fn command_line(&self, args: &[String]) -> String {
+        let command = self.command(args);
+        let mut parts = vec![command.get_program().to_string_lossy().to_string()];
+        parts.extend(command.get_args().map(|arg| arg.to_string_lossy().to_string()));
+        launch_args::join_args(&parts)
+    }
+
+    /// Starts the game without waiting for it to exit.
     pub fn launch(&self, args: Vec<String>) -> Result<(), GameError> {
-        let mut cmd = Command::new(self.0.clone());
+        self.spawn(args).map(|_| ())
+    }
 
-        if !self.0.exists() {
+    /// Starts the game and returns a handle that can wait for it to exit.
+    ///
+    /// Through MO2 the exit code is that of `ModOrganizer.exe`, so crashes are
+    /// detected from the logs and dumps the game writes, including the ones
+    /// MO2 redirects into the instance's `overwrite` folder.
+    pub fn spawn(&self, args: Vec<String>) -> Result<Session, GameError> {
+        if !self.path.exists() || self.mo2.as_ref().is_some_and(|(mo2_exe, _)| !mo2_exe.exists()) {
             return Err(GameError::ExecutableNotFound);
         }
 
-        cmd.args(args);
-        let result = cmd.spawn();
-        if let Err(err) = result {
-            return Err(GameError::Unknown(err.to_string()));
+        let mut command = self.command(&args);
+        let program = PathBuf::from(command.get_program());
+        let child = command.spawn().map_err(|err| GameError::Unknown(err.to_string()))?;
+        let session = Session::new(child, self.path.clone(), program);
+        match &self.mo2 {
+            Some((mo2_exe, _)) => {
+                let mut dirs = vec![diagnostics::logs_dir(&self.root)];
+                if let Some(instance_dir) = mo2_exe.parent() {
+                    dirs.push(diagnostics::logs_dir(&instance_dir.join("overwrite")));
+                }
+                Ok(session.with_crash_dirs(dirs))
+            }
+            None => Ok(session),
         }
-
-        Ok(())
     }
 }
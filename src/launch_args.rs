@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+
+/// Optional X-Ray/Anomaly switches, stored as `[flags]` in the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchFlags {
+    /// `-nointro`: skip the intro videos.
+    pub nointro: bool,
+    /// `-noprefetch`: don't preload resources at startup.
+    pub noprefetch: bool,
+    /// `-no_staging`: disable staging buffers on DX10/DX11.
+    pub no_staging: bool,
+    /// `-ss_tga`: save screenshots as TGA instead of JPEG.
+    pub ss_tga: bool,
+    /// `-gamedata_on`: load loose files from `gamedata`.
+    pub gamedata_on: bool,
+}
+
+/// Argument and short description of every flag, in the order of the
+/// fields of [`LaunchFlags`].
+const FLAGS: [(&str, &str); 5] = [
+    ("-nointro", "Skip intro videos"),
+    ("-noprefetch", "Don't preload resources"),
+    ("-no_staging", "Disable staging buffers (DX10/DX11)"),
+    ("-ss_tga", "Save screenshots as TGA"),
+    ("-gamedata_on", "Load loose gamedata files"),
+];
+
+impl LaunchFlags {
+    /// Every flag with its argument, a short description and whether it is set, in display order.
+    pub fn list(&self) -> impl Iterator<Item = (&'static str, &'static str, bool)> {
+        let LaunchFlags { nointro, noprefetch, no_staging, ss_tga, gamedata_on } = *self;
+        FLAGS.into_iter().zip([nointro, noprefetch, no_staging, ss_tga, gamedata_on]).map(|((arg, description), enabled)| (arg, description, enabled))
+    }
+
+    /// [`LaunchFlags::list`] with the flags open for editing.
+    pub fn list_mut(&mut self) -> impl Iterator<Item = (&'static str, &'static str, &mut bool)> {
+        let LaunchFlags { nointro, noprefetch, no_staging, ss_tga, gamedata_on } = self;
+        FLAGS.into_iter().zip([nointro, noprefetch, no_staging, ss_tga, gamedata_on]).map(|((arg, description), enabled)| (arg, description, enabled))
+    }
+
+    /// Arguments for the enabled flags.
+    pub fn args(&self) -> Vec<String> {
+        self.list().filter(|(_, _, enabled)| *enabled).map(|(arg, _, _)| arg.to_string()).collect()
+    }
+}
+
+/// Splits a command line on whitespace. Double or single quotes group words.
+/// Backslashes are kept as-is since they are path separators on Windows, except
+/// before a `"` inside double quotes, where Windows rules apply: `\"` is a
+/// literal quote and an even run of backslashes is halved.
+pub fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some('"') if c == '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if_eq(&'\\').is_some() {
+                    backslashes += 1;
+                }
+                if chars.peek() != Some(&'"') {
+                    current.extend(std::iter::repeat_n('\\', backslashes));
+                    continue;
+                }
+                current.extend(std::iter::repeat_n('\\', backslashes / 2));
+                if backslashes % 2 == 1 {
+                    chars.next();
+                    current.push('"');
+                }
+            }
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    args
+}
+
+/// Joins arguments into one line, quoting the ones that contain whitespace or quotes
+/// so that [`split_args`] and Windows read them back unchanged.
+pub fn join_args<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| {
+            let arg = arg.as_ref();
+            if !arg.is_empty() && !arg.contains(char::is_whitespace) && !arg.contains(['"', '\'']) {
+                return arg.to_string();
+            }
+            let mut quoted = String::from("\"");
+            let mut backslashes = 0;
+            for c in arg.chars() {
+                match c {
+                    '\\' => backslashes += 1,
+                    '"' => {
+                        quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                        quoted.push('"');
+                        backslashes = 0;
+                    }
+                    _ => {
+                        quoted.extend(std::iter::repeat_n('\\', backslashes));
+                        quoted.push(c);
+                        backslashes = 0;
+                    }
+                }
+            }
+            // Doubled so the closing quote isn't escaped
+            quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+            quoted.push('"');
+            quoted
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
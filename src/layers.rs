@@ -0,0 +1,267 @@
+//! Layered configuration. The effective settings are built from, in order of
+//! increasing priority:
+//!
+//! 1. the launcher's built-in defaults,
+//! 2. `launcherdefaults.toml` shipped with the modpack,
+//! 3. the user's `launcherconfig.toml`,
+//! 4. `ANOMALY_LAUNCHER_*` environment variables,
+//! 5. `--set key=value` on the command line.
+//!
+//! Only the user layer is ever written back, so overrides from the
+//! environment or command line don't end up in `launcherconfig.toml`.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+use serde::Serialize;
+use toml::{Table, Value};
+
+use crate::{
+    app_config::{AppConfig, AppConfigError},
+    migrations,
+};
+
+/// Defaults file a modpack can ship next to the game.
+pub const MODPACK_DEFAULTS_FILE: &str = "launcherdefaults.toml";
+
+/// Prefix of environment overrides. The rest of the name is the key, with
+/// `__` separating table levels: `ANOMALY_LAUNCHER_RUNNER__KIND=Proton`.
+pub const ENV_PREFIX: &str = "ANOMALY_LAUNCHER_";
+
+/// Tables keyed by names the user picks; those keys keep their case in environment overrides.
+const NAMED_TABLES: [&str; 2] = ["profiles", "runner.env"];
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layer {
+    Default,
+    Modpack,
+    User,
+    Environment,
+    CommandLine,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::Default => write!(f, "default"),
+            Layer::Modpack => write!(f, "modpack ({})", MODPACK_DEFAULTS_FILE),
+            Layer::User => write!(f, "user (launcherconfig.toml)"),
+            Layer::Environment => write!(f, "environment"),
+            Layer::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+/// How a loaded [`AppConfig`] was put together.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    /// Defaults and modpack defaults merged.
+    base: Table,
+    /// The user's file as read.
+    user: Table,
+    /// Values set by the environment and command line.
+    overrides: Table,
+    origins: BTreeMap<String, Layer>,
+}
+
+impl ConfigLayers {
+    /// Layer of every effective value, keyed by dotted path.
+    pub fn origins(&self) -> &BTreeMap<String, Layer> {
+        &self.origins
+    }
+
+    /// Layer a dotted key came from; for a table, the highest layer among its values.
+    pub fn origin(&self, key: &str) -> Option<Layer> {
+        if let Some(layer) = self.origins.get(key) {
+            return Some(*layer);
+        }
+        let prefix = format!("{}.", key);
+        self.origins.iter().filter(|(path, _)| path.starts_with(&prefix)).map(|(_, layer)| *layer).max()
+    }
+
+    /// The part of `effective` that belongs in the user's file: values the user
+    /// had set or changed, minus untouched environment and command-line overrides.
+    pub fn user_table(&self, effective: &Table) -> Table {
+        self.user_values(effective, "")
+    }
+
+    fn user_values(&self, effective: &Table, prefix: &str) -> Table {
+        let mut out = Table::new();
+        for (key, value) in effective {
+            let path = format!("{}{}", prefix, key);
+            if let Value::Table(table) = value {
+                let nested = self.user_values(table, &format!("{}.", path));
+                if !nested.is_empty() {
+                    out.insert(key.clone(), Value::Table(nested));
+                }
+                continue;
+            }
+            let user_value = lookup_table(&self.user, &path);
+            let kept = if lookup_table(&self.overrides, &path) == Some(value) {
+                user_value.cloned()
+            } else if user_value.is_some() || lookup_table(&self.base, &path) != Some(value) {
+                Some(value.clone())
+            } else {
+                None
+            };
+            if let Some(kept) = kept {
+                out.insert(key.clone(), kept);
+            }
+        }
+        out
+    }
+}
+
+/// Merges the layers on top of the built-in defaults. `root` holds the modpack
+/// defaults file; `env` is usually [`std::env::vars`].
+pub fn resolve(
+    root: &Path,
+    user: Table,
+    env: impl IntoIterator<Item = (String, String)>,
+    cli: &[(String, String)],
+) -> Result<(AppConfig, ConfigLayers), AppConfigError> {
+    let mut merged = to_table(&AppConfig::default())?;
+    let mut origins = BTreeMap::new();
+    record(&Value::Table(merged.clone()), "", Layer::Default, &mut origins);
+
+    match fs::read_to_string(root.join(MODPACK_DEFAULTS_FILE)) {
+        Ok(content) => {
+            let mut modpack: Table = toml::from_str(&content).map_err(|e| layer_error(MODPACK_DEFAULTS_FILE, e.message()))?;
+            migrations::migrate(&mut modpack);
+            merge(&mut merged, &modpack, "", Layer::Modpack, &mut origins);
+            validate(&merged, MODPACK_DEFAULTS_FILE)?;
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(layer_error(MODPACK_DEFAULTS_FILE, &e.to_string())),
+    }
+    let base = merged.clone();
+    merge(&mut merged, &user, "", Layer::User, &mut origins);
+
+    let mut env_vars: Vec<(String, String)> = env.into_iter().filter(|(name, _)| name.starts_with(ENV_PREFIX)).collect();
+    env_vars.sort();
+    let env_overrides = env_vars.into_iter().map(|(name, raw)| {
+        let key = env_key(&name[ENV_PREFIX.len()..]);
+        (Layer::Environment, name, key, raw)
+    });
+    let cli_overrides = cli.iter().map(|(key, raw)| (Layer::CommandLine, format!("--set {}", key), key.clone(), raw.clone()));
+
+    let mut overrides = Table::new();
+    for (layer, source, key, raw) in env_overrides.chain(cli_overrides) {
+        let value = parse_value(&raw);
+        if key.is_empty() || !insert(&mut merged, &key, value.clone()) {
+            return Err(layer_error(&source, &format!("'{}' is not a configuration key", key)));
+        }
+        insert(&mut overrides, &key, value.clone());
+        origins.retain(|path, _| path != &key && !path.starts_with(&format!("{}.", key)));
+        record(&value, &key, layer, &mut origins);
+        validate(&merged, &source)?;
+        let known = Value::Table(merged.clone()).try_into::<AppConfig>().is_ok_and(|config| is_known_key(&config, &key));
+        if !known {
+            return Err(layer_error(&source, &format!("'{}' is not a configuration key", key)));
+        }
+    }
+
+    let config = Value::Table(merged).try_into::<AppConfig>().map_err(|e| layer_error("configuration", e.message()))?;
+    Ok((config, ConfigLayers { base, user, overrides, origins }))
+}
+
+/// Dotted key of an environment override name: `RUNNER__ENV__DXVK_HUD` is `runner.env.DXVK_HUD`.
+fn env_key(name: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for part in name.split("__") {
+        let named = NAMED_TABLES.contains(&parts.join(".").as_str());
+        parts.push(if named { part.to_string() } else { part.to_ascii_lowercase() });
+    }
+    parts.join(".")
+}
+
+pub(crate) fn to_table(config: &AppConfig) -> Result<Table, AppConfigError> {
+    match Value::try_from(config) {
+        Ok(Value::Table(table)) => Ok(table),
+        Ok(_) => Err(AppConfigError::WriteFailed("configuration is not a table".to_string())),
+        Err(e) => Err(AppConfigError::WriteFailed(e.to_string())),
+    }
+}
+
+fn layer_error(source: &str, message: &str) -> AppConfigError {
+    AppConfigError::BadOverride { source: source.to_string(), message: message.trim().to_string() }
+}
+
+fn validate(merged: &Table, source: &str) -> Result<(), AppConfigError> {
+    Value::Table(merged.clone()).try_into::<AppConfig>().map(|_| ()).map_err(|e| layer_error(source, e.message()))
+}
+
+fn merge(target: &mut Table, layer: &Table, prefix: &str, kind: Layer, origins: &mut BTreeMap<String, Layer>) {
+    for (key, value) in layer {
+        let path = format!("{}{}", prefix, key);
+        match (target.get_mut(key), value) {
+            (Some(Value::Table(existing)), Value::Table(nested)) => merge(existing, nested, &format!("{}.", path), kind, origins),
+            _ => {
+                origins.retain(|p, _| !p.starts_with(&format!("{}.", path)));
+                record(value, &path, kind, origins);
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn record(value: &Value, path: &str, kind: Layer, origins: &mut BTreeMap<String, Layer>) {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                let nested = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                record(value, &nested, kind, origins);
+            }
+        }
+        _ => {
+            origins.insert(path.to_string(), kind);
+        }
+    }
+}
+
+/// Interprets `raw` as a TOML value (`true`, `42`, `"text"`, `[1, 2]`), treating anything else as a bare string.
+pub fn parse_value(raw: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+/// Whether `key` is a setting of `config`. Keys [`AppConfig`] doesn't know are
+/// dropped when it is deserialized, so they are missing from its table.
+pub fn is_known_key(config: &AppConfig, key: &str) -> bool {
+    to_table(config).is_ok_and(|table| lookup_table(&table, key).is_some())
+}
+
+pub fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(value, |value, part| value.get(part))
+}
+
+fn lookup_table<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (first, rest) = match key.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (key, None),
+    };
+    let value = table.get(first)?;
+    match rest {
+        Some(rest) => lookup(value, rest),
+        None => Some(value),
+    }
+}
+
+/// Sets a dotted key, creating intermediate tables. Fails when a part of the path is not a table.
+pub fn insert(table: &mut Table, key: &str, new_value: Value) -> bool {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().unwrap_or_default();
+    let mut current = table;
+    for part in parts {
+        let entry = current.entry(part.to_string()).or_insert_with(|| Value::Table(Table::new()));
+        let Some(nested) = entry.as_table_mut() else {
+            return false;
+        };
+        current = nested;
+    }
+    current.insert(last.to_string(), new_value);
+    true
+}
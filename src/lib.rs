@@ -0,0 +1,62 @@
+//! Core of the Dead Reckoning launcher for S.T.A.L.K.E.R. Anomaly.
+//!
+//! The GUI binary is a thin layer over this crate, so other tools can read and
+//! write the launcher configuration, start the game and install patches the
+//! same way the launcher does:
+//!
+//! ```no_run
+//! use anomaly_launcher::{AppConfig, Game, Updater};
+//!
+//! let config = AppConfig::load()?;
+//! let new_version = Updater::new(config.clone())?.update(|_| {})?;
+//! println!("updated to {}", new_version);
+//!
+//! Game::new(&config).launch(config.launch_args())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! All paths are resolved against the current directory, which is expected to
+//! be the game folder. The configuration itself is kept per user unless the
+//! install is portable, see [`config_location`].
+
+pub mod addons;
+pub mod app_config;
+pub mod config_location;
+pub mod conflicts;
+pub mod crash_analyzer;
+pub mod diagnostics;
+pub mod fs_util;
+pub mod game;
+pub mod launch_args;
+pub mod layers;
+pub mod manifest;
+pub mod migrations;
+pub mod mo2;
+pub mod modlist;
+pub mod runner;
+pub mod save_backup;
+pub mod saves;
+pub mod session;
+pub mod shader_cache;
+pub mod system_info;
+pub mod updater;
+pub mod user_ltx;
+
+pub use addons::{AddonError, AddonStatus, InstalledAddons};
+pub use app_config::{AfterLaunch, AppConfig, AppConfigError, LaunchSettings, ProfileError, Renderer, ShadowMapSize};
+pub use config_location::ConfigLocation;
+pub use conflicts::{ConflictReport, FileConflict, SectionConflict};
+pub use crash_analyzer::{CrashReport, CrashRules, CrashRulesError, Diagnosis};
+pub use game::{ExecutableEntry, Game, GameError};
+pub use launch_args::LaunchFlags;
+pub use layers::{ConfigLayers, Layer};
+pub use manifest::{Manifest, ManifestAddon, ManifestPatch, PatchContents};
+pub use mo2::{Mo2Config, Mo2Instance};
+pub use modlist::{Deviation, ModEntry, ModList, ModState};
+pub use runner::{RunnerConfig, RunnerKind};
+pub use save_backup::{BackupReason, SaveBackup, SaveBackupConfig};
+pub use saves::SaveGame;
+pub use session::{Session, SessionRecord};
+pub use shader_cache::{ShaderCacheConfig, ShaderCacheError, ShaderCacheUsage};
+pub use updater::{PatchInfo, SaveBreakChoice, SaveBreakWarning, UpdateProgress, Updater, UpdaterError};
+pub use user_ltx::{GraphicsSettings, ScreenMode, UserLtx};
@@ -1,25 +1,27 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::{
-    env, fmt, fs,
-    path::{Path, PathBuf},
-    process::exit,
-    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    env,
+    fs,
+    path::Path,
+    process::{exit, Command},
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
 };
 
-mod app_config;
-mod game;
+mod cli;
 mod styles;
-mod updater;
+mod views;
 
-use app_config::{AppConfig, Renderer, ShadowMapSize};
+use anomaly_launcher::{
+    saves, session::SESSION_LOG, shader_cache, system_info::CpuFeatures, app_config::CONFIG_FILE, fs_util, AfterLaunch, AppConfig, ExecutableEntry, Game, GameError, LaunchSettings,
+    Mo2Config, Renderer, RunnerConfig, SaveBreakChoice, SaveBreakWarning, Session, SessionRecord, ShadowMapSize, Updater, UpdaterError,
+};
 use eframe::egui::{
-    self, vec2, Button, ComboBox, FontData, FontDefinitions, FontFamily, IconData, RichText, Stroke, Vec2, ViewportBuilder,
+    self, vec2, Button, Checkbox, Color32, ComboBox, Popup, ViewportCommand, FontData, FontDefinitions, FontFamily, IconData, RichText, Stroke, Vec2, ViewportBuilder,
 };
-use game::Game;
 use rfd::MessageDialog;
 use styles::Styles;
-use updater::{Updater, UpdaterError};
+use views::{addons::AddonsView, conflicts::ConflictsView, crash_log::CrashLogView, graphics::GraphicsView, launch_options::LaunchOptionsView, mo2::Mo2View, mod_list::ModListView, profiles::ProfilesView, runner::RunnerView, save_backups::SaveBackupsView, saves::SavesView, shader_cache::ShaderCacheView, system_info::SystemInfoView};
 
 fn show_error(title: &str, desc: &str) {
     MessageDialog::new()
@@ -57,86 +59,346 @@ fn load_fonts() -> FontDefinitions {
 }
 
 fn main() -> eframe::Result<()> {
-    if !Path::new("launcherconfig.toml").exists() {
-        let default_config = AppConfig::default();
-        let _ = default_config.write();
+    if env::args_os().len() > 1 {
+        exit(cli::run());
+    }
+
+    // Игра, которую окно передало на наблюдение перед тем как закрыться
+    let handed_over: Arc<Mutex<Option<Session>>> = Arc::new(Mutex::new(None));
+    let mut last_session: Option<SessionRecord> = None;
+
+    loop {
+        let icon_data = match  load_icon_data() {
+            Ok(data) => Arc::new(data),
+            Err(_) => {show_error("Icon Error", "Failed to load application icon."); exit(1);},
+        };
+
+        let viewport = ViewportBuilder::default()
+            .with_maximize_button(false)
+            .with_resizable(false)
+            .with_inner_size(Vec2 { x: 500.0, y: 265.0 })
+            .with_icon(icon_data);
+
+        let app_handed_over = handed_over.clone();
+        let app_last_session = last_session.clone();
+        eframe::run_native(
+            "Dead Reckoning",
+            eframe::NativeOptions {
+                viewport,
+                vsync: false,
+                centered: true,
+                ..Default::default()
+            },
+            Box::new(|cc| {
+                Ok(Box::new(LauncherApp::new(cc, app_handed_over, app_last_session)))
+            }),
+        )?;
+
+        // Окно закрыто в режиме "Hide": ждём игру и возвращаем лаунчер только после вылета
+        let session = handed_over.lock().ok().and_then(|mut guard| guard.take());
+        let Some(session) = session else {
+            break;
+        };
+        let config = AppConfig::load().unwrap_or_default();
+        match watch_session(session, &config) {
+            Some(record) if record.is_abnormal() => last_session = Some(record),
+            _ => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Waits for the game, logs the session and reports a crash, offering to collect a diagnostic bundle.
+fn watch_session(session: Session, config: &AppConfig) -> Option<SessionRecord> {
+    let record = match session.wait() {
+        Ok(record) => record,
+        Err(e) => {
+            show_error("Game Monitoring Failed", &format!("Lost track of the game process: {}", e));
+            return None;
+        }
+    };
+
+    let _ = record.append_to_log(Path::new(SESSION_LOG));
+    // Запоминаем, с какой версией сборки сделаны новые сохранения
+    let _ = saves::track(Path::new("."), config.version.as_deref());
+    if record.is_abnormal() {
+        let collect = MessageDialog::new()
+            .set_title("Game crashed")
+            .set_description(format!("The game exited unexpectedly ({}).\n\nCollect the logs, crash dumps and settings into a diagnostic bundle you can send to the modpack team?", record.summary()))
+            .set_level(rfd::MessageLevel::Error)
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if collect == rfd::MessageDialogResult::Yes {
+            views::crash_log::create_bundle(config);
+        }
+    }
+    Some(record)
+}
+
+/// Opens a file or folder with the program the system associates with it.
+fn open_path(path: &Path) -> std::io::Result<()> {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+    command.arg(path).spawn().map(|_| ())
+}
+
+/// Loads the configuration, letting the user repair a broken file instead of exiting.
+fn load_config() -> AppConfig {
+    let path = AppConfig::location().path().to_path_buf();
+    loop {
+        let err = match AppConfig::load() {
+            Ok(config) => return config,
+            Err(err) => err,
+        };
+
+        let reset = "Reset to defaults";
+        let open = "Open file";
+        let keep_editing = "Keep editing";
+        let choice = MessageDialog::new()
+            .set_title("Configuration error")
+            .set_description(format!("{}\n\nReset to defaults keeps the broken file as '{}.broken'. Open file shows '{}' in your text editor.", err, CONFIG_FILE, path.display()))
+            .set_level(rfd::MessageLevel::Error)
+            .set_buttons(rfd::MessageButtons::YesNoCancelCustom(reset.to_string(), open.to_string(), keep_editing.to_string()))
+            .show();
+
+        match choice {
+            rfd::MessageDialogResult::Custom(label) if label == reset => {
+                // Без файла остаются значения по умолчанию и настройки модпака
+                if let Err(e) = fs::rename(&path, fs_util::with_suffix(&path, ".broken")) {
+                    show_error("Reset Failed", &format!("Failed to move '{}' aside: {}", path.display(), e));
+                }
+                continue;
+            }
+            rfd::MessageDialogResult::Custom(label) if label == open => {
+                if let Err(e) = open_path(&path) {
+                    show_error("Open File", &format!("Failed to open '{}': {}", path.display(), e));
+                }
+            }
+            _ => {}
+        }
+
+        // Ждём, пока пользователь исправит файл, и проверяем его снова
+        let retry = MessageDialog::new()
+            .set_title("Configuration error")
+            .set_description(format!("Fix '{}' and press OK to check it again, or Cancel to quit.", path.display()))
+            .set_level(rfd::MessageLevel::Info)
+            .set_buttons(rfd::MessageButtons::OkCancel)
+            .show();
+        if retry != rfd::MessageDialogResult::Ok {
+            exit(1);
+        }
     }
+}
+
+/// Asks before installing patches that break the saves; called from the update thread.
+fn confirm_save_break(warning: &SaveBreakWarning) -> SaveBreakChoice {
+    let versions: Vec<String> = warning.versions.iter().map(|v| v.to_string()).collect();
+    let mut names: Vec<String> = warning.saves.iter().take(10).map(|save| format!("  {}", save.name)).collect();
+    if warning.saves.len() > names.len() {
+        names.push(format!("  ...and {} more", warning.saves.len() - names.len()));
+    }
+    let description = format!(
+        "Version {} can't load saves made before it. These saves will stop working:\n\n{}\n\n{}",
+        versions.join(", "),
+        names.join("\n"),
+        if warning.backup_planned { "Your saves are backed up before the update." } else { "Back up your saves first to be able to go back to them." }
+    );
+    let dialog = MessageDialog::new().set_title("Update breaks saves").set_description(description).set_level(rfd::MessageLevel::Warning);
+    if warning.backup_planned {
+        return match dialog.set_buttons(rfd::MessageButtons::OkCancelCustom("Update".to_string(), "Cancel".to_string())).show() {
+            rfd::MessageDialogResult::Custom(choice) if choice == "Update" => SaveBreakChoice::Update,
+            rfd::MessageDialogResult::Ok => SaveBreakChoice::Update,
+            _ => SaveBreakChoice::Cancel,
+        };
+    }
+    let buttons = rfd::MessageButtons::YesNoCancelCustom("Back Up and Update".to_string(), "Update".to_string(), "Cancel".to_string());
+    match dialog.set_buttons(buttons).show() {
+        rfd::MessageDialogResult::Custom(choice) if choice == "Back Up and Update" => SaveBreakChoice::BackUpAndUpdate,
+        rfd::MessageDialogResult::Custom(choice) if choice == "Update" => SaveBreakChoice::Update,
+        rfd::MessageDialogResult::Yes => SaveBreakChoice::BackUpAndUpdate,
+        rfd::MessageDialogResult::No => SaveBreakChoice::Update,
+        _ => SaveBreakChoice::Cancel,
+    }
+}
 
-    let icon_data = match  load_icon_data() {
-        Ok(data) => Arc::new(data),
-        Err(_) => {show_error("Icon Error", "Failed to load application icon."); exit(1);},
+/// Warns when the latest save predates a save-breaking patch; true to launch anyway.
+fn confirm_outdated_save() -> bool {
+    let Some((save, record)) = saves::outdated_latest_save(Path::new(".")) else {
+        return true;
     };
+    let answer = MessageDialog::new()
+        .set_title("Old save")
+        .set_description(format!(
+            "Your latest save '{}' was made before version {}, which can't load it. Loading it will most likely crash the game.\n\nPlay anyway?",
+            save.name, record.version
+        ))
+        .set_level(rfd::MessageLevel::Warning)
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    answer == rfd::MessageDialogResult::Yes
+}
 
-    let viewport = ViewportBuilder::default()
-        .with_maximize_button(false)
-        .with_resizable(false)
-        .with_inner_size(Vec2 { x: 500.0, y: 225.0 })
-        .with_icon(icon_data);
-
-    eframe::run_native(
-        "Dead Reckoning",
-        eframe::NativeOptions {
-            viewport,
-            vsync: false,
-            centered: true,
-            ..Default::default()
+fn show_launch_error(error: GameError) {
+    match error {
+        GameError::ExecutableNotFound => {
+            MessageDialog::new()
+                .set_title("Executable not found")
+                .set_description("Could not find the executable file of the game. Make sure you run the launcher from the game folder.")
+                .set_level(rfd::MessageLevel::Error)
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        },
+        GameError::Unknown(i) => {
+            MessageDialog::new()
+                .set_title("Unknown error occured")
+                .set_description(format!("The launcher failed to launch the game due to an unexpected error: {}",i))
+                .set_level(rfd::MessageLevel::Error)
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
         },
-        Box::new(|cc| {
-            Ok(Box::new(LauncherApp::new(cc)))
-        }),
-    )
+    }
 }
 
+/// Settings [`Game::new`] and the launch arguments depend on.
+type CommandLineInputs = (LaunchSettings, Vec<ExecutableEntry>, RunnerConfig, Mo2Config);
+
 struct LauncherApp {
     config: AppConfig,
+    // Подсказка кнопки Play; пересобирается только при смене настроек
+    command_line: Option<(CommandLineInputs, String)>,
     app_shutdown: bool,
     is_updating: Arc<AtomicBool>,
-    new_version: Arc<std::sync::Mutex<Option<String>>>,
-    config_update: Arc<std::sync::Mutex<Option<AppConfig>>>,
+    new_version: Arc<Mutex<Option<String>>>,
+    config_update: Arc<Mutex<Option<AppConfig>>>,
+    game_running: Arc<AtomicBool>,
+    last_session: Arc<Mutex<Option<SessionRecord>>>,
+    handed_over: Arc<Mutex<Option<Session>>>,
+    closing_for_game: bool,
+    cpu: CpuFeatures,
+    system_info: SystemInfoView,
+    runner: RunnerView,
+    crash_log: CrashLogView,
+    launch_options: LaunchOptionsView,
+    mo2: Mo2View,
+    mod_list: ModListView,
+    conflicts: ConflictsView,
+    addons: AddonsView,
+    save_backups: SaveBackupsView,
+    saves: SavesView,
+    shader_cache: ShaderCacheView,
+    profiles: ProfilesView,
+    graphics: GraphicsView,
+    // Поднимается наблюдателем, когда игра вылетела, пока лаунчер был свёрнут
+    crash_detected: Arc<AtomicBool>,
 }
 
 impl LauncherApp {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let config = AppConfig::load().unwrap_or_else(|err| {
-            match err {
-                app_config::AppConfigError::ReadFailed => show_error("Read Failed", "Failed to read the configuration file. Please remove 'launcherconfig.toml' and try to launch program again."),
-                app_config::AppConfigError::BadStructure => show_error("Bad configuration", "Your configuration seems to be damaged. Please remove 'launcherconfig.toml' and try to launch program again."),
-                app_config::AppConfigError::WriteFailed => todo!(),
-            };
-            exit(1);
-        });
+    fn new(
+        cc: &eframe::CreationContext<'_>,
+        handed_over: Arc<Mutex<Option<Session>>>,
+        last_session: Option<SessionRecord>,
+    ) -> Self {
+        let config = load_config();
 
         cc.egui_ctx.set_fonts(load_fonts());
 
+        let mut crash_log = CrashLogView::new();
+        if last_session.as_ref().is_some_and(|record| record.is_abnormal()) {
+            crash_log.open_latest();
+        }
+
         LauncherApp {
             config,
             app_shutdown: false,
             is_updating: Arc::new(AtomicBool::new(false)),
-            new_version: Arc::new(std::sync::Mutex::new(None)),
-            config_update: Arc::new(std::sync::Mutex::new(None)),
+            new_version: Arc::new(Mutex::new(None)),
+            config_update: Arc::new(Mutex::new(None)),
+            game_running: Arc::new(AtomicBool::new(false)),
+            last_session: Arc::new(Mutex::new(last_session)),
+            handed_over,
+            closing_for_game: false,
+            command_line: None,
+            cpu: CpuFeatures::detect(),
+            system_info: SystemInfoView::new(),
+            runner: RunnerView::new(),
+            crash_log,
+            launch_options: LaunchOptionsView::new(),
+            mo2: Mo2View::new(),
+            mod_list: ModListView::new(),
+            conflicts: ConflictsView::new(),
+            addons: AddonsView::new(),
+            save_backups: SaveBackupsView::new(),
+            saves: SavesView::new(),
+            shader_cache: ShaderCacheView::new(),
+            profiles: ProfilesView::new(),
+            graphics: GraphicsView::new(),
+            crash_detected: Arc::new(AtomicBool::new(false)),
         }
     }
-}
 
-impl fmt::Display for Renderer {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Renderer::DX8 => write!(f, "DirectX 8"),
-            Renderer::DX9 => write!(f, "DirectX 9"),
-            Renderer::DX10 => write!(f, "DirectX 10"),
-            Renderer::DX11 => write!(f, "DirectX 11"),
+    /// Command line the game would be started with. `Game::new` probes for MO2,
+    /// so the result is kept until the settings change.
+    fn command_line(&mut self) -> &str {
+        let inputs = (self.config.active().clone(), self.config.executables.clone(), self.config.runner.clone(), self.config.mo2.clone());
+        if self.command_line.as_ref().is_none_or(|(cached, _)| *cached != inputs) {
+            let command_line = Game::new(&self.config).command_line(&self.config.launch_args());
+            self.command_line = Some((inputs, command_line));
         }
+        self.command_line.as_ref().map(|(_, command_line)| command_line.as_str()).unwrap_or_default()
     }
-}
 
-impl fmt::Display for ShadowMapSize {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ShadowMapSize::Size1536 => write!(f, "1536"),
-            ShadowMapSize::Size2048 => write!(f, "2048"),
-            ShadowMapSize::Size2560 => write!(f, "2560"),
-            ShadowMapSize::Size3072 => write!(f, "3072"),
-            ShadowMapSize::Size4096 => write!(f, "4096"),
+    fn after_launch(&mut self, ctx: &egui::Context, session: Session) {
+        if self.config.after_launch == AfterLaunch::Exit {
+            self.app_shutdown = true;
+            return;
+        }
+
+        // Лаунчер остаётся жить, поэтому сохраняем настройки сразу
+        if let Err(e) = self.config.write() {
+            show_error("Write Failed", &format!("{}. You might need to set your options again.", e));
+        }
+
+        match self.config.after_launch {
+            AfterLaunch::Minimize => {
+                self.game_running.store(true, Ordering::Relaxed);
+                let game_running = self.game_running.clone();
+                let last_session = self.last_session.clone();
+                let crash_detected = self.crash_detected.clone();
+                let config = self.config.clone();
+                let ctx_clone = ctx.clone();
+                std::thread::spawn(move || {
+                    let record = watch_session(session, &config);
+                    if record.as_ref().is_some_and(|record| record.is_abnormal()) {
+                        crash_detected.store(true, Ordering::Relaxed);
+                    }
+                    if let Ok(mut guard) = last_session.lock() {
+                        if record.is_some() {
+                            *guard = record;
+                        }
+                    }
+                    game_running.store(false, Ordering::Relaxed);
+                    ctx_clone.send_viewport_cmd(ViewportCommand::Minimized(false));
+                    ctx_clone.send_viewport_cmd(ViewportCommand::Focus);
+                    ctx_clone.request_repaint();
+                });
+                ctx.send_viewport_cmd(ViewportCommand::Minimized(true));
+            },
+            _ => {
+                if let Ok(mut guard) = self.handed_over.lock() {
+                    *guard = Some(session);
+                }
+                self.closing_for_game = true;
+                ctx.send_viewport_cmd(ViewportCommand::Close);
+            },
         }
     }
 }
@@ -167,6 +429,25 @@ impl eframe::App for LauncherApp {
                         ui.label(RichText::new("Dead Reckoning").size(24.0));
                         ui.horizontal(|ui| {
                             ui.label("Modpack by Eslider");
+                            ui.add_space(16.);
+                            ComboBox::from_id_salt("profile")
+                                .selected_text(self.config.profile_name().to_string())
+                                .width(110.)
+                                .show_ui(ui, |ui| {
+                                    ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
+                                    let selected = self.config.profile_name().to_string();
+                                    for name in self.config.profile_names() {
+                                        if ui.selectable_label(name == selected, &name).clicked() {
+                                            let _ = self.config.select_profile(&name);
+                                        }
+                                    }
+                                    ui.separator();
+                                    if ui.selectable_label(false, "Manage...").clicked() {
+                                        self.profiles.open = true;
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Launch profile");
 
                         });
                     });
@@ -180,26 +461,26 @@ impl eframe::App for LauncherApp {
                             ui.set_min_size(vec2(150., 100.));
                             ui.label(RichText::new("Renderer"));
                             ComboBox::from_id_salt("renderer")
-                                .selected_text(self.config.renderer.to_string())
+                                .selected_text(self.config.active().renderer.to_string())
                                 .width(150.)
                                 .show_ui(ui, |ui| {
                                     ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
-                                    ui.selectable_value(&mut self.config.renderer, Renderer::DX8, "DirectX 8");
-                                    ui.selectable_value(&mut self.config.renderer, Renderer::DX9, "DirectX 9");
-                                    ui.selectable_value(&mut self.config.renderer, Renderer::DX10, "DirectX 10");
-                                    ui.selectable_value(&mut self.config.renderer, Renderer::DX11, "DirectX 11");
+                                    ui.selectable_value(&mut self.config.active_mut().renderer, Renderer::DX8, "DirectX 8");
+                                    ui.selectable_value(&mut self.config.active_mut().renderer, Renderer::DX9, "DirectX 9");
+                                    ui.selectable_value(&mut self.config.active_mut().renderer, Renderer::DX10, "DirectX 10");
+                                    ui.selectable_value(&mut self.config.active_mut().renderer, Renderer::DX11, "DirectX 11");
                                 });
                             ui.label(RichText::new("Shadow Map Size"));
                             ComboBox::from_id_salt("shadow_map")
-                                .selected_text(self.config.shadow_map.to_string())
+                                .selected_text(self.config.active().shadow_map.to_string())
                                 .width(150.)
                                 .show_ui(ui, |ui| {
                                     ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
-                                    ui.selectable_value(&mut self.config.shadow_map, ShadowMapSize::Size1536, "1536");
-                                    ui.selectable_value(&mut self.config.shadow_map, ShadowMapSize::Size2048, "2048");
-                                    ui.selectable_value(&mut self.config.shadow_map, ShadowMapSize::Size2560, "2560");
-                                    ui.selectable_value(&mut self.config.shadow_map, ShadowMapSize::Size3072, "3072");
-                                    ui.selectable_value(&mut self.config.shadow_map, ShadowMapSize::Size4096, "4096");
+                                    ui.selectable_value(&mut self.config.active_mut().shadow_map, ShadowMapSize::Size1536, "1536");
+                                    ui.selectable_value(&mut self.config.active_mut().shadow_map, ShadowMapSize::Size2048, "2048");
+                                    ui.selectable_value(&mut self.config.active_mut().shadow_map, ShadowMapSize::Size2560, "2560");
+                                    ui.selectable_value(&mut self.config.active_mut().shadow_map, ShadowMapSize::Size3072, "3072");
+                                    ui.selectable_value(&mut self.config.active_mut().shadow_map, ShadowMapSize::Size4096, "4096");
                                 });
                                 // Отображаем версию, если она есть
                             // Сначала проверяем, есть ли новая версия
@@ -222,9 +503,29 @@ impl eframe::App for LauncherApp {
                         ui.vertical(|ui| {
                             ui.set_min_size(vec2(150., 100.));
                             ui.label(RichText::new("Misc settings"));
-                            ui.checkbox(&mut self.config.debug, "Debug Mode");
-                            ui.checkbox(&mut self.config.prefetch_sounds, "Prefetch Sounds");
-                            ui.checkbox(&mut self.config.use_avx, "Use AVX");
+                            ui.checkbox(&mut self.config.active_mut().debug, "Debug Mode");
+                            ui.checkbox(&mut self.config.active_mut().prefetch_sounds, "Prefetch Sounds");
+                            // На процессорах без AVX флажок можно только снять
+                            let avx_supported = self.cpu.supports_avx_build();
+                            let avx_label = if avx_supported {
+                                RichText::new("Use AVX")
+                            } else {
+                                RichText::new("Use AVX").color(Color32::from_rgb(200, 70, 70))
+                            };
+                            ui.add_enabled(avx_supported || self.config.active().use_avx, Checkbox::new(&mut self.config.active_mut().use_avx, avx_label))
+                                .on_hover_text("Start the AVX build of the game. Requires a processor with AVX support.")
+                                .on_disabled_hover_text("Your processor does not support AVX.");
+                            ComboBox::from_id_salt("after_launch")
+                                .selected_text(self.config.after_launch.to_string())
+                                .width(150.)
+                                .show_ui(ui, |ui| {
+                                    ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
+                                    ui.selectable_value(&mut self.config.after_launch, AfterLaunch::Exit, "Close launcher");
+                                    ui.selectable_value(&mut self.config.after_launch, AfterLaunch::Minimize, "Minimize launcher");
+                                    ui.selectable_value(&mut self.config.after_launch, AfterLaunch::Hide, "Hide launcher");
+                                })
+                                .response
+                                .on_hover_text("Keep the launcher running while the game is open to report crashes.");
                         });
                         
                     });
@@ -233,7 +534,20 @@ impl eframe::App for LauncherApp {
                     
                 });
                 ui.vertical(|ui| {
-                    let play_button = ui.add_sized([180., 65.], Button::new("Play"));
+                    let game_running = self.game_running.load(Ordering::Relaxed);
+                    let play_text = if game_running { "Running..." } else { "Play" };
+                    let mut play_button = ui.add_enabled_ui(!game_running, |ui| {
+                        ui.add_sized([180., 65.], Button::new(play_text))
+                    }).inner;
+                    if let Some(record) = self.last_session.lock().ok().and_then(|guard| guard.clone()) {
+                        play_button = play_button.on_hover_text(format!("Last session: {}", record.summary()));
+                    }
+                    if play_button.hovered() {
+                        let command_line = self.command_line().to_string();
+                        play_button = play_button.on_hover_ui(|ui| {
+                            ui.label(RichText::new(command_line).monospace());
+                        });
+                    }
                     
                     // Добавляем кнопку обновления, если настроен URL
                     if self.config.update_url.is_some() {
@@ -257,10 +571,13 @@ impl eframe::App for LauncherApp {
                             std::thread::spawn(move || {
                                 match Updater::new(config_clone.clone()) {
                                     Ok(mut updater) => {
-                                        let result = updater.update(|_progress| {
-                                            // Обновляем UI при изменении прогресса
-                                            ctx_clone.request_repaint();
-                                        });
+                                        let result = updater.update_with_confirmation(
+                                            |_progress| {
+                                                // Обновляем UI при изменении прогресса
+                                                ctx_clone.request_repaint();
+                                            },
+                                            confirm_save_break,
+                                        );
                                         
                                         // Сбрасываем флаг обновления
                                         is_updating_clone.store(false, Ordering::Relaxed);
@@ -283,10 +600,10 @@ impl eframe::App for LauncherApp {
                                                 }
                                                 
                                                 // Сохраняем обновленную конфигурацию в файл
-                                                if let Err(_e) = updated_config.write() {
+                                                if let Err(e) = updated_config.write() {
                                                     MessageDialog::new()
                                                         .set_title("Configuration Save Error")
-                                                        .set_description(format!("Failed to save updated configuration:"))
+                                                        .set_description(format!("Failed to save updated configuration: {}", e))
                                                         .set_level(rfd::MessageLevel::Error)
                                                         .set_buttons(rfd::MessageButtons::Ok)
                                                         .show();
@@ -300,6 +617,7 @@ impl eframe::App for LauncherApp {
                                                     .set_buttons(rfd::MessageButtons::Ok)
                                                     .show();
                                             },
+                                            Err(UpdaterError::Cancelled) => {},
                                             Err(UpdaterError::NoUpdatesAvailable) => {
                                                 MessageDialog::new()
                                                     .set_title("No Updates Available")
@@ -336,71 +654,78 @@ impl eframe::App for LauncherApp {
                     }
                     
                     let clear_button = ui.add_sized([180., 35.], Button::new("Clear Shader Cache"));
+                    let tools_button = ui.add_sized([180., 35.], Button::new("Tools"));
+                    Popup::menu(&tools_button).show(|ui| {
+                        if ui.button("Game Settings").clicked() {
+                            self.graphics.open();
+                        }
+                        if ui.button("Launch Profiles").clicked() {
+                            self.profiles.open = true;
+                        }
+                        if ui.button("Launch Options").clicked() {
+                            self.launch_options.open = true;
+                        }
+                        if ui.button("Mod Organizer 2").clicked() {
+                            self.mo2.open(&self.config.mo2);
+                        }
+                        if ui.button("Mod List").clicked() {
+                            self.mod_list.open(&self.config.mo2);
+                        }
+                        if ui.button("Mod Conflicts").clicked() {
+                            self.conflicts.open(ctx, &self.config.mo2);
+                        }
+                        if ui.button("Addons").clicked() {
+                            self.addons.open(ctx, &self.config);
+                        }
+                        if ui.button("Saves").clicked() {
+                            self.saves.open(&self.config);
+                        }
+                        if ui.button("Save Backups").clicked() {
+                            self.save_backups.open();
+                        }
+                        if ui.button("System Information").clicked() {
+                            self.system_info.open = true;
+                        }
+                        if ui.button("Crash Log").clicked() {
+                            self.crash_log.open_latest();
+                        }
+                        if ui.button("Create Diagnostic Bundle").clicked() {
+                            views::crash_log::create_bundle(&self.config);
+                        }
+                        if !cfg!(windows) && ui.button("Wine / Proton").clicked() {
+                            self.runner.open = true;
+                        }
+                    });
                     let about_button = ui.add_sized([180., 35.], Button::new("About Launcher"));
                     let quit_button = ui.add_sized([180., 35.], Button::new("Quit"));
-                    if play_button.clicked() {
-                        let game = Game::new(self.config.renderer, self.config.use_avx);
-                        let mut args: Vec<String> = Vec::new();
-                        let shadows_arg: String = match self.config.shadow_map {
-                            ShadowMapSize::Size1536 => "-smap1536".to_string(),
-                            ShadowMapSize::Size2048 => "-smap2048".to_string(),
-                            ShadowMapSize::Size2560 => "-smap2560".to_string(),
-                            ShadowMapSize::Size3072 => "-smap3072".to_string(),
-                            ShadowMapSize::Size4096 => "-smap4096".to_string(),
-                        };
-                        args.push(shadows_arg);
-                        if self.config.debug {
-                            args.push("-dbg".to_string());
+                    if play_button.clicked() && self.config.active().avx_unsupported(&self.cpu) {
+                        let switch = MessageDialog::new()
+                            .set_title("AVX not supported")
+                            .set_description("Your processor does not support AVX, so the AVX build of the game will crash. Switch to the regular build and continue?")
+                            .set_level(rfd::MessageLevel::Warning)
+                            .set_buttons(rfd::MessageButtons::YesNo)
+                            .show();
+                        if switch == rfd::MessageDialogResult::Yes {
+                            self.config.active_mut().use_avx = false;
                         }
+                    }
 
-                        if self.config.prefetch_sounds {
-                            args.push("-prefetch_sounds".to_string());
+                    if play_button.clicked() && !self.config.active().avx_unsupported(&self.cpu) && confirm_outdated_save() {
+                        let game = Game::new(&self.config);
+                        let args = self.config.launch_args();
+                        // Кэш другого рендерера только мешает; игру запускаем в любом случае
+                        let root = env::current_dir().unwrap_or_default();
+                        if let Err(e) = shader_cache::prepare_launch(&root, &self.config.mo2, self.config.active().renderer, &self.config.shader_cache) {
+                            show_error("Clear Shader Cache", &e.to_string());
                         }
-                        let launch_result = game.launch(args);
-                        if let Err(e) = launch_result {
-                            match e {
-                                game::GameError::ExecutableNotFound => {
-                                    MessageDialog::new()
-                                        .set_title("Executable not found")
-                                        .set_description("Could not find the executable file of the game. Make sure you run the launcher from the game folder.")
-                                        .set_level(rfd::MessageLevel::Error)
-                                        .set_buttons(rfd::MessageButtons::Ok)
-                                        .show();
-                                },
-                                game::GameError::Unknown(i) => {
-                                    MessageDialog::new()
-                                        .set_title("Unknown error occured")
-                                        .set_description(format!("The launcher failed to launch the game due to an unexpected error: {}",i))
-                                        .set_level(rfd::MessageLevel::Error)
-                                        .set_buttons(rfd::MessageButtons::Ok)
-                                        .show();
-                                },
-                            }
-                        } else {
-                            self.app_shutdown = true;
+                        match game.spawn(args) {
+                            Err(e) => show_launch_error(e),
+                            Ok(session) => self.after_launch(ctx, session),
                         }
                     }
 
                     if clear_button.clicked() {
-                        let mut cache_path: PathBuf = env::current_dir().unwrap();
-                        cache_path.push("appdata\\shaders_cache");
-                        if !cache_path.exists() {
-                            let _ = MessageDialog::new()
-                            .set_title("Path not found")
-                            .set_description("The launcher cannot find the shader cache folder. Make sure you run the launcher in the Anomaly game folder.")
-                            .set_level(rfd::MessageLevel::Error)
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .show();
-                        } else {
-                            fs::remove_dir_all(cache_path.clone()).unwrap();
-                            fs::create_dir(cache_path.clone()).unwrap();
-                            MessageDialog::new()
-                            .set_title("Clear Shader Cache")
-                            .set_description("Shader cache has been deleted.")
-                            .set_level(rfd::MessageLevel::Info)
-                            .set_buttons(rfd::MessageButtons::Ok)
-                            .show();
-                        }
+                        self.shader_cache.open(&self.config);
                     }
 
                     if about_button.clicked() {
@@ -423,15 +748,32 @@ https://github.com/kostya-zero/AnomalyLauncher"#).show();
             });
         });
 
+        self.system_info.show(ctx, &self.cpu);
+        self.runner.show(ctx, &mut self.config.runner);
+        self.launch_options.show(ctx, &mut self.config);
+        self.mo2.show(ctx, &mut self.config.mo2);
+        self.mod_list.show(ctx, &self.config.mo2);
+        self.conflicts.show(ctx, &self.config.mo2);
+        self.addons.show(ctx, &self.config);
+        self.saves.show(ctx, &self.config);
+        self.save_backups.show(ctx, &mut self.config);
+        self.shader_cache.show(ctx, &mut self.config);
+        self.profiles.show(ctx, &mut self.config);
+        self.graphics.show(ctx, self.game_running.load(Ordering::Relaxed));
+        if self.crash_detected.swap(false, Ordering::Relaxed) {
+            self.crash_log.open_latest();
+        }
+        self.crash_log.show(ctx, &self.config);
+
         // Handle close via close button
-        if ctx.input(|i| i.viewport().close_requested()) {
+        if ctx.input(|i| i.viewport().close_requested()) && !self.closing_for_game {
             self.app_shutdown = true;
         }
 
         if self.app_shutdown {
             match self.config.write() {
                 Ok(_) => {},
-                Err(_) => show_error("Write Failed", "Failed to write data to configuration file. You might need to set your options again."),
+                Err(e) => show_error("Write Failed", &format!("{}. You might need to set your options again.", e)),
             };
             exit(0);
         }
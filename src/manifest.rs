@@ -0,0 +1,212 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Read},
+    path::{Component, Path},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Name of the file inside a patch archive that lists its hashes and deletions.
+pub const PATCH_INFO_FILE: &str = ".patchinfo.toml";
+
+/// Update list published at [`crate::AppConfig::update_url`].
+///
+/// The TOML form carries hashes and signatures. A plain list of
+/// `patch-X.Y.Z.zip` URLs, one per line, is still accepted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default, rename = "patch")]
+    pub patches: Vec<ManifestPatch>,
+    /// Optional addons users can install into Mod Organizer 2, see [`crate::addons`].
+    #[serde(default, rename = "addon", skip_serializing_if = "Vec::is_empty")]
+    pub addons: Vec<ManifestAddon>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestPatch {
+    pub version: Version,
+    pub url: String,
+    /// SHA-256 of the whole archive, lowercase hex.
+    pub sha256: Option<String>,
+    /// Base64 Ed25519 signature of the raw SHA-256 digest.
+    pub signature: Option<String>,
+    /// Saves made before this version can't be loaded after it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub breaks_saves: bool,
+}
+
+/// An optional addon, published as `[[addon]]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestAddon {
+    /// Stable identifier used by `requires` and `conflicts`.
+    pub id: String,
+    /// Shown to users and used as the MO2 mod folder name.
+    pub name: String,
+    pub version: Version,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// SHA-256 of the archive, lowercase hex.
+    pub sha256: Option<String>,
+    /// Base64 Ed25519 signature of the raw SHA-256 digest.
+    pub signature: Option<String>,
+    /// Addons that must be installed first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    /// Addons that can't be installed together with this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+}
+
+/// Contents of [`PATCH_INFO_FILE`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchContents {
+    pub version: Option<Version>,
+    /// Version the patch was generated against.
+    pub from: Option<Version>,
+    /// Files removed from the game folder after extraction, relative to its root.
+    #[serde(default)]
+    pub deleted: Vec<String>,
+    /// SHA-256 of every file in the archive, keyed by its path.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Parses the TOML form, or the legacy URL list when `content` has no
+    /// `[[patch]]` or `[[addon]]` tables. A broken TOML manifest is an error
+    /// rather than an empty URL list.
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        if is_toml_manifest(content) {
+            let mut manifest = toml::from_str::<Manifest>(content)?;
+            // The id names the download and the fallback mod folder, which gets deleted on removal
+            manifest.addons.retain(|addon| is_safe_file_name(&addon.id));
+            return Ok(manifest);
+        }
+
+        let mut patches = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(version) = version_from_file_name(line) {
+                patches.push(ManifestPatch {
+                    version,
+                    url: line.to_string(),
+                    sha256: None,
+                    signature: None,
+                    breaks_saves: false,
+                });
+            }
+        }
+        Ok(Manifest { patches, addons: Vec::new() })
+    }
+
+    /// Adds a patch, replacing any entry with the same version, and keeps the list sorted.
+    pub fn upsert(&mut self, patch: ManifestPatch) {
+        self.patches.retain(|p| p.version != patch.version);
+        self.patches.push(patch);
+        self.patches.sort_by(|a, b| a.version.cmp(&b.version));
+    }
+
+    /// Resolves relative patch and addon URLs, such as the bare file names
+    /// patch-publisher writes without `--base-url`, against the manifest's own URL.
+    pub fn resolve_urls(&mut self, manifest_url: &str) {
+        let Ok(base) = reqwest::Url::parse(manifest_url) else {
+            return;
+        };
+        let urls = self.patches.iter_mut().map(|patch| &mut patch.url).chain(self.addons.iter_mut().map(|addon| &mut addon.url));
+        for url in urls {
+            if let Ok(resolved) = base.join(url) {
+                *url = resolved.to_string();
+            }
+        }
+    }
+
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+}
+
+fn is_toml_manifest(content: &str) -> bool {
+    content.lines().any(|line| {
+        let table: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+        table.starts_with("[[patch]]") || table.starts_with("[[addon]]")
+    })
+}
+
+/// Extracts the version from a `.../patch-X.Y.Z.zip` URL or path.
+pub fn version_from_file_name(url: &str) -> Option<Version> {
+    let file_name = Path::new(url).file_name()?.to_str()?;
+    let version = file_name.strip_prefix("patch-")?.strip_suffix(".zip")?;
+    Version::parse(version).ok()
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 65536];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Checks that a relative path from a patch stays inside the game folder.
+pub fn is_safe_relative_path(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Checks that `name` is a single file or folder name, with no separators on any platform.
+pub fn is_safe_file_name(name: &str) -> bool {
+    is_safe_relative_path(name) && !name.contains(['/', '\\', ':'])
+}
+
+/// Signs a hex SHA-256 digest with a base64 Ed25519 secret key.
+pub fn sign_digest(secret_key: &str, sha256: &str) -> Result<String, String> {
+    let secret: [u8; 32] = decode_key(secret_key)?;
+    let digest = decode_hex(sha256)?;
+    let signature = SigningKey::from_bytes(&secret).sign(&digest);
+    Ok(BASE64.encode(signature.to_bytes()))
+}
+
+/// Verifies a signature produced by [`sign_digest`] against a base64 public key.
+pub fn verify_digest(public_key: &str, sha256: &str, signature: &str) -> Result<(), String> {
+    let public: [u8; 32] = decode_key(public_key)?;
+    let key = VerifyingKey::from_bytes(&public).map_err(|e| format!("Invalid public key: {}", e))?;
+    let signature = BASE64
+        .decode(signature.trim())
+        .map_err(|e| format!("Invalid signature encoding: {}", e))
+        .and_then(|bytes| Signature::from_slice(&bytes).map_err(|e| format!("Invalid signature: {}", e)))?;
+    key.verify(&decode_hex(sha256)?, &signature)
+        .map_err(|_| "Signature does not match".to_string())
+}
+
+fn decode_key(key: &str) -> Result<[u8; 32], String> {
+    BASE64
+        .decode(key.trim())
+        .map_err(|e| format!("Invalid key encoding: {}", e))?
+        .try_into()
+        .map_err(|_| "Key must be 32 bytes long".to_string())
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(format!("Invalid SHA-256 digest: {}", hex));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("Invalid SHA-256 digest: {}", hex)))
+        .collect()
+}
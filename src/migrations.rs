@@ -0,0 +1,82 @@
+//! Upgrades of `launcherconfig.toml` written by older launchers.
+//!
+//! Each step works on the raw TOML table, so fields can be renamed or
+//! reshaped before [`crate::AppConfig`] is deserialized. To change the format,
+//! bump [`SCHEMA_VERSION`] and append a step to [`MIGRATIONS`].
+
+use toml::{Table, Value};
+
+/// Schema version written by this launcher.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` upgrades a table from version `n` to `n + 1`.
+/// Files without `schema_version` are version 0.
+const MIGRATIONS: [fn(&mut Table); SCHEMA_VERSION as usize] = [upgrade_legacy_file];
+
+pub fn schema_version(table: &Table) -> u32 {
+    table
+        .get("schema_version")
+        .and_then(Value::as_integer)
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(0)
+}
+
+/// Brings `table` up to [`SCHEMA_VERSION`]. Returns the version it started
+/// at when any step ran. Files from newer launchers are left as they are.
+pub fn migrate(table: &mut Table) -> Option<u32> {
+    let from = schema_version(table);
+    if from >= SCHEMA_VERSION {
+        return None;
+    }
+    for step in &MIGRATIONS[from as usize..] {
+        step(table);
+    }
+    table.insert("schema_version".to_string(), Value::Integer(SCHEMA_VERSION.into()));
+    Some(from)
+}
+
+/// 0 → 1: files from before schema versioning.
+fn upgrade_legacy_file(table: &mut Table) {
+    normalize_legacy_values(table);
+    invert_use_avx(table);
+}
+
+/// Older launchers started the regular build for `use_avx = true` and the
+/// AVX build for `false`. Inverting the flag keeps the executable the game
+/// was started with. Profiles didn't exist yet, so only the top level has it.
+fn invert_use_avx(table: &mut Table) {
+    if let Some(Value::Boolean(use_avx)) = table.get_mut("use_avx") {
+        *use_avx = !*use_avx;
+    }
+}
+
+/// Accepts the hand-edited forms of enum values, such as
+/// `shadow_map = 2048` or `renderer = "DirectX 11"`.
+fn normalize_legacy_values(table: &mut Table) {
+    normalize_launch_settings(table);
+    // Profiles are newer than schema 0, but may have been added by hand
+    if let Some(Value::Table(profiles)) = table.get_mut("profiles") {
+        for profile in profiles.iter_mut().filter_map(|(_, profile)| profile.as_table_mut()) {
+            normalize_launch_settings(profile);
+        }
+    }
+}
+
+fn normalize_launch_settings(section: &mut Table) {
+    if let Some(value) = section.get_mut("shadow_map") {
+        let size = match value {
+            Value::Integer(size) => Some(size.to_string()),
+            Value::String(size) if size.chars().all(|c| c.is_ascii_digit()) => Some(size.clone()),
+            _ => None,
+        };
+        if let Some(size) = size {
+            *value = Value::String(format!("Size{}", size));
+        }
+    }
+    if let Some(Value::String(renderer)) = section.get_mut("renderer") {
+        let name = renderer.to_ascii_uppercase().replace("DIRECTX", "DX").replace(' ', "");
+        if ["DX8", "DX9", "DX10", "DX11"].contains(&name.as_str()) {
+            *renderer = name;
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Mod Organizer 2 instances. Modpacks install their mods into a portable MO2
+//! instance and start the game through it, so the virtual file system puts
+//! the mods into the game folder.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::join_relative;
+
+pub const MO2_EXECUTABLE: &str = "ModOrganizer.exe";
+pub const MO2_INI: &str = "ModOrganizer.ini";
+
+/// Folders searched for a portable instance, relative to the game folder.
+const SEARCH_DIRS: [&str; 5] = [".", "MO2", "ModOrganizer", "..", "../MO2"];
+
+/// MO2 settings stored as `[mo2]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mo2Config {
+    /// Launch through MO2 when an instance is found.
+    pub enabled: bool,
+    /// Folder with `ModOrganizer.exe`; searched for next to the game when unset.
+    pub path: Option<PathBuf>,
+    /// MO2 profile to launch with; the one selected in MO2 when unset.
+    pub profile: Option<String>,
+}
+
+impl Default for Mo2Config {
+    fn default() -> Self {
+        Self { enabled: true, path: None, profile: None }
+    }
+}
+
+/// An entry of MO2's executables list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mo2Executable {
+    pub title: String,
+    pub binary: String,
+    pub arguments: String,
+}
+
+/// A portable MO2 instance: `ModOrganizer.exe` with its `ModOrganizer.ini`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mo2Instance {
+    pub dir: PathBuf,
+    pub game_name: Option<String>,
+    /// Profile selected in MO2 the last time it was closed.
+    pub selected_profile: Option<String>,
+    pub mods_dir: PathBuf,
+    pub profiles_dir: PathBuf,
+    pub executables: Vec<Mo2Executable>,
+}
+
+impl Mo2Instance {
+    /// Finds the instance set in `config`, or one next to the game folder `root`.
+    pub fn detect(root: &Path, config: &Mo2Config) -> Option<Self> {
+        let candidates: Vec<PathBuf> = match &config.path {
+            Some(path) => vec![root.join(path)],
+            None => SEARCH_DIRS.iter().map(|dir| join_relative(root, dir)).collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|dir| dir.join(MO2_EXECUTABLE).is_file())
+            .find_map(|dir| Mo2Instance::load(&dir).ok())
+    }
+
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let bytes = fs::read(dir.join(MO2_INI))?;
+        Ok(Mo2Instance::parse(dir, &String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Reads the instance settings from the contents of `ModOrganizer.ini`.
+    pub fn parse(dir: &Path, ini: &str) -> Self {
+        let entries = parse_ini(ini);
+        let get = |section: &str, key: &str| {
+            entries.iter().find(|(s, k, _)| s == section && k == key).map(|(_, _, value)| value.clone()).filter(|value| !value.is_empty())
+        };
+
+        let base_dir = get("Settings", "base_directory").map(|path| resolve_path(dir, dir, &path)).unwrap_or_else(|| dir.to_path_buf());
+        let mods_dir = get("Settings", "mod_directory").map(|path| resolve_path(dir, &base_dir, &path)).unwrap_or_else(|| base_dir.join("mods"));
+        let profiles_dir =
+            get("Settings", "profiles_directory").map(|path| resolve_path(dir, &base_dir, &path)).unwrap_or_else(|| base_dir.join("profiles"));
+
+        let count: usize = get("customExecutables", "size").and_then(|size| size.parse().ok()).unwrap_or(0);
+        let executables = (1..=count)
+            .filter_map(|i| {
+                let field = |name: &str| get("customExecutables", &format!("{}\\{}", i, name));
+                Some(Mo2Executable { title: field("title")?, binary: field("binary")?, arguments: field("arguments").unwrap_or_default() })
+            })
+            .collect();
+
+        Mo2Instance {
+            dir: dir.to_path_buf(),
+            game_name: get("General", "gameName"),
+            selected_profile: get("General", "selected_profile"),
+            mods_dir,
+            profiles_dir,
+            executables,
+        }
+    }
+
+    pub fn executable(&self) -> PathBuf {
+        self.dir.join(MO2_EXECUTABLE)
+    }
+
+    /// Names of the profiles in the instance, sorted.
+    pub fn profiles(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(&self.profiles_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort_by_key(|name| name.to_lowercase());
+        names
+    }
+
+    pub fn profile_dir(&self, profile: &str) -> PathBuf {
+        self.profiles_dir.join(profile)
+    }
+
+    /// `wanted` if the instance has that profile, otherwise the one selected in MO2.
+    pub fn resolve_profile(&self, wanted: Option<&str>) -> Option<String> {
+        let profiles = self.profiles();
+        wanted
+            .filter(|name| profiles.iter().any(|p| p == name))
+            .map(str::to_string)
+            .or_else(|| self.selected_profile.clone())
+            .or_else(|| profiles.into_iter().next())
+    }
+}
+
+/// Arguments for `ModOrganizer.exe` to start `executable` with `args` in `profile`.
+pub fn run_args(profile: &str, executable: &str, args: &str) -> Vec<String> {
+    let mut run = vec!["-p".to_string(), profile.to_string(), "run".to_string()];
+    if !args.is_empty() {
+        run.push("-a".to_string());
+        run.push(args.to_string());
+    }
+    run.push(executable.to_string());
+    run
+}
+
+/// Entries of a Qt INI file as (section, key, value), with `@ByteArray()`,
+/// quotes and escapes removed from the values.
+fn parse_ini(content: &str) -> Vec<(String, String, String)> {
+    let mut section = String::new();
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = name.to_string();
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            entries.push((section.clone(), key.trim().replace("\\\\", "\\"), unquote(value.trim())));
+        }
+    }
+    entries
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+    let value = value.strip_prefix("@ByteArray(").and_then(|v| v.strip_suffix(')')).unwrap_or(value);
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Resolves a path from `ModOrganizer.ini`, where `%BASE_DIR%` stands for the
+/// base directory. Windows paths that don't exist here, such as the drive
+/// letters of another machine, fall back to the folder name under `base_dir`.
+fn resolve_path(instance: &Path, base_dir: &Path, value: &str) -> PathBuf {
+    let value = value.replace("%BASE_DIR%", &base_dir.to_string_lossy());
+    let is_windows_absolute = value.len() > 2 && value.as_bytes()[1] == b':';
+    let path = if is_windows_absolute {
+        // Wine maps the root of the Unix file system to Z:
+        let unix = (!cfg!(windows) && value[..1].eq_ignore_ascii_case("z")).then(|| PathBuf::from(value[2..].replace('\\', "/")));
+        unix.unwrap_or_else(|| PathBuf::from(&value))
+    } else if Path::new(&value).is_absolute() {
+        PathBuf::from(&value)
+    } else {
+        join_relative(instance, &value)
+    };
+    if path.exists() || cfg!(windows) {
+        return path;
+    }
+    let name = value.rsplit(['/', '\\']).find(|part| !part.is_empty()).unwrap_or_default();
+    base_dir.join(name)
+}
@@ -0,0 +1,212 @@
+//! MO2 profile mod lists and the modpack's reference list they are checked against.
+//!
+//! `modlist.txt` holds one mod per line, highest priority first: `+Name` is
+//! enabled, `-Name` disabled and `*Name` a mod MO2 doesn't manage (DLC and the
+//! like), which is always active.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::fs_util;
+
+pub const MODLIST_FILE: &str = "modlist.txt";
+pub const LOADORDER_FILE: &str = "loadorder.txt";
+/// The modpack's mod list, shipped in the game folder in `modlist.txt` format.
+pub const REFERENCE_MODLIST: &str = "reference_modlist.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModState {
+    Enabled,
+    Disabled,
+    Unmanaged,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModEntry {
+    pub name: String,
+    pub state: ModState,
+}
+
+impl ModEntry {
+    pub fn is_active(&self) -> bool {
+        self.state != ModState::Disabled
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModList {
+    /// In file order, highest priority first.
+    pub entries: Vec<ModEntry>,
+    line_ending: &'static str,
+}
+
+impl ModList {
+    /// Parses `modlist.txt`; comments and blank lines are dropped.
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                let state = match line.chars().next()? {
+                    '+' => ModState::Enabled,
+                    '-' => ModState::Disabled,
+                    '*' => ModState::Unmanaged,
+                    _ => return None,
+                };
+                Some(ModEntry { name: line[1..].to_string(), state })
+            })
+            .filter(|entry| !entry.name.is_empty())
+            .collect();
+        ModList { entries, line_ending: if content.contains("\r\n") { "\r\n" } else { "\n" } }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(ModList::parse(&String::from_utf8_lossy(&fs::read(path)?)))
+    }
+
+    /// Atomically writes this list in place of `path`. The first save keeps
+    /// the original as `modlist.txt.bak`, later ones leave it alone.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs_util::backup_once(path, &fs_util::with_suffix(path, ".bak"))?;
+        fs_util::write_atomic(path, self.to_string().as_bytes())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ModEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Entries with the priority MO2 shows, lowest priority (0) first.
+    pub fn by_priority(&self) -> impl Iterator<Item = (usize, &ModEntry)> {
+        self.entries.iter().rev().enumerate()
+    }
+
+    /// Names of the mods MO2 manages, highest priority first, active or not.
+    fn managed(&self) -> Vec<&str> {
+        self.entries.iter().filter(|entry| entry.state != ModState::Unmanaged).map(|entry| entry.name.as_str()).collect()
+    }
+}
+
+impl fmt::Display for ModList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "# This file was automatically generated by Mod Organizer.{}", self.line_ending)?;
+        for entry in &self.entries {
+            let prefix = match entry.state {
+                ModState::Enabled => '+',
+                ModState::Disabled => '-',
+                ModState::Unmanaged => '*',
+            };
+            write!(f, "{}{}{}", prefix, entry.name, self.line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plugins from `loadorder.txt`, in load order.
+pub fn load_order(path: &Path) -> io::Result<Vec<String>> {
+    let content = String::from_utf8_lossy(&fs::read(path)?).to_string();
+    Ok(content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(str::to_string).collect())
+}
+
+pub fn modlist_path(profile_dir: &Path) -> PathBuf {
+    profile_dir.join(MODLIST_FILE)
+}
+
+/// How a profile's mod list differs from the reference list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "lowercase")]
+pub enum Deviation {
+    /// Enabled in the reference list but disabled here.
+    Disabled(String),
+    /// Enabled here but not part of the reference list, or disabled in it.
+    Enabled(String),
+    /// In the reference list but not in the profile at all.
+    Missing(String),
+    /// Both lists have it, in a different order.
+    Moved(String),
+}
+
+impl Deviation {
+    pub fn name(&self) -> &str {
+        match self {
+            Deviation::Disabled(name) | Deviation::Enabled(name) | Deviation::Missing(name) | Deviation::Moved(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Deviation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Deviation::Disabled(name) => write!(f, "'{}' is disabled", name),
+            Deviation::Enabled(name) => write!(f, "'{}' is enabled but not part of the modpack", name),
+            Deviation::Missing(name) => write!(f, "'{}' is missing", name),
+            Deviation::Moved(name) => write!(f, "'{}' is out of order", name),
+        }
+    }
+}
+
+/// Differences of `active` from `reference`, in reference order followed by extra mods.
+pub fn compare(active: &ModList, reference: &ModList) -> Vec<Deviation> {
+    let mut deviations = Vec::new();
+    for expected in reference.entries.iter().filter(|entry| entry.state != ModState::Unmanaged) {
+        match active.get(&expected.name) {
+            None if expected.is_active() => deviations.push(Deviation::Missing(expected.name.clone())),
+            Some(actual) if expected.is_active() && !actual.is_active() => deviations.push(Deviation::Disabled(expected.name.clone())),
+            _ => {}
+        }
+    }
+    for actual in active.entries.iter().filter(|entry| entry.state == ModState::Enabled) {
+        if !reference.get(&actual.name).is_some_and(ModEntry::is_active) {
+            deviations.push(Deviation::Enabled(actual.name.clone()));
+        }
+    }
+
+    // Mods both lists have that fall outside their longest common order were moved
+    let expected: Vec<&str> = reference.managed().into_iter().filter(|name| active.get(name).is_some()).collect();
+    let actual: Vec<&str> = active.managed().into_iter().filter(|name| reference.get(name).is_some()).collect();
+    let in_order = longest_common_subsequence(&expected, &actual);
+    for name in expected {
+        if !in_order.contains(&name) {
+            deviations.push(Deviation::Moved(name.to_string()));
+        }
+    }
+    deviations
+}
+
+/// The reference list, with mods the profile has on top of it kept at the lowest priority, disabled.
+pub fn restore(active: &ModList, reference: &ModList) -> ModList {
+    let mut restored = ModList { entries: reference.entries.clone(), line_ending: active.line_ending };
+    for entry in &active.entries {
+        if reference.get(&entry.name).is_none() && entry.state != ModState::Unmanaged {
+            restored.entries.push(ModEntry { name: entry.name.clone(), state: ModState::Disabled });
+        }
+    }
+    restored
+}
+
+fn longest_common_subsequence<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<&'a str> {
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] { lengths[i + 1][j + 1] + 1 } else { lengths[i + 1][j].max(lengths[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut common = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            common.push(a[i]);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    common
+}
@@ -0,0 +1,107 @@
+use std::{
+    collections::BTreeMap,
+    env, fmt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How the Windows game executable is started.
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RunnerKind {
+    /// Spawn the executable directly (Windows).
+    Native,
+    /// `wine <exe> <args>`.
+    Wine,
+    /// `proton run <exe> <args>`, outside of Steam.
+    Proton,
+}
+
+impl fmt::Display for RunnerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunnerKind::Native => write!(f, "Native"),
+            RunnerKind::Wine => write!(f, "Wine"),
+            RunnerKind::Proton => write!(f, "Proton"),
+        }
+    }
+}
+
+/// Runner settings stored as `[runner]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunnerConfig {
+    pub kind: RunnerKind,
+    /// Wine binary or Proton script; `wine` or `proton` from `PATH` when unset.
+    pub binary: Option<String>,
+    /// `WINEPREFIX` for Wine, `STEAM_COMPAT_DATA_PATH` for Proton.
+    pub prefix: Option<PathBuf>,
+    /// Use the DXVK DLLs installed in the prefix instead of WineD3D.
+    pub dxvk: bool,
+    /// Extra environment variables, applied last.
+    pub env: BTreeMap<String, String>,
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self {
+            kind: if cfg!(windows) { RunnerKind::Native } else { RunnerKind::Wine },
+            binary: None,
+            prefix: None,
+            dxvk: true,
+            env: BTreeMap::new(),
+        }
+    }
+}
+
+/// Direct3D DLLs that DXVK replaces.
+const DXVK_DLLS: &str = "d3d9,d3d10core,d3d11,dxgi";
+
+impl RunnerConfig {
+    /// Builds the command that starts `executable` with `args` through this runner.
+    pub fn command(&self, executable: &Path, args: &[String]) -> Command {
+        let mut cmd = match self.kind {
+            RunnerKind::Native => Command::new(executable),
+            RunnerKind::Wine => {
+                let mut cmd = Command::new(self.binary.as_deref().unwrap_or("wine"));
+                cmd.arg(executable);
+                if let Some(prefix) = &self.prefix {
+                    cmd.env("WINEPREFIX", prefix);
+                }
+                let overrides = if self.dxvk { "n,b" } else { "b" };
+                cmd.env("WINEDLLOVERRIDES", format!("{}={}", DXVK_DLLS, overrides));
+                cmd
+            }
+            RunnerKind::Proton => {
+                let mut cmd = Command::new(self.binary.as_deref().unwrap_or("proton"));
+                cmd.arg("run").arg(executable);
+                if let Some(prefix) = &self.prefix {
+                    cmd.env("STEAM_COMPAT_DATA_PATH", prefix);
+                }
+                if env::var_os("STEAM_COMPAT_CLIENT_INSTALL_PATH").is_none() {
+                    if let Some(home) = env::var_os("HOME") {
+                        cmd.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", Path::new(&home).join(".steam").join("steam"));
+                    }
+                }
+                if !self.dxvk {
+                    cmd.env("PROTON_USE_WINED3D", "1");
+                }
+                cmd
+            }
+        };
+        cmd.args(args);
+        cmd.envs(&self.env);
+        cmd
+    }
+
+    /// `path` as a program started by this runner sees it: Wine maps the
+    /// root of the Unix file system to drive `Z:`.
+    pub fn windows_path(&self, path: &Path) -> String {
+        let path = path.to_string_lossy();
+        match self.kind {
+            RunnerKind::Wine | RunnerKind::Proton if path.starts_with('/') => format!("Z:{}", path.replace('/', "\\")),
+            _ => path.to_string(),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Zip backups of the game's saves, taken before updates and on demand.
+//!
+//! Backups are stored in [`BACKUPS_DIR`] as
+//! `saves_<date>-<time>_<modpack version>_<reason>.zip`, so they can be listed
+//! without opening them.
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize, Serializer};
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::fs_util;
+
+/// Folder the game keeps its saves in, relative to the game folder.
+pub const SAVES_DIR: &str = "appdata/savedfiles";
+pub const BACKUPS_DIR: &str = "backups/saves";
+
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Backup settings stored as `[save_backups]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveBackupConfig {
+    /// Back up the saves before the updater applies a patch.
+    pub before_update: bool,
+    /// Number of backups to keep; 0 keeps all of them.
+    pub keep: usize,
+    /// Backups older than this are deleted, except the newest one.
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for SaveBackupConfig {
+    fn default() -> Self {
+        Self { before_update: true, keep: 10, max_age_days: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupReason {
+    Manual,
+    Update,
+    /// Taken right before another backup was restored.
+    Restore,
+}
+
+impl BackupReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BackupReason::Manual => "manual",
+            BackupReason::Update => "update",
+            BackupReason::Restore => "restore",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [BackupReason::Manual, BackupReason::Update, BackupReason::Restore].into_iter().find(|reason| reason.as_str() == value)
+    }
+}
+
+impl fmt::Display for BackupReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupReason::Manual => write!(f, "manual"),
+            BackupReason::Update => write!(f, "before update"),
+            BackupReason::Restore => write!(f, "before restore"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SaveBackup {
+    pub path: PathBuf,
+    #[serde(serialize_with = "serialize_time")]
+    pub created: NaiveDateTime,
+    /// Modpack version the saves were backed up with.
+    pub version: Option<String>,
+    pub reason: BackupReason,
+    /// Size of the archive in bytes.
+    pub size: u64,
+}
+
+impl SaveBackup {
+    /// Reads the details of a backup from its file name.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let rest = name.strip_prefix("saves_")?.strip_suffix(".zip")?;
+        let (time, rest) = rest.split_once('_')?;
+        let (version, reason) = rest.rsplit_once('_')?;
+        Some(SaveBackup {
+            path: path.to_path_buf(),
+            created: NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?,
+            version: (version != "unknown").then(|| version.to_string()),
+            reason: BackupReason::parse(reason)?,
+            size: fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.path.file_name().unwrap_or_default().to_string_lossy().to_string()
+    }
+}
+
+/// Zips the saves in `root` into a new backup. Returns `None` when there are no saves to back up.
+pub fn create(root: &Path, version: Option<&str>, reason: BackupReason) -> io::Result<Option<SaveBackup>> {
+    let saves = root.join(SAVES_DIR);
+    let files = if saves.is_dir() { fs_util::relative_files(&saves)? } else { Vec::new() };
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let dir = root.join(BACKUPS_DIR);
+    fs::create_dir_all(&dir)?;
+    // Keep the separators of the name out of the version so it can be read back
+    let version = version.map(|v| v.replace(['_', '/', '\\'], "-")).unwrap_or_else(|| "unknown".to_string());
+    let name = format!("saves_{}_{}_{}.zip", Local::now().format(TIME_FORMAT), version, reason.as_str());
+    let path = dir.join(&name);
+    let partial = fs_util::with_suffix(&path, ".partial");
+
+    let result = (|| {
+        let mut zip = ZipWriter::new(File::create(&partial)?);
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated).large_file(true);
+        for relative in &files {
+            zip.start_file(relative.as_str(), options)?;
+            io::copy(&mut File::open(saves.join(relative))?, &mut zip)?;
+        }
+        zip.finish()?.sync_all()?;
+        fs::rename(&partial, &path)
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    Ok(SaveBackup::from_path(&path))
+}
+
+/// Backups in `root`, newest first.
+pub fn list(root: &Path) -> Vec<SaveBackup> {
+    let mut backups: Vec<SaveBackup> = fs::read_dir(root.join(BACKUPS_DIR))
+        .map(|entries| entries.flatten().filter_map(|entry| SaveBackup::from_path(&entry.path())).collect())
+        .unwrap_or_default();
+    backups.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| b.path.cmp(&a.path)));
+    backups
+}
+
+/// Deletes the backups the retention rules don't keep and returns their paths.
+/// The newest backup is always kept.
+pub fn prune(root: &Path, config: &SaveBackupConfig) -> io::Result<Vec<PathBuf>> {
+    let now = Local::now().naive_local();
+    let mut deleted = Vec::new();
+    for (index, backup) in list(root).into_iter().enumerate().skip(1) {
+        let too_many = config.keep > 0 && index >= config.keep;
+        let too_old = config.max_age_days.is_some_and(|days| (now - backup.created).num_days() >= i64::from(days));
+        if too_many || too_old {
+            fs::remove_file(&backup.path)?;
+            deleted.push(backup.path);
+        }
+    }
+    Ok(deleted)
+}
+
+/// Backs up the current saves, restores `backup` and applies the retention
+/// rules, which can't remove the backup being restored that way.
+pub fn restore_with_backup(root: &Path, backup: &Path, version: Option<&str>, config: &SaveBackupConfig) -> io::Result<()> {
+    create(root, version, BackupReason::Restore)?;
+    restore(root, backup)?;
+    prune(root, config)?;
+    Ok(())
+}
+
+/// Replaces the saves in `root` with the contents of a backup. The current
+/// saves are only removed once the backup has been extracted completely.
+pub fn restore(root: &Path, backup: &Path) -> io::Result<()> {
+    let saves = root.join(SAVES_DIR);
+    let staging = fs_util::with_suffix(&saves, ".restoring");
+    let old = fs_util::with_suffix(&saves, ".old");
+    for dir in [&staging, &old] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+
+    let result = (|| {
+        let mut zip = ZipArchive::new(File::open(backup)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::create_dir_all(&staging)?;
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            let Some(relative) = entry.enclosed_name().map(Path::to_path_buf) else {
+                continue;
+            };
+            let outpath = staging.join(relative);
+            if entry.is_dir() {
+                fs::create_dir_all(&outpath)?;
+                continue;
+            }
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut outfile = File::create(&outpath)?;
+            io::copy(&mut entry, &mut outfile)?;
+            outfile.flush()?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    if saves.exists() {
+        fs::rename(&saves, &old)?;
+    }
+    if let Err(e) = fs::rename(&staging, &saves) {
+        let _ = fs::rename(&old, &saves);
+        return Err(e);
+    }
+    if old.exists() {
+        fs::remove_dir_all(&old)?;
+    }
+    Ok(())
+}
+
+fn serialize_time<S: Serializer>(time: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&time.format("%Y-%m-%d %H:%M:%S"))
+}
@@ -0,0 +1,301 @@
+//! The game's saves: a `.scop` file with a `.dds` thumbnail next to it.
+//!
+//! The game doesn't record which modpack version a save was made with, so the
+//! launcher notes it in [`SAVE_VERSIONS_FILE`] whenever it sees a new or
+//! changed save, which is after every game session and before every update.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize, Serializer};
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{fs_util, save_backup::SAVES_DIR};
+
+pub const SAVE_EXTENSION: &str = "scop";
+pub const THUMBNAIL_EXTENSION: &str = "dds";
+/// Modpack versions of the saves, kept in the saves folder so backups carry them along.
+pub const SAVE_VERSIONS_FILE: &str = "launcher_saves.toml";
+/// The last save-breaking patch installed. Kept in the game folder, outside the
+/// saves, so restoring a backup doesn't undo it.
+pub const SAVE_BREAK_FILE: &str = "launcher_save_break.toml";
+/// Version info stored in archives made by [`export`].
+const EXPORT_INFO_FILE: &str = "launcher_save.toml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveVersion {
+    /// `None` for saves that already existed when the launcher started tracking them.
+    pub version: Option<String>,
+    /// Modification time of the save the version was noted for, in Unix seconds.
+    pub modified: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SaveVersions {
+    #[serde(default, rename = "save")]
+    saves: BTreeMap<String, SaveVersion>,
+}
+
+/// Contents of [`SAVE_BREAK_FILE`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveBreak {
+    pub version: String,
+    /// When the patch was installed, in Unix seconds.
+    pub installed: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SaveGame {
+    /// File name without the extension, as the game shows it.
+    pub name: String,
+    pub path: PathBuf,
+    pub thumbnail: Option<PathBuf>,
+    #[serde(serialize_with = "serialize_time")]
+    pub modified: DateTime<Local>,
+    /// Size of the save and its thumbnail in bytes.
+    pub size: u64,
+    pub version: Option<String>,
+}
+
+impl SaveGame {
+    fn from_path(path: &Path, versions: &SaveVersions) -> Option<Self> {
+        let name = path.file_stem()?.to_string_lossy().to_string();
+        let metadata = fs::metadata(path).ok()?;
+        let thumbnail = Some(path.with_extension(THUMBNAIL_EXTENSION)).filter(|thumbnail| thumbnail.is_file());
+        let thumbnail_size = thumbnail.as_ref().and_then(|thumbnail| fs::metadata(thumbnail).ok()).map(|m| m.len()).unwrap_or(0);
+        let modified = metadata.modified().ok()?;
+        // A save overwritten since its version was noted was made with an unknown version
+        let version = versions.saves.get(&name).filter(|entry| entry.modified == unix_seconds(modified)).and_then(|entry| entry.version.clone());
+        Some(SaveGame {
+            name,
+            path: path.to_path_buf(),
+            thumbnail,
+            modified: modified.into(),
+            size: metadata.len() + thumbnail_size,
+            version,
+        })
+    }
+
+    /// Decodes the `.dds` thumbnail.
+    pub fn load_thumbnail(&self) -> Option<image::RgbaImage> {
+        let thumbnail = self.thumbnail.as_ref()?;
+        Some(image::open(thumbnail).ok()?.into_rgba8())
+    }
+}
+
+pub fn saves_dir(root: &Path) -> PathBuf {
+    root.join(SAVES_DIR)
+}
+
+/// Saves in `root`, newest first.
+pub fn list(root: &Path) -> io::Result<Vec<SaveGame>> {
+    let dir = saves_dir(root);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let versions = load_versions(&dir)?;
+    let mut saves: Vec<SaveGame> = fs::read_dir(&dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_save(path))
+        .filter_map(|path| SaveGame::from_path(&path, &versions))
+        .collect();
+    saves.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| a.name.cmp(&b.name)));
+    Ok(saves)
+}
+
+/// Notes `version` for every save that is new or changed since it was last seen.
+/// The first time, the saves already there are noted with an unknown version.
+pub fn track(root: &Path, version: Option<&str>) -> io::Result<()> {
+    let dir = saves_dir(root);
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let first_time = !dir.join(SAVE_VERSIONS_FILE).exists();
+    let mut versions = load_versions(&dir)?;
+    let mut changed = first_time;
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        let (Some(name), Ok(modified)) = (path.file_stem(), entry.metadata().and_then(|m| m.modified())) else {
+            continue;
+        };
+        if !is_save(&path) {
+            continue;
+        }
+        let modified = unix_seconds(modified);
+        let name = name.to_string_lossy().to_string();
+        if versions.saves.get(&name).is_some_and(|entry| entry.modified == modified) {
+            continue;
+        }
+        let version = if first_time { None } else { version.map(str::to_string) };
+        versions.saves.insert(name, SaveVersion { version, modified });
+        changed = true;
+    }
+    // Forget saves deleted in the game
+    let before = versions.saves.len();
+    versions.saves.retain(|name, _| dir.join(format!("{}.{}", name, SAVE_EXTENSION)).exists());
+    if changed || versions.saves.len() != before {
+        save_versions(&dir, &versions)?;
+    }
+    Ok(())
+}
+
+/// Deletes a save and its thumbnail.
+pub fn delete(save: &SaveGame) -> io::Result<()> {
+    fs::remove_file(&save.path)?;
+    if let Some(thumbnail) = &save.thumbnail {
+        fs::remove_file(thumbnail)?;
+    }
+    if let Some(dir) = save.path.parent() {
+        let mut versions = load_versions(dir)?;
+        if versions.saves.remove(&save.name).is_some() {
+            save_versions(dir, &versions)?;
+        }
+    }
+    Ok(())
+}
+
+/// Zips a save, its thumbnail and its version to `archive`.
+pub fn export(save: &SaveGame, archive: &Path) -> io::Result<()> {
+    let mut zip = ZipWriter::new(File::create(archive)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for path in std::iter::once(&save.path).chain(save.thumbnail.as_ref()) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        zip.start_file(name, options)?;
+        io::copy(&mut File::open(path)?, &mut zip)?;
+    }
+    let info = SaveVersions {
+        saves: BTreeMap::from([(save.name.clone(), SaveVersion { version: save.version.clone(), modified: save.modified.timestamp() })]),
+    };
+    zip.start_file(EXPORT_INFO_FILE, options)?;
+    zip.write_all(toml::to_string(&info).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Copies a save into the game: a `.scop` file, with the `.dds` next to it if
+/// there is one, or an archive made by [`export`]. Existing saves are never
+/// overwritten. Returns the name of the imported save.
+pub fn import(root: &Path, source: &Path) -> io::Result<String> {
+    let dir = saves_dir(root);
+    fs::create_dir_all(&dir)?;
+    let is_archive = source.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    let (name, version) = if is_archive { import_archive(&dir, source)? } else { import_file(&dir, source)? };
+
+    // The copies are new files; note the version they were made with, not the current one
+    let mut versions = load_versions(&dir)?;
+    if let Ok(modified) = fs::metadata(dir.join(format!("{}.{}", name, SAVE_EXTENSION))).and_then(|m| m.modified()) {
+        versions.saves.insert(name.clone(), SaveVersion { version, modified: unix_seconds(modified) });
+        save_versions(&dir, &versions)?;
+    }
+    Ok(name)
+}
+
+/// Notes that a patch which breaks saves has just been installed.
+pub fn record_break(root: &Path, version: &str) -> io::Result<()> {
+    let record = SaveBreak { version: version.to_string(), installed: Local::now().timestamp() };
+    let content = toml::to_string(&record).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs_util::write_atomic(&root.join(SAVE_BREAK_FILE), content.as_bytes())
+}
+
+pub fn last_break(root: &Path) -> Option<SaveBreak> {
+    toml::from_str(&fs::read_to_string(root.join(SAVE_BREAK_FILE)).ok()?).ok()
+}
+
+/// The latest save, when it was made before the last save-breaking patch was installed.
+pub fn outdated_latest_save(root: &Path) -> Option<(SaveGame, SaveBreak)> {
+    let record = last_break(root)?;
+    let latest = list(root).ok()?.into_iter().next()?;
+    (latest.modified.timestamp() < record.installed).then_some((latest, record))
+}
+
+fn import_file(dir: &Path, source: &Path) -> io::Result<(String, Option<String>)> {
+    if !is_save(source) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a .{} save", source.display(), SAVE_EXTENSION)));
+    }
+    let name = source.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let target = dir.join(format!("{}.{}", name, SAVE_EXTENSION));
+    ensure_free(&target)?;
+    fs::copy(source, &target)?;
+    let thumbnail = source.with_extension(THUMBNAIL_EXTENSION);
+    if thumbnail.is_file() {
+        fs::copy(thumbnail, target.with_extension(THUMBNAIL_EXTENSION))?;
+    }
+    Ok((name, None))
+}
+
+fn import_archive(dir: &Path, source: &Path) -> io::Result<(String, Option<String>)> {
+    let invalid = |e: zip::result::ZipError| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+    let mut zip = ZipArchive::new(File::open(source)?).map_err(invalid)?;
+    // Only plain file names at the top level; `\\` counts as a separator on every platform
+    let files: Vec<(usize, String)> = (0..zip.len())
+        .filter_map(|i| {
+            let entry = zip.by_index(i).ok()?;
+            let name = entry.enclosed_name()?;
+            let mut components = name.components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(file)), None) if !entry.is_dir() => file.to_str().filter(|file| !file.contains('\\')).map(|file| (i, file.to_string())),
+                _ => None,
+            }
+        })
+        .collect();
+    let (_, save) = files
+        .iter()
+        .find(|(_, file)| is_save(Path::new(file)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{} doesn't contain a .{} save", source.display(), SAVE_EXTENSION)))?;
+    let name = Path::new(save).file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let target = dir.join(save);
+    ensure_free(&target)?;
+
+    let thumbnail = format!("{}.{}", name, THUMBNAIL_EXTENSION);
+    for (index, file) in files.iter().filter(|(_, file)| *file == *save || *file == thumbnail) {
+        let mut entry = zip.by_index(*index).map_err(invalid)?;
+        io::copy(&mut entry, &mut File::create(dir.join(file))?)?;
+    }
+
+    let mut info = String::new();
+    if let Ok(mut entry) = zip.by_name(EXPORT_INFO_FILE) {
+        entry.read_to_string(&mut info)?;
+    }
+    let version = toml::from_str::<SaveVersions>(&info).ok().and_then(|info| info.saves.get(&name).and_then(|entry| entry.version.clone()));
+    Ok((name, version))
+}
+
+fn ensure_free(target: &Path) -> io::Result<()> {
+    if target.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("a save named {} already exists", target.display())));
+    }
+    Ok(())
+}
+
+fn is_save(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(SAVE_EXTENSION))
+}
+
+fn load_versions(dir: &Path) -> io::Result<SaveVersions> {
+    match fs::read_to_string(dir.join(SAVE_VERSIONS_FILE)) {
+        // A damaged file only loses the versions, the saves themselves are fine
+        Ok(content) => Ok(toml::from_str(&content).unwrap_or_default()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(SaveVersions::default()),
+        Err(e) => Err(e),
+    }
+}
+
+fn save_versions(dir: &Path, versions: &SaveVersions) -> io::Result<()> {
+    let content = toml::to_string(versions).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs_util::write_atomic(&dir.join(SAVE_VERSIONS_FILE), content.as_bytes())
+}
+
+fn unix_seconds(time: std::time::SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+fn serialize_time<S: Serializer>(time: &DateTime<Local>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&time.format("%Y-%m-%d %H:%M:%S"))
+}
@@ -0,0 +1,166 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Child,
+    time::{Duration, Instant, SystemTime},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::diagnostics;
+
+/// File in the game folder every finished session is appended to.
+pub const SESSION_LOG: &str = "launcher_sessions.log";
+
+/// A running game process started by [`crate::Game::spawn`].
+///
+/// The launcher can only wait for the program it spawned. Under Wine that is
+/// the runner, which passes the game's exit code on. Under Mod Organizer 2 it
+/// is `ModOrganizer.exe`, whose exit code says nothing about the game, so
+/// such sessions are judged by the crash dumps and logs the game leaves
+/// behind instead, see [`Session::with_crash_dirs`].
+pub struct Session {
+    child: Child,
+    executable: PathBuf,
+    program: PathBuf,
+    crash_dirs: Option<Vec<PathBuf>>,
+    started: Instant,
+    started_at: DateTime<Local>,
+    started_time: SystemTime,
+}
+
+/// How a game session ended.
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    /// Game executable the session was started for.
+    pub executable: PathBuf,
+    /// Program that was spawned and waited for: the game itself, or the
+    /// runner or `ModOrganizer.exe` that starts it.
+    pub program: PathBuf,
+    pub started_at: DateTime<Local>,
+    pub runtime: Duration,
+    /// Exit code of [`SessionRecord::program`]; `None` when it was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// Crash dumps and logs with a fatal error written during the session,
+    /// when crashes are detected from them rather than the exit code.
+    pub crash_files: Option<Vec<PathBuf>>,
+}
+
+impl Session {
+    pub fn new(child: Child, executable: PathBuf, program: PathBuf) -> Self {
+        Session {
+            child,
+            executable,
+            program,
+            crash_dirs: None,
+            started: Instant::now(),
+            started_at: Local::now(),
+            started_time: SystemTime::now(),
+        }
+    }
+
+    /// Detects crashes from crash dumps and `FATAL ERROR` logs written to
+    /// `dirs` during the session, for when the exit code isn't the game's.
+    pub fn with_crash_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.crash_dirs = Some(dirs);
+        self
+    }
+
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Blocks until the spawned program exits.
+    pub fn wait(mut self) -> io::Result<SessionRecord> {
+        let status = self.child.wait()?;
+        // File systems with coarse timestamps may date a file written right
+        // after the start slightly before it.
+        let since = self.started_time - Duration::from_secs(2);
+        let crash_files = self.crash_dirs.as_deref().map(|dirs| crash_files(dirs, since));
+        Ok(SessionRecord {
+            executable: self.executable,
+            program: self.program,
+            started_at: self.started_at,
+            runtime: self.started.elapsed(),
+            exit_code: status.code(),
+            crash_files,
+        })
+    }
+}
+
+impl SessionRecord {
+    /// Anything but a clean zero exit code counts as a crash, unless crashes
+    /// are detected from the files the game wrote.
+    pub fn is_abnormal(&self) -> bool {
+        match &self.crash_files {
+            Some(files) => !files.is_empty(),
+            None => self.exit_code != Some(0),
+        }
+    }
+
+    /// Short description such as `exit code 0 after 1h 05m`.
+    pub fn summary(&self) -> String {
+        let code = match (&self.crash_files, self.exit_code) {
+            (Some(files), _) if !files.is_empty() => "crash report written".to_string(),
+            (_, Some(code)) => format!("exit code {}", code),
+            (_, None) => "terminated".to_string(),
+        };
+        format!("{} after {}", code, format_duration(self.runtime))
+    }
+
+    pub fn append_to_log(&self, path: &Path) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        write!(
+            file,
+            "{} runtime={}s exit_code={} {}",
+            self.started_at.format("%Y-%m-%d %H:%M:%S"),
+            self.runtime.as_secs(),
+            self.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "none".to_string()),
+            self.executable.display()
+        )?;
+        if self.program != self.executable {
+            write!(file, " via {}", self.program.display())?;
+        }
+        writeln!(file)
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60) {
+        (0, 0) => format!("{}s", secs),
+        (0, minutes) => format!("{}m {:02}s", minutes, secs % 60),
+        (hours, minutes) => format!("{}h {:02}m", hours, minutes),
+    }
+}
+
+/// Crash dumps and X-Ray logs with a `FATAL ERROR` in `dirs` modified since `since`.
+fn crash_files(dirs: &[PathBuf], since: SystemTime) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+            if !path.is_file() || modified.map_or(true, |modified| modified < since) {
+                continue;
+            }
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+            let crashed = match extension.as_str() {
+                "mdmp" | "dmp" => true,
+                "log" => fs::read(&path).is_ok_and(|log| {
+                    let log = String::from_utf8_lossy(&log);
+                    diagnostics::fatal_error_section(&log.lines().collect::<Vec<_>>()).is_some()
+                }),
+                _ => false,
+            };
+            if crashed {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
@@ -0,0 +1,216 @@
+//! The game's compiled shader cache, `appdata/shaders_cache`, with one folder per renderer.
+//!
+//! Under Mod Organizer 2 the game writes new files to MO2's `overwrite` folder,
+//! so the cache can live there as well.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_config::Renderer,
+    mo2::{Mo2Config, Mo2Instance},
+};
+
+pub const SHADER_CACHE_DIR: &str = "appdata/shaders_cache";
+/// Renderer the game was last launched with, kept in the cache folder it was found in.
+const RENDERER_MARKER: &str = "launcher_renderer.txt";
+
+pub const RENDERERS: [Renderer; 4] = [Renderer::DX8, Renderer::DX9, Renderer::DX10, Renderer::DX11];
+
+#[derive(Debug)]
+pub enum ShaderCacheError {
+    NotFound,
+    RemoveFailed(String),
+}
+
+impl fmt::Display for ShaderCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderCacheError::NotFound => write!(f, "Shader cache folder not found"),
+            ShaderCacheError::RemoveFailed(msg) => write!(f, "Failed to delete the shader cache: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ShaderCacheError {}
+
+/// Shader cache settings stored as `[shader_cache]` in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShaderCacheConfig {
+    /// Clear the cache when the game is launched with another renderer than last time.
+    pub clear_on_renderer_change: bool,
+    /// Clear the cache after installing a patch that changes `gamedata/shaders`.
+    pub clear_after_update: bool,
+}
+
+impl Default for ShaderCacheConfig {
+    fn default() -> Self {
+        Self { clear_on_renderer_change: true, clear_after_update: true }
+    }
+}
+
+/// Cache folder of a renderer: `r1` for DX8 up to `r4` for DX11.
+pub fn folder(renderer: Renderer) -> &'static str {
+    match renderer {
+        Renderer::DX8 => "r1",
+        Renderer::DX9 => "r2",
+        Renderer::DX10 => "r3",
+        Renderer::DX11 => "r4",
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShaderCacheUsage {
+    pub locations: Vec<PathBuf>,
+    /// Bytes per renderer, in [`RENDERERS`] order.
+    pub renderers: Vec<(Renderer, u64)>,
+    /// Bytes outside the renderer folders.
+    pub other: u64,
+}
+
+impl ShaderCacheUsage {
+    pub fn total(&self) -> u64 {
+        self.renderers.iter().map(|(_, size)| size).sum::<u64>() + self.other
+    }
+
+    pub fn size(&self, renderer: Renderer) -> u64 {
+        self.renderers.iter().find(|(r, _)| *r == renderer).map(|(_, size)| *size).unwrap_or(0)
+    }
+}
+
+/// Existing cache folders: the game's, then the one in MO2's `overwrite`.
+pub fn locations(root: &Path, mo2: &Mo2Config) -> Vec<PathBuf> {
+    let mut locations = vec![root.join(SHADER_CACHE_DIR)];
+    if let Some(instance) = Mo2Instance::detect(root, mo2) {
+        locations.push(instance.dir.join("overwrite").join(SHADER_CACHE_DIR));
+    }
+    locations.into_iter().filter(|location| location.is_dir()).collect()
+}
+
+pub fn usage(root: &Path, mo2: &Mo2Config) -> Result<ShaderCacheUsage, ShaderCacheError> {
+    let locations = locations(root, mo2);
+    if locations.is_empty() {
+        return Err(ShaderCacheError::NotFound);
+    }
+    let renderers = RENDERERS
+        .iter()
+        .map(|&renderer| (renderer, locations.iter().map(|location| dir_size(&location.join(folder(renderer)))).sum()))
+        .collect();
+    let total: u64 = locations.iter().map(|location| dir_size(location)).sum();
+    let mut usage = ShaderCacheUsage { locations, renderers, other: 0 };
+    usage.other = total.saturating_sub(usage.total());
+    Ok(usage)
+}
+
+/// Deletes the cache of one renderer, or all of it, and returns the bytes freed.
+/// Files that can't be deleted, usually because the game is running, are
+/// skipped and reported once everything else is gone.
+pub fn clear(root: &Path, mo2: &Mo2Config, renderer: Option<Renderer>) -> Result<u64, ShaderCacheError> {
+    let locations = locations(root, mo2);
+    if locations.is_empty() {
+        return Err(ShaderCacheError::NotFound);
+    }
+    let mut freed = 0;
+    let mut failed = Vec::new();
+    for location in &locations {
+        let target = match renderer {
+            Some(renderer) => location.join(folder(renderer)),
+            None => location.clone(),
+        };
+        if target.is_dir() {
+            remove_contents(&target, &mut freed, &mut failed);
+        }
+        if renderer.is_some() {
+            let _ = fs::remove_dir(&target);
+        }
+    }
+
+    match failed.first() {
+        None => Ok(freed),
+        Some((path, e)) => Err(ShaderCacheError::RemoveFailed(format!(
+            "{} file(s) are in use, close the game and try again ({}: {})",
+            failed.len(),
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Clears the cache when `renderer` differs from the one the game was last
+/// launched with, then remembers it in the cache folder. Returns whether
+/// anything was cleared; without a cache folder there is nothing to do.
+pub fn prepare_launch(root: &Path, mo2: &Mo2Config, renderer: Renderer, config: &ShaderCacheConfig) -> Result<bool, ShaderCacheError> {
+    let locations = locations(root, mo2);
+    let marker = locations
+        .iter()
+        .map(|location| location.join(RENDERER_MARKER))
+        .find(|marker| marker.is_file())
+        .or_else(|| locations.first().map(|location| location.join(RENDERER_MARKER)));
+    let Some(marker) = marker else {
+        return Ok(false);
+    };
+    let previous = fs::read_to_string(&marker).ok();
+    let changed = previous.is_some_and(|previous| previous.trim() != folder(renderer));
+    let cleared = match changed && config.clear_on_renderer_change {
+        true => match clear(root, mo2, None) {
+            Ok(_) => true,
+            Err(ShaderCacheError::NotFound) => false,
+            Err(e) => return Err(e),
+        },
+        false => false,
+    };
+    fs::write(&marker, folder(renderer)).map_err(|e| ShaderCacheError::RemoveFailed(format!("{}: {}", marker.display(), e)))?;
+    Ok(cleared)
+}
+
+/// Whether a patch path changes shaders, so the cache built from them is stale.
+pub fn is_shader_path(path: &str) -> bool {
+    path.replace('\\', "/").to_lowercase().starts_with("gamedata/shaders/")
+}
+
+fn remove_contents(dir: &Path, freed: &mut u64, failed: &mut Vec<(PathBuf, io::Error)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            failed.push((dir.to_path_buf(), e));
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.file_name().is_some_and(|name| name == RENDERER_MARKER) {
+            continue;
+        }
+        if path.is_dir() {
+            remove_contents(&path, freed, failed);
+            // Still holds the files that couldn't be deleted, which are reported already
+            let _ = fs::remove_dir(&path);
+            continue;
+        }
+        let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        match fs::remove_file(&path) {
+            Ok(()) => *freed += size,
+            Err(e) => failed.push((path, e)),
+        }
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.file_name() != RENDERER_MARKER)
+                .map(|entry| match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+                    _ => entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
@@ -0,0 +1,61 @@
+use serde::Serialize;
+
+/// Instruction set extensions relevant to choosing between the regular and AVX game builds.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CpuFeatures {
+    pub brand: Option<String>,
+    pub sse4_2: bool,
+    pub avx: bool,
+    pub avx2: bool,
+    pub avx512f: bool,
+}
+
+impl CpuFeatures {
+    /// Queries the running CPU. The AVX flags also require OS support for saving the YMM registers.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    pub fn detect() -> Self {
+        CpuFeatures {
+            brand: cpu_brand(),
+            sse4_2: is_x86_feature_detected!("sse4.2"),
+            avx: is_x86_feature_detected!("avx"),
+            avx2: is_x86_feature_detected!("avx2"),
+            avx512f: is_x86_feature_detected!("avx512f"),
+        }
+    }
+
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    pub fn detect() -> Self {
+        CpuFeatures::default()
+    }
+
+    /// Whether the `AnomalyDXnAVX.exe` builds can run on this CPU.
+    pub fn supports_avx_build(&self) -> bool {
+        self.avx
+    }
+
+    /// Feature names with their detection result, in display order.
+    pub fn list(&self) -> [(&'static str, bool); 4] {
+        [("SSE4.2", self.sse4_2), ("AVX", self.avx), ("AVX2", self.avx2), ("AVX-512", self.avx512f)]
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn cpu_brand() -> Option<String> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    if __cpuid(0x8000_0000).eax < 0x8000_0004 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(48);
+    for leaf in 0x8000_0002..=0x8000_0004 {
+        let result = __cpuid(leaf);
+        for register in [result.eax, result.ebx, result.ecx, result.edx] {
+            bytes.extend_from_slice(&register.to_le_bytes());
+        }
+    }
+    let brand = String::from_utf8_lossy(&bytes).trim_matches(char::from(0)).trim().to_string();
+    (!brand.is_empty()).then_some(brand)
+}
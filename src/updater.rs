@@ -9,7 +9,15 @@ use reqwest::blocking::Client;
 use semver::Version;
 use zip::ZipArchive;
 
-use crate::app_config::AppConfig;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    app_config::AppConfig,
+    manifest::{self, Manifest, ManifestAddon, PatchContents, PATCH_INFO_FILE},
+    save_backup::{self, BackupReason},
+    saves::{self, SaveGame},
+    shader_cache,
+};
 
 #[derive(Debug)]
 pub enum UpdaterError {
@@ -17,8 +25,12 @@ pub enum UpdaterError {
     VersionParseError(String),
     FileSystemError(String),
     ZipExtractionError(String),
+    VerificationError(String),
+    ManifestError(String),
     NoUpdateUrlConfigured,
     NoUpdatesAvailable,
+    /// Declined in the callback of [`Updater::update_with_confirmation`].
+    Cancelled,
 }
 
 impl std::fmt::Display for UpdaterError {
@@ -28,14 +40,19 @@ impl std::fmt::Display for UpdaterError {
             UpdaterError::VersionParseError(msg) => write!(f, "Version parse error: {}", msg),
             UpdaterError::FileSystemError(msg) => write!(f, "File system error: {}", msg),
             UpdaterError::ZipExtractionError(msg) => write!(f, "Zip extraction error: {}", msg),
+            UpdaterError::VerificationError(msg) => write!(f, "Verification error: {}", msg),
+            UpdaterError::ManifestError(msg) => write!(f, "Invalid update list: {}", msg),
             UpdaterError::NoUpdateUrlConfigured => write!(f, "No update URL configured"),
             UpdaterError::NoUpdatesAvailable => write!(f, "No updates available"),
+            UpdaterError::Cancelled => write!(f, "Update cancelled"),
         }
     }
 }
 
 impl std::error::Error for UpdaterError {}
 
+/// Downloads `patch-X.Y.Z.zip` archives listed at [`AppConfig::update_url`]
+/// and extracts them over the game folder in version order.
 #[derive(Debug)]
 pub struct Updater {
     config: AppConfig,
@@ -43,16 +60,41 @@ pub struct Updater {
     updates_dir: PathBuf,
 }
 
+/// A patch found in the update list.
 #[derive(Debug, Clone)]
 pub struct PatchInfo {
     pub version: Version,
     pub download_url: String,
+    pub sha256: Option<String>,
+    pub signature: Option<String>,
+    pub breaks_saves: bool,
+}
+
+/// Saves that patches about to be installed can't load, passed to the
+/// callback of [`Updater::update_with_confirmation`].
+#[derive(Debug, Clone)]
+pub struct SaveBreakWarning {
+    /// Versions of the save-breaking patches.
+    pub versions: Vec<Version>,
+    /// Every current save, newest first.
+    pub saves: Vec<SaveGame>,
+    /// Whether the saves are backed up anyway, see [`crate::SaveBackupConfig::before_update`].
+    pub backup_planned: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaveBreakChoice {
+    BackUpAndUpdate,
+    Update,
+    Cancel,
 }
 
-#[allow(dead_code)]
+/// Progress events reported to the callback passed to [`Updater::update`].
 pub enum UpdateProgress {
     CheckingForUpdates,
     UpdatesAvailable(Vec<PatchInfo>),
+    /// Saves are being backed up before the first patch is applied.
+    BackingUpSaves,
     Downloading {
         current: usize,
         total: usize,
@@ -89,7 +131,8 @@ impl Updater {
         })
     }
     
-    pub fn check_for_updates(&self) -> Result<Vec<PatchInfo>, UpdaterError> {
+    /// Fetches and parses the update list at [`AppConfig::update_url`].
+    pub fn fetch_manifest(&self) -> Result<Manifest, UpdaterError> {
         let update_url = self.config.update_url.as_ref().ok_or(UpdaterError::NoUpdateUrlConfigured)?;
         
         // Получаем список доступных патчей
@@ -104,32 +147,26 @@ impl Updater {
         let content = response.text()
             .map_err(|e| UpdaterError::NetworkError(format!("Failed to read response: {}", e)))?;
         
-        // Парсим строки как URL-ы патчей
-        let mut available_patches = Vec::new();
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue; // Пропускаем пустые строки и комментарии
-            }
-            
-            // Предполагаем формат имени файла: patch-X.Y.Z.zip
-            if let Some(file_name) = Path::new(line).file_name() {
-                if let Some(file_name_str) = file_name.to_str() {
-                    if file_name_str.starts_with("patch-") && file_name_str.ends_with(".zip") {
-                        let version_str = &file_name_str[6..file_name_str.len() - 4]; // Убираем 'patch-' и '.zip'
-                        match Version::parse(version_str) {
-                            Ok(version) => {
-                                available_patches.push(PatchInfo {
-                                    version,
-                                    download_url: line.to_string(),
-                                });
-                            },
-                            Err(_) => continue, // Пропускаем некорректные версии
-                        }
-                    }
-                }
-            }
-        }
+        // Поддерживаются TOML-манифест и старый список URL-ов
+        let mut manifest = Manifest::parse(&content)
+            .map_err(|e| UpdaterError::ManifestError(e.message().trim().to_string()))?;
+        manifest.resolve_urls(update_url);
+        Ok(manifest)
+    }
+    
+    /// Fetches the update list and returns every patch in it, sorted by version.
+    pub fn check_for_updates(&self) -> Result<Vec<PatchInfo>, UpdaterError> {
+        let mut available_patches: Vec<PatchInfo> = self.fetch_manifest()?
+            .patches
+            .into_iter()
+            .map(|patch| PatchInfo {
+                version: patch.version,
+                download_url: patch.url,
+                sha256: patch.sha256,
+                signature: patch.signature,
+                breaks_saves: patch.breaks_saves,
+            })
+            .collect();
         
         // Сортируем патчи по версии
         available_patches.sort_by(|a, b| a.version.cmp(&b.version));
@@ -141,52 +178,119 @@ impl Updater {
         Ok(available_patches)
     }
     
+    /// Downloads a patch into the `updates` folder and returns the archive path.
     pub fn download_patch(&self, patch: &PatchInfo, progress_callback: &mut dyn FnMut(UpdateProgress)) 
         -> Result<PathBuf, UpdaterError> {
-        let file_name = format!("patch-{}.zip", patch.version);
-        let output_path = self.updates_dir.join(&file_name);
+        let output_path = self.updates_dir.join(format!("patch-{}.zip", patch.version));
+        self.download(&patch.download_url, &output_path, &mut |progress| {
+            progress_callback(UpdateProgress::Downloading {
+                current: 1, // Мы скачиваем по одному файлу за раз
+                total: 1,
+                version: patch.version.to_string(),
+                progress,
+            });
+        })?;
+        
+        self.verify_patch(patch, &output_path)?;
         
+        Ok(output_path)
+    }
+    
+    /// Downloads an addon archive into the `updates` folder and verifies it like a patch.
+    pub fn download_addon(&self, addon: &ManifestAddon, progress_callback: &mut dyn FnMut(f32)) 
+        -> Result<PathBuf, UpdaterError> {
+        let output_path = self.updates_dir.join(format!("addon-{}-{}.zip", addon.id, addon.version));
+        self.download(&addon.url, &output_path, progress_callback)?;
+        
+        let label = format!("Addon {} {}", addon.name, addon.version);
+        self.verify_file(&label, addon.sha256.as_deref(), addon.signature.as_deref(), &output_path)?;
+        
+        Ok(output_path)
+    }
+    
+    fn download(&self, url: &str, output_path: &Path, progress_callback: &mut dyn FnMut(f32)) -> Result<(), UpdaterError> {
         // Создаем временный файл
-        let mut output_file = File::create(&output_path)
+        let mut output_file = File::create(output_path)
             .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create output file: {}", e)))?;
         
         // Скачиваем файл
-        let mut response = self.client.get(&patch.download_url)
+        let mut response = self.client.get(url)
             .send()
-            .map_err(|e| UpdaterError::NetworkError(format!("Failed to download patch: {}", e)))?;
+            .map_err(|e| UpdaterError::NetworkError(format!("Failed to download {}: {}", url, e)))?;
         
         if !response.status().is_success() {
             return Err(UpdaterError::NetworkError(format!("Server returned error: {}", response.status())));
         }
         
-        let total_size = response.content_length().unwrap_or(0);
+        let total_size = response.content_length();
         let mut downloaded = 0;
         let mut buffer = [0u8; 8192];
         
-        while let Ok(n) = response.read(&mut buffer) {
-            if n == 0 {
-                break;
-            }
+        loop {
+            let n = match response.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(UpdaterError::NetworkError(format!("Failed to download {}: {}", url, e))),
+            };
             
             output_file.write_all(&buffer[..n])
                 .map_err(|e| UpdaterError::FileSystemError(format!("Failed to write to file: {}", e)))?;
             
             downloaded += n as u64;
             
-            if total_size > 0 {
-                let progress = downloaded as f32 / total_size as f32;
-                progress_callback(UpdateProgress::Downloading {
-                    current: 1, // Мы скачиваем по одному файлу за раз
-                    total: 1,
-                    version: patch.version.to_string(),
-                    progress,
-                });
+            if let Some(total_size) = total_size.filter(|&total_size| total_size > 0) {
+                progress_callback(downloaded as f32 / total_size as f32);
             }
         }
         
-        Ok(output_path)
+        // Оборванное соединение может выглядеть как обычный конец файла
+        if let Some(total_size) = total_size {
+            if downloaded != total_size {
+                return Err(UpdaterError::NetworkError(format!(
+                    "Download of {} was cut off: got {} of {} bytes", url, downloaded, total_size
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Checks the downloaded archive against the manifest hash and, when
+    /// [`AppConfig::update_public_key`] is set, its signature.
+    pub fn verify_patch(&self, patch: &PatchInfo, patch_path: &Path) -> Result<(), UpdaterError> {
+        let label = format!("Patch {}", patch.version);
+        self.verify_file(&label, patch.sha256.as_deref(), patch.signature.as_deref(), patch_path)
     }
     
+    fn verify_file(&self, label: &str, sha256: Option<&str>, signature: Option<&str>, path: &Path) -> Result<(), UpdaterError> {
+        let public_key = self.config.update_public_key.as_deref();
+        if sha256.is_none() && public_key.is_none() {
+            return Ok(());
+        }
+        
+        let actual = manifest::sha256_file(path)
+            .map_err(|e| UpdaterError::FileSystemError(format!("Failed to hash {}: {}", path.display(), e)))?;
+        let expected = sha256.ok_or_else(|| {
+            UpdaterError::VerificationError(format!("{} has no hash in the update list", label))
+        })?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(UpdaterError::VerificationError(format!("{} is corrupted (hash mismatch)", label)));
+        }
+        
+        if let Some(public_key) = public_key {
+            let signature = signature.ok_or_else(|| {
+                UpdaterError::VerificationError(format!("{} is not signed", label))
+            })?;
+            manifest::verify_digest(public_key, &actual, signature)
+                .map_err(|e| UpdaterError::VerificationError(format!("{}: {}", label, e)))?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Extracts a patch archive into the current directory. Game files are
+    /// replaced only once every file in the archive has passed its hash check.
     pub fn apply_patch(&self, patch_path: &Path, progress_callback: &mut dyn FnMut(UpdateProgress)) 
         -> Result<(), UpdaterError> {
         let file = File::open(patch_path)
@@ -195,52 +299,72 @@ impl Updater {
         let mut archive = ZipArchive::new(file)
             .map_err(|e| UpdaterError::ZipExtractionError(format!("Failed to open zip archive: {}", e)))?;
         
-        let total_files = archive.len();
+        // Список удалений и хэшей, если патч собран через patch-publisher
+        let contents: PatchContents = match archive.by_name(PATCH_INFO_FILE) {
+            Ok(mut info_file) => {
+                let mut info = String::new();
+                info_file.read_to_string(&mut info)
+                    .map_err(|e| UpdaterError::ZipExtractionError(format!("Failed to read {}: {}", PATCH_INFO_FILE, e)))?;
+                toml::from_str(&info)
+                    .map_err(|e| UpdaterError::ZipExtractionError(format!("Failed to parse {}: {}", PATCH_INFO_FILE, e)))?
+            },
+            Err(_) => PatchContents::default(),
+        };
         
         // Получаем имя файла патча для извлечения версии
         let file_name = patch_path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
         
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)
-                .map_err(|e| UpdaterError::ZipExtractionError(format!("Failed to access file in archive: {}", e)))?;
-            
-            let outpath = match file.enclosed_name() {
-                Some(path) => path.to_owned(),
-                None => continue,
-            };
-            
-            // Информируем о прогрессе
-            progress_callback(UpdateProgress::Extracting {
-                current: i + 1,
-                total: total_files,
-                version: file_name.to_string(),
-            });
-            
-            if file.is_dir() {
-                fs::create_dir_all(&outpath)
-                    .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create directory: {}", e)))?;
-            } else {
-                if let Some(p) = outpath.parent() {
-                    if !p.exists() {
-                        fs::create_dir_all(p)
-                            .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create parent directory: {}", e)))?;
-                    }
+        // Сначала всё распаковываем рядом и подменяем файлы игры только когда
+        // каждый файл прошёл проверку хэша, иначе игра осталась бы наполовину обновлённой
+        let mut staged: Vec<(PathBuf, PathBuf)> = Vec::new();
+        if let Err(e) = stage_patch(&mut archive, &contents, file_name, &mut staged, progress_callback) {
+            for (staging, _) in &staged {
+                let _ = fs::remove_file(staging);
+            }
+            return Err(e);
+        }
+        
+        for (i, (staging, outpath)) in staged.iter().enumerate() {
+            if let Err(e) = fs::rename(staging, outpath) {
+                for (staging, _) in &staged[i..] {
+                    let _ = fs::remove_file(staging);
                 }
-                
-                let mut outfile = File::create(&outpath)
-                    .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create output file: {}", e)))?;
-                
-                io::copy(&mut file, &mut outfile)
-                    .map_err(|e| UpdaterError::FileSystemError(format!("Failed to write output file: {}", e)))?;
+                return Err(UpdaterError::FileSystemError(format!("Failed to replace {}: {}", outpath.display(), e)));
             }
         }
         
+        for deleted in &contents.deleted {
+            if !manifest::is_safe_relative_path(deleted) {
+                continue;
+            }
+            let path = Path::new(deleted);
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else if path.exists() {
+                fs::remove_file(path)
+            } else {
+                Ok(())
+            };
+            result.map_err(|e| UpdaterError::FileSystemError(format!("Failed to delete {}: {}", deleted, e)))?;
+        }
+        
         Ok(())
     }
     
-    pub fn update(&mut self, mut progress_callback: impl FnMut(UpdateProgress)) -> Result<String, UpdaterError> {
+    /// Installs every patch newer than the configured version and returns the new version.
+    /// Save-breaking patches are installed without asking.
+    pub fn update(&mut self, progress_callback: impl FnMut(UpdateProgress)) -> Result<String, UpdaterError> {
+        self.update_with_confirmation(progress_callback, |_| SaveBreakChoice::Update)
+    }
+    
+    /// Like [`Updater::update`], but asks `confirm` first when a patch breaks existing saves.
+    pub fn update_with_confirmation(
+        &mut self,
+        mut progress_callback: impl FnMut(UpdateProgress),
+        mut confirm: impl FnMut(&SaveBreakWarning) -> SaveBreakChoice,
+    ) -> Result<String, UpdaterError> {
         progress_callback(UpdateProgress::CheckingForUpdates);
         
         // Получаем текущую версию
@@ -266,13 +390,46 @@ impl Updater {
             return Err(UpdaterError::NoUpdatesAvailable);
         }
         
+        let root = std::env::current_dir()
+            .map_err(|e| UpdaterError::FileSystemError(format!("Failed to get current directory: {}", e)))?;
+        
+        // Сейвы, сделанные до обновления, относятся к текущей версии
+        let _ = saves::track(&root, self.config.version.as_deref());
+        
+        // Спрашиваем, если обновление сломает существующие сейвы
+        let mut back_up = self.config.save_backups.before_update;
+        let breaking: Vec<Version> = applicable_patches.iter().filter(|patch| patch.breaks_saves).map(|patch| patch.version.clone()).collect();
+        let affected = if breaking.is_empty() { Vec::new() } else { saves::list(&root).unwrap_or_default() };
+        if !affected.is_empty() {
+            let warning = SaveBreakWarning { versions: breaking, saves: affected, backup_planned: back_up };
+            match confirm(&warning) {
+                SaveBreakChoice::Cancel => return Err(UpdaterError::Cancelled),
+                SaveBreakChoice::BackUpAndUpdate => back_up = true,
+                SaveBreakChoice::Update => {}
+            }
+        }
+        
+        // Сохраняем сейвы до того, как патч сможет их сломать
+        if back_up {
+            progress_callback(UpdateProgress::BackingUpSaves);
+            save_backup::create(&root, self.config.version.as_deref(), BackupReason::Update)
+                .and_then(|_| save_backup::prune(&root, &self.config.save_backups))
+                .map_err(|e| UpdaterError::FileSystemError(format!("Failed to back up saves: {}", e)))?;
+        }
+        
         // Применяем патчи последовательно
-        for (_i, patch) in applicable_patches.iter().enumerate() {
+        let mut shaders_changed = false;
+        for patch in applicable_patches.iter() {
             // Скачиваем патч
             let patch_path = self.download_patch(patch, &mut progress_callback)?;
             
             // Применяем патч
+            shaders_changed |= touches_shaders(&patch_path);
             self.apply_patch(&patch_path, &mut progress_callback)?;
+            if patch.breaks_saves {
+                saves::record_break(&root, &patch.version.to_string())
+                    .map_err(|e| UpdaterError::FileSystemError(format!("Failed to record the save-breaking patch: {}", e)))?;
+            }
             
             // Обновляем версию в конфиге после каждого патча
             if let Some(config_version) = &mut self.config.version {
@@ -282,6 +439,11 @@ impl Updater {
             }
         }
         
+        // Кэш собран из старых шейдеров; заблокированные файлы игра перезапишет сама
+        if shaders_changed && self.config.shader_cache.clear_after_update {
+            let _ = shader_cache::clear(&root, &self.config.mo2, None);
+        }
+        
         // Возвращаем новую версию
         let new_version = self.config.version.clone().unwrap_or_else(|| "unknown".to_string());
         
@@ -289,4 +451,104 @@ impl Updater {
         
         Ok(new_version)
     }
-}
\ No newline at end of file
+}
+
+/// Extracts every file of a patch next to its target as `<name>.partial` and
+/// checks it against the hashes in `contents`. Staged files are pushed to
+/// `staged` before they are written, so the caller can remove them on failure.
+fn stage_patch(
+    archive: &mut ZipArchive<File>,
+    contents: &PatchContents,
+    file_name: &str,
+    staged: &mut Vec<(PathBuf, PathBuf)>,
+    progress_callback: &mut dyn FnMut(UpdateProgress),
+) -> Result<(), UpdaterError> {
+    let total_files = archive.len();
+    for i in 0..total_files {
+        let mut file = archive.by_index(i)
+            .map_err(|e| UpdaterError::ZipExtractionError(format!("Failed to access file in archive: {}", e)))?;
+        
+        let outpath = match file.enclosed_name() {
+            Some(path) => path.to_owned(),
+            None => continue,
+        };
+        if outpath == Path::new(PATCH_INFO_FILE) {
+            continue;
+        }
+        
+        // Информируем о прогрессе
+        progress_callback(UpdateProgress::Extracting {
+            current: i + 1,
+            total: total_files,
+            version: file_name.to_string(),
+        });
+        
+        if file.is_dir() {
+            fs::create_dir_all(&outpath)
+                .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create directory: {}", e)))?;
+            continue;
+        }
+        if let Some(p) = outpath.parent() {
+            if !p.exists() {
+                fs::create_dir_all(p)
+                    .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create parent directory: {}", e)))?;
+            }
+        }
+        
+        let mut staging_name = outpath.file_name().unwrap_or_default().to_os_string();
+        staging_name.push(".partial");
+        let staging = outpath.with_file_name(staging_name);
+        let outfile = File::create(&staging)
+            .map_err(|e| UpdaterError::FileSystemError(format!("Failed to create output file: {}", e)))?;
+        staged.push((staging, outpath));
+        let mut writer = HashingWriter { inner: outfile, hasher: Sha256::new() };
+        
+        io::copy(&mut file, &mut writer)
+            .map_err(|e| UpdaterError::FileSystemError(format!("Failed to write output file: {}", e)))?;
+        drop(writer.inner);
+        
+        let entry_name = file.name().to_string();
+        if let Some(expected) = contents.files.get(&entry_name) {
+            let actual = format!("{:x}", writer.hasher.finalize());
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(UpdaterError::VerificationError(format!("{} does not match its hash in {}", entry_name, file_name)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether a patch archive adds or deletes anything under `gamedata/shaders`.
+fn touches_shaders(patch_path: &Path) -> bool {
+    let Some(mut archive) = File::open(patch_path).ok().and_then(|file| ZipArchive::new(file).ok()) else {
+        return false;
+    };
+    if archive.file_names().any(shader_cache::is_shader_path) {
+        return true;
+    }
+    let mut info = String::new();
+    if archive.by_name(PATCH_INFO_FILE).map(|mut info_file| info_file.read_to_string(&mut info)).is_err() {
+        return false;
+    }
+    toml::from_str::<PatchContents>(&info)
+        .map(|contents| contents.deleted.iter().any(|deleted| shader_cache::is_shader_path(deleted)))
+        .unwrap_or(false)
+}
+
+/// Computes the SHA-256 of everything written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::fs_util;
+
+/// The game's console settings, relative to the game folder.
+pub const USER_LTX: &str = "appdata/user.ltx";
+
+/// `user.ltx` kept line by line, so comments, binds and keys the launcher
+/// doesn't know about are written back untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserLtx {
+    lines: Vec<String>,
+    line_ending: &'static str,
+    /// The file wasn't UTF-8 (the game writes cp1251); bytes map 1:1 to chars.
+    single_byte: bool,
+}
+
+impl UserLtx {
+    pub fn parse(content: &str) -> Self {
+        UserLtx {
+            lines: content.lines().map(|line| line.to_string()).collect(),
+            line_ending: if content.contains("\r\n") { "\r\n" } else { "\n" },
+            single_byte: false,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Ok(match String::from_utf8(bytes) {
+            Ok(content) => UserLtx::parse(&content),
+            Err(err) => {
+                let content: String = err.into_bytes().into_iter().map(char::from).collect();
+                UserLtx { single_byte: true, ..UserLtx::parse(&content) }
+            }
+        })
+    }
+
+    /// Value of the first line setting `key`; the rest of the line after the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| {
+            let (name, value) = split_line(line)?;
+            (name == key).then_some(value)
+        })
+    }
+
+    /// Replaces the value of `key`, or appends the key when the file doesn't have it.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let entry = format!("{} {}", key, value);
+        match self.lines.iter_mut().find(|line| split_line(line).is_some_and(|(name, _)| name == key)) {
+            Some(line) => *line = entry,
+            None => self.lines.push(entry),
+        }
+    }
+
+    /// Atomically writes this file in place of `path`. The first save keeps
+    /// the original as `user.ltx.bak`, later ones leave it alone.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs_util::backup_once(path, &backup_path(path))?;
+        let text = self.to_string();
+        if self.single_byte {
+            fs_util::write_atomic(path, &text.chars().map(|c| c as u32 as u8).collect::<Vec<u8>>())
+        } else {
+            fs_util::write_atomic(path, text.as_bytes())
+        }
+    }
+}
+
+impl fmt::Display for UserLtx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            write!(f, "{}{}", line, self.line_ending)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    fs_util::with_suffix(path, ".bak")
+}
+
+/// Splits `key value` lines; comments (`;`) and blank lines have no key.
+fn split_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with(';') {
+        return None;
+    }
+    match line.split_once(char::is_whitespace) {
+        Some((key, value)) => Some((key, value.trim())),
+        None => Some((line, "")),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreenMode {
+    Fullscreen,
+    Borderless,
+    Windowed,
+}
+
+impl ScreenMode {
+    fn as_ltx(self) -> &'static str {
+        match self {
+            ScreenMode::Fullscreen => "fullscreen",
+            ScreenMode::Borderless => "borderless",
+            ScreenMode::Windowed => "windowed",
+        }
+    }
+}
+
+impl fmt::Display for ScreenMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenMode::Fullscreen => write!(f, "Fullscreen"),
+            ScreenMode::Borderless => write!(f, "Borderless window"),
+            ScreenMode::Windowed => write!(f, "Windowed"),
+        }
+    }
+}
+
+/// The graphics options the launcher edits, read from and written to `user.ltx`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphicsSettings {
+    /// `vid_mode`, e.g. `1920x1080`.
+    pub resolution: (u32, u32),
+    /// `rs_screenmode`.
+    pub screen_mode: ScreenMode,
+    /// `rs_v_sync`.
+    pub vsync: bool,
+    /// `r__framelimit`; 0 means unlimited.
+    pub fps_limit: u32,
+    /// `texture_lod`; 0 is the most detailed.
+    pub texture_lod: u32,
+    /// `fov`.
+    pub fov: f32,
+}
+
+/// Resolutions offered in the picker besides the current one.
+pub const COMMON_RESOLUTIONS: [(u32, u32); 9] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (1920, 1200),
+    (2560, 1080),
+    (2560, 1440),
+    (3440, 1440),
+    (3840, 2160),
+];
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsSettings {
+            resolution: (1920, 1080),
+            screen_mode: ScreenMode::Fullscreen,
+            vsync: false,
+            fps_limit: 0,
+            texture_lod: 0,
+            fov: 75.,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// Reads the settings, keeping the default for keys that are missing or malformed.
+    pub fn from_ltx(ltx: &UserLtx) -> Self {
+        let mut settings = GraphicsSettings::default();
+        if let Some((w, h)) = ltx.get("vid_mode").and_then(|v| v.split_once('x')) {
+            if let (Ok(w), Ok(h)) = (w.trim().parse(), h.trim().parse()) {
+                settings.resolution = (w, h);
+            }
+        }
+        settings.screen_mode = match ltx.get("rs_screenmode") {
+            Some("borderless") => ScreenMode::Borderless,
+            Some("windowed") => ScreenMode::Windowed,
+            _ => settings.screen_mode,
+        };
+        if let Some(vsync) = ltx.get("rs_v_sync") {
+            settings.vsync = vsync == "on";
+        }
+        if let Some(limit) = ltx.get("r__framelimit").and_then(|v| v.parse().ok()) {
+            settings.fps_limit = limit;
+        }
+        if let Some(lod) = ltx.get("texture_lod").and_then(|v| v.parse().ok()) {
+            settings.texture_lod = lod;
+        }
+        if let Some(fov) = ltx.get("fov").and_then(|v| v.parse().ok()) {
+            settings.fov = fov;
+        }
+        settings
+    }
+
+    /// Writes the values that differ from what `ltx` already holds, leaving the rest of the file as it was.
+    pub fn apply(&self, ltx: &mut UserLtx) {
+        let current = GraphicsSettings::from_ltx(ltx);
+        if self.resolution != current.resolution || ltx.get("vid_mode").is_none() {
+            ltx.set("vid_mode", &format!("{}x{}", self.resolution.0, self.resolution.1));
+        }
+        if self.screen_mode != current.screen_mode {
+            ltx.set("rs_screenmode", self.screen_mode.as_ltx());
+        }
+        if self.vsync != current.vsync {
+            ltx.set("rs_v_sync", if self.vsync { "on" } else { "off" });
+        }
+        if self.fps_limit != current.fps_limit {
+            ltx.set("r__framelimit", &self.fps_limit.to_string());
+        }
+        if self.texture_lod != current.texture_lod {
+            ltx.set("texture_lod", &self.texture_lod.to_string());
+        }
+        if self.fov != current.fov {
+            ltx.set("fov", &format!("{}", self.fov));
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    env, fs,
+    sync::{Arc, Mutex},
+};
+
+use anomaly_launcher::{
+    addons::{self, AddonStatus},
+    AppConfig, InstalledAddons, ManifestAddon, Mo2Instance, Updater,
+};
+use eframe::egui::{self, Color32, RichText, ScrollArea};
+use rfd::MessageDialog;
+
+enum Event {
+    Catalog(Result<Vec<ManifestAddon>, String>),
+    Finished(Result<String, String>),
+}
+
+pub struct AddonsView {
+    pub open: bool,
+    // Заполняется фоновым потоком: каталог или результат установки/удаления
+    event: Arc<Mutex<Option<Event>>>,
+    progress: Arc<Mutex<f32>>,
+    busy: Option<String>,
+    catalog: Vec<ManifestAddon>,
+    installed: InstalledAddons,
+    instance: Option<Mo2Instance>,
+    status: Option<(String, bool)>,
+}
+
+impl AddonsView {
+    pub fn new() -> Self {
+        AddonsView {
+            open: false,
+            event: Arc::new(Mutex::new(None)),
+            progress: Arc::new(Mutex::new(0.)),
+            busy: None,
+            catalog: Vec::new(),
+            installed: InstalledAddons::default(),
+            instance: None,
+            status: None,
+        }
+    }
+
+    /// Opens the window and fetches the addon catalog in the background.
+    pub fn open(&mut self, ctx: &egui::Context, config: &AppConfig) {
+        self.open = true;
+        self.refresh(ctx, config);
+    }
+
+    fn refresh(&mut self, ctx: &egui::Context, config: &AppConfig) {
+        if self.busy.is_some() {
+            return;
+        }
+        self.status = None;
+        self.load_installed(config);
+        if self.instance.is_none() {
+            self.status = Some(("Addons are installed into Mod Organizer 2, but no instance was found next to the game.".to_string(), true));
+            return;
+        }
+        self.busy = Some("Fetching the addon catalog...".to_string());
+        let event = self.event.clone();
+        let config = config.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let catalog = Updater::new(config).and_then(|updater| updater.fetch_manifest()).map(|manifest| manifest.addons).map_err(|e| e.to_string());
+            if let Ok(mut guard) = event.lock() {
+                *guard = Some(Event::Catalog(catalog));
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn load_installed(&mut self, config: &AppConfig) {
+        self.instance = Mo2Instance::detect(&env::current_dir().unwrap_or_default(), &config.mo2);
+        self.installed = match &self.instance {
+            Some(instance) => InstalledAddons::load(&instance.dir).unwrap_or_else(|e| {
+                self.status = Some((format!("Failed to read {}: {}", addons::INSTALLED_ADDONS_FILE, e), true));
+                InstalledAddons::default()
+            }),
+            None => InstalledAddons::default(),
+        };
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &AppConfig) {
+        if let Some(event) = self.event.lock().ok().and_then(|mut guard| guard.take()) {
+            self.busy = None;
+            match event {
+                Event::Catalog(Ok(catalog)) => self.catalog = catalog,
+                Event::Catalog(Err(e)) => self.status = Some((format!("Failed to fetch the addon catalog: {}", e), true)),
+                Event::Finished(result) => {
+                    self.load_installed(config);
+                    self.status = Some(match result {
+                        Ok(message) => (message, false),
+                        Err(e) => (e, true),
+                    });
+                }
+            }
+        }
+
+        let mut open = self.open;
+        let mut refresh = false;
+        let mut install = None;
+        let mut remove = None;
+        super::show_window(ctx, &mut open, "addons", "Addons", [540., 460.], |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(self.busy.is_none(), egui::Button::new("Refresh")).clicked() {
+                    refresh = true;
+                }
+                if let Some(task) = &self.busy {
+                    ui.spinner();
+                    ui.label(task);
+                    let progress = self.progress.lock().map(|p| *p).unwrap_or_default();
+                    if progress > 0. {
+                        ui.add(egui::ProgressBar::new(progress).desired_width(120.));
+                    }
+                }
+            });
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+            ui.separator();
+
+            if self.catalog.is_empty() {
+                if self.busy.is_none() && self.instance.is_some() && self.status.is_none() {
+                    ui.label("The update server doesn't offer any addons.");
+                }
+                return;
+            }
+            ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                for addon in &self.catalog {
+                    let status = addons::status(addon, &self.installed);
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&addon.name).strong());
+                        ui.label(RichText::new(addon.version.to_string()).weak());
+                        match (status, self.installed.get(&addon.id)) {
+                            (AddonStatus::Outdated, Some(current)) => {
+                                ui.label(RichText::new(format!("{} installed", current.version)).color(Color32::from_rgb(200, 140, 60)));
+                            }
+                            (AddonStatus::Installed, _) => {
+                                ui.label(RichText::new("installed").color(Color32::from_rgb(80, 170, 80)));
+                            }
+                            _ => {}
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let idle = self.busy.is_none();
+                            if status != AddonStatus::Available && ui.add_enabled(idle, egui::Button::new("Remove")).clicked() {
+                                remove = Some(addon.id.clone());
+                            }
+                            let label = if status == AddonStatus::Outdated { "Update" } else { "Install" };
+                            if status != AddonStatus::Installed && ui.add_enabled(idle, egui::Button::new(label)).clicked() {
+                                install = Some(addon.id.clone());
+                            }
+                        });
+                    });
+                    if !addon.description.is_empty() {
+                        ui.label(&addon.description);
+                    }
+                    let mut rules = Vec::new();
+                    if !addon.requires.is_empty() {
+                        rules.push(format!("Requires: {}", addon.requires.join(", ")));
+                    }
+                    if !addon.conflicts.is_empty() {
+                        rules.push(format!("Incompatible with: {}", addon.conflicts.join(", ")));
+                    }
+                    if !rules.is_empty() {
+                        ui.label(RichText::new(rules.join("    ")).weak());
+                    }
+                    ui.separator();
+                }
+            });
+        });
+        self.open = open;
+
+        if let Some(id) = install {
+            self.install(ctx, config, &id);
+        } else if let Some(id) = remove {
+            self.remove(ctx, &id);
+        } else if refresh {
+            self.refresh(ctx, config);
+        }
+    }
+
+    fn install(&mut self, ctx: &egui::Context, config: &AppConfig, id: &str) {
+        let Some(instance) = self.instance.clone() else {
+            return;
+        };
+        let addon = match addons::check_install(&self.catalog, &self.installed, id) {
+            Ok(addon) => addon.clone(),
+            Err(e) => {
+                self.status = Some((e.to_string(), true));
+                return;
+            }
+        };
+        self.busy = Some(format!("Installing {}...", addon.name));
+        self.status = None;
+        if let Ok(mut progress) = self.progress.lock() {
+            *progress = 0.;
+        }
+        let (event, progress) = (self.event.clone(), self.progress.clone());
+        let mut installed = self.installed.clone();
+        let config = config.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = (|| {
+                let updater = Updater::new(config.clone()).map_err(|e| e.to_string())?;
+                let archive = updater
+                    .download_addon(&addon, &mut |value| {
+                        if let Ok(mut progress) = progress.lock() {
+                            *progress = value;
+                        }
+                        ctx.request_repaint();
+                    })
+                    .map_err(|e| e.to_string())?;
+                let profile = instance.resolve_profile(config.mo2.profile.as_deref());
+                let result = addons::install(&instance, profile.as_deref(), &addon, &archive, &mut installed);
+                let _ = fs::remove_file(&archive);
+                result.map_err(|e| e.to_string())?;
+                Ok(format!("{} {} has been installed.", addon.name, addon.version))
+            })();
+            if let Ok(mut guard) = event.lock() {
+                *guard = Some(Event::Finished(result));
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    fn remove(&mut self, ctx: &egui::Context, id: &str) {
+        let Some(instance) = self.instance.clone() else {
+            return;
+        };
+        if let Err(e) = addons::check_remove(&self.catalog, &self.installed, id) {
+            self.status = Some((e.to_string(), true));
+            return;
+        }
+        let name = self.catalog.iter().find(|addon| addon.id == id).map(|addon| addon.name.clone()).unwrap_or_else(|| id.to_string());
+        let confirm = MessageDialog::new()
+            .set_title("Remove addon")
+            .set_description(format!(
+                "Delete {} and remove it from the mod list?\n\nClose Mod Organizer 2 first, it overwrites the list when it exits.",
+                name
+            ))
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if confirm != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.busy = Some(format!("Removing {}...", name));
+        self.status = None;
+        let event = self.event.clone();
+        let mut installed = self.installed.clone();
+        let id = id.to_string();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = addons::remove(&instance, &id, &mut installed).map(|()| format!("{} has been removed.", name)).map_err(|e| e.to_string());
+            if let Ok(mut guard) = event.lock() {
+                *guard = Some(Event::Finished(result));
+            }
+            ctx.request_repaint();
+        });
+    }
+}
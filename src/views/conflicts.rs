@@ -0,0 +1,134 @@
+use std::{
+    env,
+    sync::{Arc, Mutex},
+};
+
+use anomaly_launcher::{conflicts, ConflictReport, Mo2Config};
+use eframe::egui::{self, Color32, RichText, ScrollArea, TextEdit};
+
+pub struct ConflictsView {
+    pub open: bool,
+    // Заполняется фоновым потоком, когда сканирование закончено
+    result: Arc<Mutex<Option<Result<ConflictReport, String>>>>,
+    scanning: bool,
+    report: Option<ConflictReport>,
+    filter: String,
+    status: Option<(String, bool)>,
+}
+
+impl ConflictsView {
+    pub fn new() -> Self {
+        ConflictsView {
+            open: false,
+            result: Arc::new(Mutex::new(None)),
+            scanning: false,
+            report: None,
+            filter: String::new(),
+            status: None,
+        }
+    }
+
+    /// Opens the window and scans the mods in the background.
+    pub fn open(&mut self, ctx: &egui::Context, config: &Mo2Config) {
+        self.open = true;
+        self.scan(ctx, config);
+    }
+
+    fn scan(&mut self, ctx: &egui::Context, config: &Mo2Config) {
+        if self.scanning {
+            return;
+        }
+        self.scanning = true;
+        self.status = None;
+        let result = self.result.clone();
+        let config = config.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let root = env::current_dir().unwrap_or_default();
+            let sources = conflicts::detect_sources(&root, &config);
+            let report = conflicts::scan(&sources).map_err(|e| e.to_string());
+            if let Ok(mut guard) = result.lock() {
+                *guard = Some(report);
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &Mo2Config) {
+        if let Some(result) = self.result.lock().ok().and_then(|mut guard| guard.take()) {
+            self.scanning = false;
+            match result {
+                Ok(report) => self.report = Some(report),
+                Err(e) => self.status = Some((format!("Failed to scan the mods: {}", e), true)),
+            }
+        }
+
+        let mut open = self.open;
+        let mut rescan = false;
+        super::show_window(ctx, &mut open, "conflicts", "Mod Conflicts", [560., 480.], |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!self.scanning, egui::Button::new("Scan Again")).clicked() {
+                    rescan = true;
+                }
+                if ui.add_enabled(self.report.is_some(), egui::Button::new("Export Report")).clicked() {
+                    if let Some(report) = &self.report {
+                        let root = env::current_dir().unwrap_or_default();
+                        self.status = Some(match conflicts::export(&root, report) {
+                            Ok(path) => (format!("Report saved to {}", path.display()), false),
+                            Err(e) => (format!("Failed to save the report: {}", e), true),
+                        });
+                    }
+                }
+                if self.scanning {
+                    ui.spinner();
+                    ui.label("Scanning mods...");
+                }
+            });
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+
+            let Some(report) = &self.report else {
+                return;
+            };
+            if report.mods.is_empty() {
+                ui.label("No mods found: neither an MO2 profile nor a 'mods' folder with gamedata.");
+                return;
+            }
+            ui.label(format!(
+                "{} mod(s): {} overwritten file(s), {} section(s) patched by several mods",
+                report.mods.len(),
+                report.files.len(),
+                report.sections.len()
+            ));
+            ui.add(TextEdit::singleline(&mut self.filter).hint_text("Filter by file, section or mod").desired_width(f32::INFINITY));
+            ui.separator();
+
+            let filter = self.filter.to_lowercase();
+            let matches = |text: &str| filter.is_empty() || text.to_lowercase().contains(&filter);
+            ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                egui::CollapsingHeader::new(format!("Overwritten files ({})", report.files.len())).default_open(true).show(ui, |ui| {
+                    for conflict in report.files.iter().filter(|c| matches(&c.path) || c.mods.iter().any(|m| matches(m))) {
+                        ui.label(&conflict.path);
+                        let losers = &conflict.mods[..conflict.mods.len() - 1];
+                        ui.label(RichText::new(format!("    {} wins over {}", conflict.winner(), losers.join(", "))).weak());
+                    }
+                });
+                egui::CollapsingHeader::new(format!("DLTX sections ({})", report.sections.len())).show(ui, |ui| {
+                    for conflict in report.sections.iter().filter(|c| matches(&c.section) || c.patches.iter().any(|(m, p)| matches(m) || matches(p))) {
+                        ui.label(format!("[{}]", conflict.section));
+                        for (name, path) in &conflict.patches {
+                            ui.label(RichText::new(format!("    {}: {}", name, path)).weak());
+                        }
+                        ui.label(RichText::new(format!("    {} wins", conflict.winner())).weak());
+                    }
+                });
+            });
+        });
+        self.open = open;
+        if rescan {
+            self.scan(ctx, config);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{env, fs, ops::Range, path::PathBuf};
+
+use anomaly_launcher::{crash_analyzer, diagnostics, AppConfig, CrashReport, CrashRules, Diagnosis};
+use eframe::egui::{self, CollapsingHeader, Color32, RichText, ScrollArea, TextStyle};
+use rfd::MessageDialog;
+
+pub struct CrashLogView {
+    pub open: bool,
+    path: Option<PathBuf>,
+    lines: Vec<String>,
+    section: Option<Range<usize>>,
+    scroll_to_section: bool,
+    report: Option<CrashReport>,
+    diagnosis: Option<Diagnosis>,
+    rules_error: Option<String>,
+}
+
+impl CrashLogView {
+    pub fn new() -> Self {
+        CrashLogView {
+            open: false,
+            path: None,
+            lines: Vec::new(),
+            section: None,
+            scroll_to_section: false,
+            report: None,
+            diagnosis: None,
+            rules_error: None,
+        }
+    }
+
+    /// Reads the newest X-Ray log and opens the window scrolled to its error section.
+    pub fn open_latest(&mut self) {
+        let root = env::current_dir().unwrap_or_default();
+        self.path = diagnostics::latest_log(&root);
+        // Логи X-Ray пишутся в cp1251, поэтому читаем байты и не падаем на невалидном UTF-8
+        let content = self
+            .path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+            .unwrap_or_default();
+        self.lines = content.lines().map(|line| line.to_string()).collect();
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.as_str()).collect();
+        self.section = diagnostics::fatal_error_section(&lines);
+        self.scroll_to_section = self.section.is_some();
+
+        // Сломанный файл правил модпака не должен мешать встроенным правилам
+        self.rules_error = None;
+        let rules = CrashRules::load(&root).unwrap_or_else(|e| {
+            self.rules_error = Some(e.to_string());
+            CrashRules::builtin()
+        });
+        self.report = crash_analyzer::parse_log(&content);
+        self.diagnosis = self.report.as_ref().and_then(|report| rules.diagnose(report));
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &AppConfig) {
+        let mut open = self.open;
+        let mut reload = false;
+        super::show_window(ctx, &mut open, "crash_log", "Crash Log", [720., 460.], |ui| {
+            ui.horizontal(|ui| {
+                match &self.path {
+                    Some(path) => ui.label(path.display().to_string()),
+                    None => ui.label("No logs found in 'appdata\\logs'."),
+                };
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Reload").clicked() {
+                    reload = true;
+                }
+                if ui.button("Create Diagnostic Bundle").clicked() {
+                    create_bundle(config);
+                }
+                match &self.section {
+                    Some(section) => {
+                        ui.label(RichText::new(format!("FATAL ERROR at line {}", section.start + 1)).color(Color32::from_rgb(200, 70, 70)));
+                        if ui.button("Jump to error").clicked() {
+                            self.scroll_to_section = true;
+                        }
+                    }
+                    None if self.path.is_some() => {
+                        ui.label("No fatal error in this log.");
+                    }
+                    None => {}
+                }
+            });
+            ui.separator();
+
+            if let Some(report) = &self.report {
+                show_analysis(ui, report, self.diagnosis.as_ref(), self.rules_error.as_deref());
+                ui.separator();
+            }
+
+            let row_height = ui.text_style_height(&TextStyle::Monospace);
+            let mut area = ScrollArea::both().auto_shrink([false, false]);
+            if self.scroll_to_section {
+                if let Some(section) = &self.section {
+                    let spacing = ui.spacing().item_spacing.y;
+                    area = area.vertical_scroll_offset(section.start as f32 * (row_height + spacing));
+                }
+                self.scroll_to_section = false;
+            }
+            area.show_rows(ui, row_height, self.lines.len(), |ui, rows| {
+                for index in rows {
+                    let text = RichText::new(&self.lines[index]).monospace();
+                    if self.section.as_ref().is_some_and(|section| section.contains(&index)) {
+                        ui.label(text.color(Color32::from_rgb(230, 90, 90)).background_color(Color32::from_rgba_unmultiplied(200, 70, 70, 30)));
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            });
+        });
+        self.open = open;
+        if reload {
+            self.open_latest();
+        }
+    }
+}
+
+fn show_analysis(ui: &mut egui::Ui, report: &CrashReport, diagnosis: Option<&Diagnosis>, rules_error: Option<&str>) {
+    match diagnosis {
+        Some(diagnosis) => {
+            ui.label(RichText::new(format!("Likely cause: {}", diagnosis.cause)).strong());
+            if !diagnosis.fix.is_empty() {
+                ui.label(format!("Fix: {}", diagnosis.fix));
+            }
+        }
+        None => {
+            ui.label("No known cause matches this crash. Create a diagnostic bundle and send it to the modpack team.");
+        }
+    }
+    if let Some(error) = rules_error {
+        ui.label(RichText::new(error).color(Color32::from_rgb(200, 70, 70)));
+    }
+
+    CollapsingHeader::new("Error details").id_salt("crash_details").show(ui, |ui| {
+        egui::Grid::new("crash_fields").num_columns(2).spacing([16., 2.]).show(ui, |ui| {
+            let location = match (&report.file, report.line) {
+                (Some(file), Some(line)) => Some(format!("{}:{}", file, line)),
+                (file, _) => file.clone(),
+            };
+            let script = report.script.as_ref().map(|(file, line)| format!("{}:{}", file, line));
+            let fields = [
+                ("Expression", report.expression.as_ref()),
+                ("Function", report.function.as_ref()),
+                ("File", location.as_ref()),
+                ("Description", report.description.as_ref()),
+                ("Arguments", report.arguments.as_ref()),
+                ("Script", script.as_ref()),
+            ];
+            for (name, value) in fields {
+                if let Some(value) = value {
+                    ui.label(name);
+                    ui.label(RichText::new(value).monospace());
+                    ui.end_row();
+                }
+            }
+        });
+        if !report.script_stack.is_empty() {
+            ui.label("Script stack:");
+            for frame in &report.script_stack {
+                ui.label(RichText::new(frame).monospace());
+            }
+        }
+    });
+}
+
+/// Writes a diagnostic bundle for the current game folder and tells the player where it is.
+pub fn create_bundle(config: &AppConfig) {
+    let root = env::current_dir().unwrap_or_default();
+    match diagnostics::create_bundle(&root, config) {
+        Ok(path) => {
+            MessageDialog::new()
+                .set_title("Diagnostic Bundle")
+                .set_description(format!("Diagnostic bundle saved to:\n{}\n\nAttach this file when reporting the crash.", path.display()))
+                .set_level(rfd::MessageLevel::Info)
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        }
+        Err(e) => {
+            MessageDialog::new()
+                .set_title("Diagnostic Bundle")
+                .set_description(format!("Failed to create the diagnostic bundle: {}", e))
+                .set_level(rfd::MessageLevel::Error)
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use anomaly_launcher::{
+    user_ltx::{self, COMMON_RESOLUTIONS, USER_LTX},
+    GraphicsSettings, ScreenMode, UserLtx,
+};
+use eframe::egui::{self, Color32, ComboBox, DragValue, RichText, Slider, Stroke};
+
+pub struct GraphicsView {
+    pub open: bool,
+    ltx: Option<UserLtx>,
+    settings: GraphicsSettings,
+    status: Option<(String, bool)>,
+}
+
+impl GraphicsView {
+    pub fn new() -> Self {
+        GraphicsView { open: false, ltx: None, settings: GraphicsSettings::default(), status: None }
+    }
+
+    /// Reads `user.ltx` again and opens the window.
+    pub fn open(&mut self) {
+        match UserLtx::load(Path::new(USER_LTX)) {
+            Ok(ltx) => {
+                self.settings = GraphicsSettings::from_ltx(&ltx);
+                self.ltx = Some(ltx);
+                self.status = None;
+            }
+            Err(e) => {
+                self.ltx = None;
+                self.status = Some((format!("Failed to read '{}': {}. Start the game once to create it.", USER_LTX, e), true));
+            }
+        }
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, game_running: bool) {
+        let mut open = self.open;
+        let mut reload = false;
+        super::show_window(ctx, &mut open, "graphics", "Game Settings", [380., 330.], |ui| {
+            if let Some(ltx) = &mut self.ltx {
+                egui::Grid::new("graphics_settings").num_columns(2).spacing([16., 8.]).show(ui, |ui| {
+                    let settings = &mut self.settings;
+
+                    ui.label("Resolution");
+                    let (width, height) = settings.resolution;
+                    ComboBox::from_id_salt("resolution")
+                        .selected_text(format!("{} x {}", width, height))
+                        .width(150.)
+                        .show_ui(ui, |ui| {
+                            ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
+                            if !COMMON_RESOLUTIONS.contains(&(width, height)) {
+                                ui.selectable_value(&mut settings.resolution, (width, height), format!("{} x {}", width, height));
+                            }
+                            for (w, h) in COMMON_RESOLUTIONS {
+                                ui.selectable_value(&mut settings.resolution, (w, h), format!("{} x {}", w, h));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Window mode");
+                    ComboBox::from_id_salt("screen_mode")
+                        .selected_text(settings.screen_mode.to_string())
+                        .width(150.)
+                        .show_ui(ui, |ui| {
+                            ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
+                            for mode in [ScreenMode::Fullscreen, ScreenMode::Borderless, ScreenMode::Windowed] {
+                                ui.selectable_value(&mut settings.screen_mode, mode, mode.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Vertical sync");
+                    ui.checkbox(&mut settings.vsync, "");
+                    ui.end_row();
+
+                    ui.label("FPS limit");
+                    ui.add(DragValue::new(&mut settings.fps_limit).range(0..=500).custom_formatter(|value, _| {
+                        if value == 0. { "Unlimited".to_string() } else { format!("{}", value) }
+                    }));
+                    ui.end_row();
+
+                    ui.label("Texture detail");
+                    ui.add(Slider::new(&mut settings.texture_lod, 0..=4).custom_formatter(|value, _| {
+                        match value as u32 {
+                            0 => "Maximum".to_string(),
+                            4 => "Minimum".to_string(),
+                            lod => format!("{}", lod),
+                        }
+                    }));
+                    ui.end_row();
+
+                    ui.label("Field of view");
+                    ui.add(Slider::new(&mut settings.fov, 55.0..=90.0).step_by(1.));
+                    ui.end_row();
+                });
+                ui.add_space(8.);
+
+                if game_running {
+                    ui.label(RichText::new("The game overwrites user.ltx when it exits. Save after closing it.").color(Color32::from_rgb(200, 140, 60)));
+                }
+                ui.horizontal(|ui| {
+                    let changed = self.settings != GraphicsSettings::from_ltx(ltx);
+                    if ui.add_enabled(changed && !game_running, egui::Button::new("Save")).clicked() {
+                        self.settings.apply(ltx);
+                        self.status = Some(match ltx.save(Path::new(USER_LTX)) {
+                            Ok(()) => ("Saved. The original file is kept as user.ltx.bak.".to_string(), false),
+                            Err(e) => (format!("Failed to save '{}': {}", USER_LTX, e), true),
+                        });
+                    }
+                    if ui.button("Reload").clicked() {
+                        reload = true;
+                    }
+                    let backup = user_ltx::backup_path(Path::new(USER_LTX));
+                    if ui.add_enabled(backup.exists() && !game_running, egui::Button::new("Restore Backup")).clicked() {
+                        match std::fs::copy(&backup, USER_LTX) {
+                            Ok(_) => reload = true,
+                            Err(e) => self.status = Some((format!("Failed to restore the backup: {}", e), true)),
+                        }
+                    }
+                });
+            }
+
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+        });
+        self.open = open;
+        if reload {
+            self.open();
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use anomaly_launcher::{AppConfig, Game};
+use eframe::egui::{self, RichText, TextEdit};
+
+pub struct LaunchOptionsView {
+    pub open: bool,
+}
+
+impl LaunchOptionsView {
+    pub fn new() -> Self {
+        LaunchOptionsView { open: false }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &mut AppConfig) {
+        super::show_window(ctx, &mut self.open, "launch_options", "Launch Options", [460., 340.], |ui| {
+            ui.label(format!("Profile: {}", config.profile_name()));
+            ui.add_space(6.);
+            ui.label(RichText::new("Flags"));
+            egui::Grid::new("launch_flags").num_columns(2).spacing([16., 4.]).show(ui, |ui| {
+                for (arg, description, enabled) in config.active_mut().flags.list_mut() {
+                    ui.checkbox(enabled, arg);
+                    ui.label(description);
+                    ui.end_row();
+                }
+            });
+            ui.add_space(6.);
+
+            ui.label(RichText::new("Extra arguments"));
+            ui.add(TextEdit::singleline(&mut config.active_mut().extra_args).hint_text("-arg \"value with spaces\"").desired_width(f32::INFINITY));
+            ui.add_space(6.);
+
+            ui.label(RichText::new("Command line"));
+            let game = Game::new(config);
+            if let Some(profile) = game.mo2_profile() {
+                ui.label(format!("Started through Mod Organizer 2 with the '{}' profile.", profile));
+            }
+            let command_line = game.command_line(&config.launch_args());
+            ui.add(TextEdit::multiline(&mut command_line.as_str()).code_editor().desired_width(f32::INFINITY).desired_rows(3));
+        });
+    }
+}
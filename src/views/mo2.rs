@@ -0,0 +1,86 @@
+use std::{env, path::PathBuf};
+
+use anomaly_launcher::{Mo2Config, Mo2Instance};
+use eframe::egui::{self, ComboBox, RichText, Stroke, TextEdit};
+
+pub struct Mo2View {
+    pub open: bool,
+    instance: Option<Mo2Instance>,
+    profiles: Vec<String>,
+}
+
+impl Mo2View {
+    pub fn new() -> Self {
+        Mo2View { open: false, instance: None, profiles: Vec::new() }
+    }
+
+    /// Looks for the instance again and opens the window.
+    pub fn open(&mut self, config: &Mo2Config) {
+        self.detect(config);
+        self.open = true;
+    }
+
+    fn detect(&mut self, config: &Mo2Config) {
+        self.instance = Mo2Instance::detect(&env::current_dir().unwrap_or_default(), config);
+        self.profiles = self.instance.as_ref().map(Mo2Instance::profiles).unwrap_or_default();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &mut Mo2Config) {
+        let mut open = self.open;
+        let mut search = false;
+        super::show_window(ctx, &mut open, "mo2", "Mod Organizer 2", [440., 340.], |ui| {
+            ui.label(RichText::new("Instance folder"));
+            ui.horizontal(|ui| {
+                let mut path = config.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+                if ui.add(TextEdit::singleline(&mut path).hint_text("found automatically").desired_width(300.)).changed() {
+                    config.path = (!path.trim().is_empty()).then(|| PathBuf::from(path.trim()));
+                }
+                if ui.button("Search").clicked() {
+                    search = true;
+                }
+            });
+            ui.add_space(6.);
+
+            let Some(instance) = &self.instance else {
+                ui.label("No Mod Organizer 2 instance with ModOrganizer.exe and ModOrganizer.ini was found. The game is started directly.");
+                return;
+            };
+            ui.label(format!("Found: {}", instance.dir.display()));
+            if let Some(game) = &instance.game_name {
+                ui.label(format!("Game: {}", game));
+            }
+            ui.checkbox(&mut config.enabled, "Launch the game through Mod Organizer 2");
+            ui.add_space(6.);
+
+            ui.add_enabled_ui(config.enabled, |ui| {
+                ui.label(RichText::new("Profile"));
+                let selected_in_mo2 = format!("Selected in MO2 ({})", instance.selected_profile.as_deref().unwrap_or("none"));
+                ComboBox::from_id_salt("mo2_profile")
+                    .selected_text(config.profile.clone().unwrap_or_else(|| selected_in_mo2.clone()))
+                    .width(220.)
+                    .show_ui(ui, |ui| {
+                        ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
+                        ui.selectable_value(&mut config.profile, None, selected_in_mo2);
+                        for profile in &self.profiles {
+                            ui.selectable_value(&mut config.profile, Some(profile.clone()), profile);
+                        }
+                    });
+            });
+            ui.add_space(6.);
+
+            egui::CollapsingHeader::new(format!("Executables ({})", instance.executables.len())).show(ui, |ui| {
+                egui::Grid::new("mo2_executables").num_columns(2).spacing([16., 4.]).show(ui, |ui| {
+                    for executable in &instance.executables {
+                        ui.label(&executable.title);
+                        ui.label(format!("{} {}", executable.binary, executable.arguments));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+        self.open = open;
+        if search {
+            self.detect(config);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use eframe::egui::{self, ViewportBuilder, ViewportId};
+
+use crate::styles::Styles;
+
+pub mod addons;
+pub mod conflicts;
+pub mod crash_log;
+pub mod graphics;
+pub mod launch_options;
+pub mod mo2;
+pub mod mod_list;
+pub mod profiles;
+pub mod runner;
+pub mod save_backups;
+pub mod saves;
+pub mod shader_cache;
+pub mod system_info;
+
+/// Shows `add_contents` in a separate native window while `open` is set.
+/// Closing the window clears `open`.
+pub fn show_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    id: &str,
+    title: &str,
+    size: [f32; 2],
+    mut add_contents: impl FnMut(&mut egui::Ui),
+) {
+    if !*open {
+        return;
+    }
+
+    let builder = ViewportBuilder::default()
+        .with_title(title)
+        .with_inner_size(size)
+        .with_min_inner_size([size[0] * 0.75, size[1] * 0.75]);
+
+    ctx.show_viewport_immediate(ViewportId::from_hash_of(id), builder, |ctx, _class| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if ui.visuals().dark_mode {
+                ui.style_mut().visuals = Styles::dark();
+            } else {
+                ui.style_mut().visuals = Styles::light();
+            }
+            add_contents(ui);
+        });
+
+        if ctx.input(|i| i.viewport().close_requested()) {
+            *open = false;
+        }
+    });
+}
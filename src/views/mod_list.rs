@@ -0,0 +1,188 @@
+use std::env;
+
+use anomaly_launcher::{
+    modlist::{self, LOADORDER_FILE, REFERENCE_MODLIST},
+    Deviation, Mo2Config, Mo2Instance, ModList, ModState,
+};
+use eframe::egui::{self, Color32, RichText, ScrollArea};
+use rfd::MessageDialog;
+
+pub struct ModListView {
+    pub open: bool,
+    profile: Option<String>,
+    modlist: Option<ModList>,
+    reference: Option<ModList>,
+    load_order: Vec<String>,
+    deviations: Vec<Deviation>,
+    only_deviations: bool,
+    status: Option<(String, bool)>,
+}
+
+impl ModListView {
+    pub fn new() -> Self {
+        ModListView {
+            open: false,
+            profile: None,
+            modlist: None,
+            reference: None,
+            load_order: Vec::new(),
+            deviations: Vec::new(),
+            only_deviations: false,
+            status: None,
+        }
+    }
+
+    /// Reads the mod list of the MO2 profile the game is launched with and opens the window.
+    pub fn open(&mut self, config: &Mo2Config) {
+        self.load(config);
+        self.open = true;
+    }
+
+    fn load(&mut self, config: &Mo2Config) {
+        *self = ModListView { open: self.open, only_deviations: self.only_deviations, ..ModListView::new() };
+        let root = env::current_dir().unwrap_or_default();
+        let Some(instance) = Mo2Instance::detect(&root, config) else {
+            self.status = Some(("No Mod Organizer 2 instance was found next to the game.".to_string(), true));
+            return;
+        };
+        let Some(profile) = instance.resolve_profile(config.profile.as_deref()) else {
+            self.status = Some(("The Mod Organizer 2 instance has no profiles.".to_string(), true));
+            return;
+        };
+        let profile_dir = instance.profile_dir(&profile);
+        match ModList::load(&modlist::modlist_path(&profile_dir)) {
+            Ok(list) => self.modlist = Some(list),
+            Err(e) => self.status = Some((format!("Failed to read the mod list of '{}': {}", profile, e), true)),
+        }
+        self.load_order = modlist::load_order(&profile_dir.join(LOADORDER_FILE)).unwrap_or_default();
+        self.reference = ModList::load(&root.join(REFERENCE_MODLIST)).ok();
+        if let (Some(list), Some(reference)) = (&self.modlist, &self.reference) {
+            self.deviations = modlist::compare(list, reference);
+        }
+        self.profile = Some(profile);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &Mo2Config) {
+        let mut open = self.open;
+        let mut reload = false;
+        let mut restore = false;
+        super::show_window(ctx, &mut open, "mod_list", "Mod List", [520., 480.], |ui| {
+            if let Some(profile) = &self.profile {
+                ui.label(format!("MO2 profile: {}", profile));
+            }
+            match (&self.modlist, &self.reference) {
+                (Some(_), None) => {
+                    ui.label(format!("The modpack doesn't ship a reference mod list ({}).", REFERENCE_MODLIST));
+                }
+                (Some(_), Some(_)) if self.deviations.is_empty() => {
+                    ui.label(RichText::new("The mod list matches the Dead Reckoning defaults.").color(Color32::from_rgb(80, 170, 80)));
+                }
+                (Some(_), Some(_)) => {
+                    ui.label(
+                        RichText::new(format!("{} difference(s) from the Dead Reckoning defaults.", self.deviations.len()))
+                            .color(Color32::from_rgb(200, 140, 60)),
+                    );
+                }
+                _ => {}
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Reload").clicked() {
+                    reload = true;
+                }
+                let can_restore = self.modlist.is_some() && self.reference.is_some() && !self.deviations.is_empty();
+                if ui.add_enabled(can_restore, egui::Button::new("Restore Dead Reckoning default mod list")).clicked() {
+                    restore = true;
+                }
+                ui.checkbox(&mut self.only_deviations, "Only differences");
+            });
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+            ui.separator();
+
+            let Some(list) = &self.modlist else {
+                return;
+            };
+            ScrollArea::vertical().auto_shrink([false, false]).max_height(ui.available_height() - 40.).show(ui, |ui| {
+                egui::Grid::new("mod_list").num_columns(3).striped(true).spacing([12., 2.]).show(ui, |ui| {
+                    for (priority, entry) in list.by_priority() {
+                        let deviation = self.deviations.iter().find(|d| d.name() == entry.name);
+                        if self.only_deviations && deviation.is_none() {
+                            continue;
+                        }
+                        ui.label(RichText::new(format!("{}", priority)).weak());
+                        let color = match entry.state {
+                            ModState::Enabled => ui.visuals().text_color(),
+                            ModState::Disabled => ui.visuals().weak_text_color(),
+                            ModState::Unmanaged => Color32::from_rgb(110, 140, 200),
+                        };
+                        ui.label(RichText::new(&entry.name).color(color));
+                        match deviation {
+                            Some(deviation) => ui.label(RichText::new(deviation_label(deviation)).color(Color32::from_rgb(200, 140, 60))),
+                            None => ui.label(""),
+                        };
+                        ui.end_row();
+                    }
+                    // Mods from the reference list the profile doesn't have at all
+                    for deviation in self.deviations.iter().filter(|d| matches!(d, Deviation::Missing(_))) {
+                        ui.label("");
+                        ui.label(RichText::new(deviation.name()).color(Color32::from_rgb(200, 70, 70)));
+                        ui.label(RichText::new(deviation_label(deviation)).color(Color32::from_rgb(200, 70, 70)));
+                        ui.end_row();
+                    }
+                });
+            });
+            if !self.load_order.is_empty() {
+                ui.label(RichText::new(format!("Plugins in loadorder.txt: {}", self.load_order.join(", "))).weak());
+            }
+        });
+        self.open = open;
+
+        if restore {
+            self.restore(config);
+        } else if reload {
+            self.load(config);
+        }
+    }
+
+    fn restore(&mut self, config: &Mo2Config) {
+        let (Some(list), Some(reference), Some(profile)) = (&self.modlist, &self.reference, &self.profile) else {
+            return;
+        };
+        let confirm = MessageDialog::new()
+            .set_title("Restore default mod list")
+            .set_description(format!(
+                "Replace the mod list of the '{}' profile with the Dead Reckoning defaults? Mods you added stay installed but are disabled. The list from before the first restore is kept as modlist.txt.bak.\n\nClose Mod Organizer 2 first, it overwrites the list when it exits.",
+                profile
+            ))
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if confirm != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        let restored = modlist::restore(list, reference);
+        let Some(instance) = Mo2Instance::detect(&env::current_dir().unwrap_or_default(), config) else {
+            return;
+        };
+        let path = modlist::modlist_path(&instance.profile_dir(profile));
+        let result = restored.save(&path);
+        self.load(config);
+        self.status = Some(match result {
+            Ok(()) => ("The default mod list has been restored.".to_string(), false),
+            Err(e) => (format!("Failed to write '{}': {}", path.display(), e), true),
+        });
+    }
+}
+
+fn deviation_label(deviation: &Deviation) -> &'static str {
+    match deviation {
+        Deviation::Disabled(_) => "disabled",
+        Deviation::Enabled(_) => "not in modpack",
+        Deviation::Missing(_) => "missing",
+        Deviation::Moved(_) => "out of order",
+    }
+}
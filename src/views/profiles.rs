@@ -0,0 +1,65 @@
+use anomaly_launcher::{app_config::DEFAULT_PROFILE, AppConfig, ProfileError};
+use eframe::egui::{self, Color32, RichText, TextEdit};
+
+pub struct ProfilesView {
+    pub open: bool,
+    name: String,
+    error: Option<String>,
+}
+
+impl ProfilesView {
+    pub fn new() -> Self {
+        ProfilesView { open: false, name: String::new(), error: None }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &mut AppConfig) {
+        let mut open = self.open;
+        super::show_window(ctx, &mut open, "profiles", "Launch Profiles", [360., 320.], |ui| {
+            ui.label("The selected profile is used by the Play button and by `launch` without `--profile`.");
+            ui.add_space(6.);
+
+            let selected = config.profile_name().to_string();
+            egui::ScrollArea::vertical().max_height(150.).show(ui, |ui| {
+                for name in config.profile_names() {
+                    if ui.selectable_label(name == selected, &name).clicked() {
+                        let _ = config.select_profile(&name);
+                        self.error = None;
+                    }
+                }
+            });
+            ui.add_space(6.);
+
+            ui.add(TextEdit::singleline(&mut self.name).hint_text("Profile name").desired_width(f32::INFINITY));
+            let name = self.name.trim().to_string();
+            let mut result: Option<Result<(), ProfileError>> = None;
+            ui.horizontal(|ui| {
+                if ui.button("New").clicked() {
+                    result = Some(config.create_profile(&name).and_then(|_| config.select_profile(&name)));
+                }
+                if ui.button("Duplicate").on_hover_text(format!("Copy '{}' to a new profile", selected)).clicked() {
+                    result = Some(config.duplicate_profile(&selected, &name).and_then(|_| config.select_profile(&name)));
+                }
+                let named = selected != DEFAULT_PROFILE;
+                if ui.add_enabled(named, egui::Button::new("Rename")).clicked() {
+                    result = Some(config.rename_profile(&selected, &name));
+                }
+                if ui.add_enabled(named, egui::Button::new("Delete")).clicked() {
+                    result = Some(config.delete_profile(&selected));
+                }
+            });
+
+            match result {
+                Some(Ok(())) => {
+                    self.name.clear();
+                    self.error = None;
+                }
+                Some(Err(e)) => self.error = Some(e.to_string()),
+                None => {}
+            }
+            if let Some(error) = &self.error {
+                ui.label(RichText::new(error).color(Color32::from_rgb(200, 70, 70)));
+            }
+        });
+        self.open = open;
+    }
+}
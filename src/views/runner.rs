@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anomaly_launcher::{RunnerConfig, RunnerKind};
+use eframe::egui::{self, ComboBox, RichText, Stroke, TextEdit};
+
+pub struct RunnerView {
+    pub open: bool,
+    new_key: String,
+    new_value: String,
+}
+
+impl RunnerView {
+    pub fn new() -> Self {
+        RunnerView { open: false, new_key: String::new(), new_value: String::new() }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, runner: &mut RunnerConfig) {
+        let mut open = self.open;
+        super::show_window(ctx, &mut open, "runner", "Wine / Proton", [420., 340.], |ui| {
+            ui.label(RichText::new("Runner"));
+            ComboBox::from_id_salt("runner_kind")
+                .selected_text(runner.kind.to_string())
+                .width(150.)
+                .show_ui(ui, |ui| {
+                    ui.style_mut().visuals.widgets.hovered.bg_stroke = Stroke::NONE;
+                    ui.selectable_value(&mut runner.kind, RunnerKind::Native, "Native");
+                    ui.selectable_value(&mut runner.kind, RunnerKind::Wine, "Wine");
+                    ui.selectable_value(&mut runner.kind, RunnerKind::Proton, "Proton");
+                });
+            if runner.kind == RunnerKind::Native {
+                ui.label("The game executable is started directly.");
+                return;
+            }
+
+            let default_binary = if runner.kind == RunnerKind::Wine { "wine" } else { "proton" };
+            ui.label(if runner.kind == RunnerKind::Wine { "Wine binary" } else { "Proton script" });
+            let mut binary = runner.binary.clone().unwrap_or_default();
+            if ui.add(TextEdit::singleline(&mut binary).hint_text(default_binary).desired_width(f32::INFINITY)).changed() {
+                runner.binary = (!binary.trim().is_empty()).then(|| binary.trim().to_string());
+            }
+
+            ui.label(if runner.kind == RunnerKind::Wine { "Prefix (WINEPREFIX)" } else { "Prefix (STEAM_COMPAT_DATA_PATH)" });
+            let mut prefix = runner.prefix.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+            if ui.add(TextEdit::singleline(&mut prefix).hint_text("default prefix").desired_width(f32::INFINITY)).changed() {
+                runner.prefix = (!prefix.trim().is_empty()).then(|| PathBuf::from(prefix.trim()));
+            }
+
+            ui.checkbox(&mut runner.dxvk, "Use DXVK")
+                .on_hover_text("Use the DXVK libraries installed in the prefix instead of WineD3D.");
+
+            ui.add_space(6.);
+            ui.label(RichText::new("Environment variables"));
+            let mut removed = None;
+            egui::Grid::new("runner_env").num_columns(3).show(ui, |ui| {
+                for (key, value) in runner.env.iter_mut() {
+                    ui.label(key);
+                    ui.add(TextEdit::singleline(value).desired_width(180.));
+                    if ui.small_button("Remove").clicked() {
+                        removed = Some(key.clone());
+                    }
+                    ui.end_row();
+                }
+                ui.add(TextEdit::singleline(&mut self.new_key).hint_text("NAME").desired_width(110.));
+                ui.add(TextEdit::singleline(&mut self.new_value).hint_text("value").desired_width(180.));
+                if ui.small_button("Add").clicked() && !self.new_key.trim().is_empty() {
+                    runner.env.insert(self.new_key.trim().to_string(), self.new_value.clone());
+                    self.new_key.clear();
+                    self.new_value.clear();
+                }
+                ui.end_row();
+            });
+            if let Some(key) = removed {
+                runner.env.remove(&key);
+            }
+        });
+        self.open = open;
+    }
+}
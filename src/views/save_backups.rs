@@ -0,0 +1,171 @@
+use std::{
+    env, fs,
+    sync::{Arc, Mutex},
+};
+
+use anomaly_launcher::{
+    save_backup::{self, BackupReason},
+    AppConfig, SaveBackup,
+};
+use eframe::egui::{self, Color32, DragValue, RichText, ScrollArea};
+use rfd::MessageDialog;
+
+pub struct SaveBackupsView {
+    pub open: bool,
+    // Заполняется фоновым потоком, когда архивация или восстановление закончены
+    result: Arc<Mutex<Option<Result<String, String>>>>,
+    busy: bool,
+    backups: Vec<SaveBackup>,
+    status: Option<(String, bool)>,
+}
+
+impl SaveBackupsView {
+    pub fn new() -> Self {
+        SaveBackupsView { open: false, result: Arc::new(Mutex::new(None)), busy: false, backups: Vec::new(), status: None }
+    }
+
+    /// Lists the backups and opens the window.
+    pub fn open(&mut self) {
+        self.reload();
+        self.open = true;
+    }
+
+    fn reload(&mut self) {
+        self.backups = save_backup::list(&env::current_dir().unwrap_or_default());
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &mut AppConfig) {
+        if let Some(result) = self.result.lock().ok().and_then(|mut guard| guard.take()) {
+            self.busy = false;
+            self.reload();
+            self.status = Some(match result {
+                Ok(message) => (message, false),
+                Err(e) => (e, true),
+            });
+        }
+
+        let mut open = self.open;
+        let mut create = false;
+        let mut restore = None;
+        let mut delete = None;
+        super::show_window(ctx, &mut open, "save_backups", "Save Backups", [520., 440.], |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!self.busy, egui::Button::new("Back Up Now")).clicked() {
+                    create = true;
+                }
+                if self.busy {
+                    ui.spinner();
+                }
+            });
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+            ui.add_space(4.);
+
+            let settings = &mut config.save_backups;
+            ui.checkbox(&mut settings.before_update, "Back up the saves before installing updates");
+            ui.horizontal(|ui| {
+                ui.label("Keep the last");
+                ui.add(DragValue::new(&mut settings.keep).range(0..=100));
+                ui.label("backups (0 keeps all)");
+            });
+            ui.horizontal(|ui| {
+                let mut limit_age = settings.max_age_days.is_some();
+                if ui.checkbox(&mut limit_age, "Delete backups older than").changed() {
+                    settings.max_age_days = limit_age.then_some(30);
+                }
+                if let Some(days) = &mut settings.max_age_days {
+                    ui.add(DragValue::new(days).range(1..=3650));
+                    ui.label("days");
+                }
+            });
+            ui.separator();
+
+            if self.backups.is_empty() {
+                ui.label("There are no save backups yet.");
+                return;
+            }
+            ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                egui::Grid::new("save_backups").num_columns(5).striped(true).spacing([12., 4.]).show(ui, |ui| {
+                    for backup in &self.backups {
+                        ui.label(backup.created.format("%Y-%m-%d %H:%M").to_string());
+                        ui.label(backup.version.as_deref().unwrap_or("unknown"));
+                        ui.label(RichText::new(backup.reason.to_string()).weak());
+                        ui.label(format!("{:.1} MB", backup.size as f64 / (1024. * 1024.)));
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!self.busy, egui::Button::new("Restore")).clicked() {
+                                restore = Some(backup.clone());
+                            }
+                            if ui.add_enabled(!self.busy, egui::Button::new("Delete")).clicked() {
+                                delete = Some(backup.clone());
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+        self.open = open;
+
+        if create {
+            self.run(ctx, config, None);
+        } else if let Some(backup) = restore {
+            let confirm = MessageDialog::new()
+                .set_title("Restore saves")
+                .set_description(format!(
+                    "Replace your saves with the backup from {}? The current saves are backed up first.",
+                    backup.created.format("%Y-%m-%d %H:%M")
+                ))
+                .set_level(rfd::MessageLevel::Warning)
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if confirm == rfd::MessageDialogResult::Yes {
+                self.run(ctx, config, Some(backup));
+            }
+        } else if let Some(backup) = delete {
+            let confirm = MessageDialog::new()
+                .set_title("Delete backup")
+                .set_description(format!("Delete the save backup from {}?", backup.created.format("%Y-%m-%d %H:%M")))
+                .set_level(rfd::MessageLevel::Warning)
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if confirm == rfd::MessageDialogResult::Yes {
+                self.status = Some(match fs::remove_file(&backup.path) {
+                    Ok(()) => ("The backup has been deleted.".to_string(), false),
+                    Err(e) => (format!("Failed to delete {}: {}", backup.name(), e), true),
+                });
+                self.reload();
+            }
+        }
+    }
+
+    /// Backs up the saves, or restores `backup`, in the background.
+    fn run(&mut self, ctx: &egui::Context, config: &AppConfig, backup: Option<SaveBackup>) {
+        self.busy = true;
+        self.status = None;
+        let result = self.result.clone();
+        let version = config.version.clone();
+        let settings = config.save_backups.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let root = env::current_dir().unwrap_or_default();
+            let message = match backup {
+                Some(backup) => save_backup::restore_with_backup(&root, &backup.path, version.as_deref(), &settings)
+                    .map(|()| format!("The saves from {} have been restored.", backup.created.format("%Y-%m-%d %H:%M")))
+                    .map_err(|e| format!("Failed to restore the saves: {}", e)),
+                None => match save_backup::create(&root, version.as_deref(), BackupReason::Manual) {
+                    Ok(Some(backup)) => save_backup::prune(&root, &settings)
+                        .map(|_| format!("Saves backed up to {}", backup.name()))
+                        .map_err(|e| format!("Failed to delete old backups: {}", e)),
+                    Ok(None) => Err(format!("There are no saves in {} to back up.", save_backup::SAVES_DIR)),
+                    Err(e) => Err(format!("Failed to back up the saves: {}", e)),
+                },
+            };
+            if let Ok(mut guard) = result.lock() {
+                *guard = Some(message);
+            }
+            ctx.request_repaint();
+        });
+    }
+}
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, env, path::PathBuf};
+
+use anomaly_launcher::{saves, AppConfig, SaveGame};
+use eframe::egui::{self, Color32, ColorImage, RichText, ScrollArea, TextureHandle, TextureOptions};
+use rfd::{FileDialog, MessageDialog};
+
+const THUMBNAIL_SIZE: f32 = 96.;
+const ROW_HEIGHT: f32 = 116.;
+
+pub struct SavesView {
+    pub open: bool,
+    saves: Vec<SaveGame>,
+    // Декодированные миниатюры; None, если .dds не читается
+    thumbnails: HashMap<PathBuf, Option<TextureHandle>>,
+    status: Option<(String, bool)>,
+}
+
+impl SavesView {
+    pub fn new() -> Self {
+        SavesView { open: false, saves: Vec::new(), thumbnails: HashMap::new(), status: None }
+    }
+
+    /// Lists the saves and opens the window.
+    pub fn open(&mut self, config: &AppConfig) {
+        self.status = None;
+        self.reload(config);
+        self.open = true;
+    }
+
+    fn reload(&mut self, config: &AppConfig) {
+        let root = env::current_dir().unwrap_or_default();
+        let _ = saves::track(&root, config.version.as_deref());
+        // Сохранение могло быть перезаписано игрой, миниатюры читаем заново
+        self.thumbnails.clear();
+        match saves::list(&root) {
+            Ok(list) => self.saves = list,
+            Err(e) => {
+                self.saves.clear();
+                self.status = Some((format!("Failed to read the saves: {}", e), true));
+            }
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &AppConfig) {
+        let mut open = self.open;
+        let mut reload = false;
+        let mut import = false;
+        let mut export = None;
+        let mut delete = None;
+        super::show_window(ctx, &mut open, "saves", "Saves", [560., 520.], |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Refresh").clicked() {
+                    reload = true;
+                }
+                if ui.button("Import...").clicked() {
+                    import = true;
+                }
+                let total: u64 = self.saves.iter().map(|save| save.size).sum();
+                ui.label(format!("{} save(s), {:.1} MB", self.saves.len(), total as f64 / (1024. * 1024.)));
+            });
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+            ui.separator();
+
+            if self.saves.is_empty() {
+                ui.label("There are no saves yet.");
+                return;
+            }
+            // Рисуются только видимые строки, так что .dds декодируются по мере прокрутки
+            ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, ROW_HEIGHT, self.saves.len(), |ui, rows| {
+                for save in &self.saves[rows] {
+                    ui.horizontal(|ui| {
+                        ui.set_height(ROW_HEIGHT);
+                        let texture = self.thumbnails.entry(save.path.clone()).or_insert_with(|| {
+                            let image = save.load_thumbnail()?;
+                            let size = [image.width() as usize, image.height() as usize];
+                            let image = ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                            Some(ui.ctx().load_texture(save.name.clone(), image, TextureOptions::LINEAR))
+                        });
+                        match texture {
+                            Some(texture) => {
+                                ui.add(egui::Image::new(&*texture).fit_to_exact_size(egui::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE)));
+                            }
+                            None => {
+                                ui.add_sized([THUMBNAIL_SIZE, THUMBNAIL_SIZE], egui::Label::new(RichText::new("no preview").weak()));
+                            }
+                        }
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(&save.name).strong());
+                            ui.label(save.modified.format("%Y-%m-%d %H:%M").to_string());
+                            ui.label(format!("{:.1} MB", save.size as f64 / (1024. * 1024.)));
+                            ui.label(RichText::new(format!("Modpack version: {}", save.version.as_deref().unwrap_or("unknown"))).weak());
+                            ui.horizontal(|ui| {
+                                if ui.button("Export...").clicked() {
+                                    export = Some(save.clone());
+                                }
+                                if ui.button("Delete").clicked() {
+                                    delete = Some(save.clone());
+                                }
+                            });
+                        });
+                    });
+                }
+            });
+        });
+        self.open = open;
+
+        if let Some(save) = export {
+            let Some(path) = FileDialog::new().set_file_name(format!("{}.zip", save.name)).add_filter("Zip archive", &["zip"]).save_file() else {
+                return;
+            };
+            self.status = Some(match saves::export(&save, &path) {
+                Ok(()) => (format!("Exported to {}", path.display()), false),
+                Err(e) => (format!("Failed to export {}: {}", save.name, e), true),
+            });
+        } else if let Some(save) = delete {
+            let confirm = MessageDialog::new()
+                .set_title("Delete save")
+                .set_description(format!("Delete the save '{}'? This can't be undone unless it is in a save backup.", save.name))
+                .set_level(rfd::MessageLevel::Warning)
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if confirm == rfd::MessageDialogResult::Yes {
+                let result = saves::delete(&save);
+                self.reload(config);
+                self.status = Some(match result {
+                    Ok(()) => (format!("'{}' has been deleted.", save.name), false),
+                    Err(e) => (format!("Failed to delete {}: {}", save.name, e), true),
+                });
+            }
+        } else if import {
+            let Some(path) = FileDialog::new().add_filter("Saves", &["scop", "zip"]).pick_file() else {
+                return;
+            };
+            let result = saves::import(&env::current_dir().unwrap_or_default(), &path);
+            self.reload(config);
+            self.status = Some(match result {
+                Ok(name) => (format!("'{}' has been imported.", name), false),
+                Err(e) => (format!("Failed to import {}: {}", path.display(), e), true),
+            });
+        } else if reload {
+            self.status = None;
+            self.reload(config);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::env;
+
+use anomaly_launcher::{shader_cache, AppConfig, Renderer, ShaderCacheError, ShaderCacheUsage};
+use eframe::egui::{self, Color32, Grid, RichText};
+
+pub struct ShaderCacheView {
+    pub open: bool,
+    // None, если папки кэша ещё нет
+    usage: Option<ShaderCacheUsage>,
+    status: Option<(String, bool)>,
+}
+
+impl ShaderCacheView {
+    pub fn new() -> Self {
+        ShaderCacheView { open: false, usage: None, status: None }
+    }
+
+    /// Measures the cache and opens the window.
+    pub fn open(&mut self, config: &AppConfig) {
+        self.status = None;
+        self.reload(config);
+        self.open = true;
+    }
+
+    fn reload(&mut self, config: &AppConfig) {
+        self.usage = shader_cache::usage(&env::current_dir().unwrap_or_default(), &config.mo2).ok();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &mut AppConfig) {
+        let mut open = self.open;
+        // Some(None) — очистить весь кэш
+        let mut clear: Option<Option<Renderer>> = None;
+        let mut reload = false;
+        super::show_window(ctx, &mut open, "shader_cache", "Shader Cache", [420., 340.], |ui| {
+            let Some(usage) = &self.usage else {
+                ui.label("The shader cache folder doesn't exist yet. The game creates it on the first launch.");
+                return;
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("Total: {}", format_size(usage.total()))).strong());
+                if ui.button("Refresh").clicked() {
+                    reload = true;
+                }
+            });
+            ui.add_space(4.);
+            Grid::new("shader_cache_sizes").num_columns(3).striped(true).show(ui, |ui| {
+                for (renderer, size) in &usage.renderers {
+                    let current = *renderer == config.active().renderer;
+                    ui.label(if current { RichText::new(format!("{} (current)", renderer)).strong() } else { RichText::new(renderer.to_string()) });
+                    ui.label(format_size(*size));
+                    if ui.add_enabled(*size > 0, egui::Button::new("Clear")).clicked() {
+                        clear = Some(Some(*renderer));
+                    }
+                    ui.end_row();
+                }
+                if usage.other > 0 {
+                    ui.label("Other");
+                    ui.label(format_size(usage.other));
+                    ui.end_row();
+                }
+            });
+            ui.add_space(4.);
+            if ui.add_enabled(usage.total() > 0, egui::Button::new("Clear All")).clicked() {
+                clear = Some(None);
+            }
+            if let Some((message, is_error)) = &self.status {
+                let color = if *is_error { Color32::from_rgb(200, 70, 70) } else { Color32::from_rgb(80, 170, 80) };
+                ui.label(RichText::new(message).color(color));
+            }
+
+            ui.separator();
+            let settings = &mut config.shader_cache;
+            ui.checkbox(&mut settings.clear_on_renderer_change, "Clear the cache when switching renderers");
+            ui.checkbox(&mut settings.clear_after_update, "Clear the cache after updates that change shaders");
+        });
+        self.open = open;
+
+        if let Some(renderer) = clear {
+            let result = shader_cache::clear(&env::current_dir().unwrap_or_default(), &config.mo2, renderer);
+            self.reload(config);
+            let what = renderer.map_or_else(|| "The shader cache".to_string(), |renderer| format!("The {} shader cache", renderer));
+            self.status = Some(match result {
+                Ok(freed) => (format!("{} has been deleted, {} freed.", what, format_size(freed)), false),
+                Err(ShaderCacheError::NotFound) => ("The shader cache folder was not found.".to_string(), true),
+                Err(e) => (e.to_string(), true),
+            });
+        } else if reload {
+            self.reload(config);
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024. * 1024.))
+}
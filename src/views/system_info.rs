@@ -0,0 +1,43 @@
+use std::{env, thread};
+
+use anomaly_launcher::system_info::CpuFeatures;
+use eframe::egui::{self, Color32, RichText};
+
+pub struct SystemInfoView {
+    pub open: bool,
+}
+
+impl SystemInfoView {
+    pub fn new() -> Self {
+        SystemInfoView { open: false }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, cpu: &CpuFeatures) {
+        super::show_window(ctx, &mut self.open, "system_info", "System Information", [360., 240.], |ui| {
+            ui.label(RichText::new("Processor").size(18.0));
+            ui.label(cpu.brand.as_deref().unwrap_or("Unknown processor"));
+            let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+            ui.label(format!("{} logical cores, {} {}", cores, env::consts::OS, env::consts::ARCH));
+            ui.add_space(6.);
+
+            egui::Grid::new("cpu_features").num_columns(2).spacing([24., 4.]).show(ui, |ui| {
+                for (name, supported) in cpu.list() {
+                    ui.label(name);
+                    if supported {
+                        ui.label(RichText::new("Supported").color(Color32::from_rgb(80, 170, 80)));
+                    } else {
+                        ui.label(RichText::new("Not supported").color(Color32::from_rgb(200, 70, 70)));
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.add_space(6.);
+
+            if cpu.supports_avx_build() {
+                ui.label("The AVX builds of the game can be used on this computer.");
+            } else {
+                ui.label("This processor cannot run the AVX builds. Keep \"Use AVX\" disabled.");
+            }
+        });
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use anomaly_launcher::{
+    addons::{self, AddonError, AddonStatus},
+    modlist::{self, ModState},
+    InstalledAddons, Manifest, ManifestAddon, Mo2Instance, ModList,
+};
+use semver::Version;
+
+const MANIFEST: &str = r#"
+[[addon]]
+id = "hd-models"
+name = "HD Models"
+version = "1.2.0"
+url = "https://example.com/addons/hd-models.zip"
+description = "High-poly weapon and NPC models"
+
+[[addon]]
+id = "hd-models-patch"
+name = "HD Models Patch"
+version = "1.0.0"
+url = "https://example.com/addons/hd-models-patch.zip"
+requires = ["hd-models"]
+
+[[addon]]
+id = "minimal-hud"
+name = "Minimal HUD"
+version = "2.0.0"
+url = "https://example.com/addons/minimal-hud.zip"
+conflicts = ["classic-hud"]
+
+[[addon]]
+id = "classic-hud"
+name = "Classic HUD"
+version = "1.0.0"
+url = "https://example.com/addons/classic-hud.zip"
+"#;
+
+fn catalog() -> Vec<ManifestAddon> {
+    Manifest::parse(MANIFEST).unwrap().addons
+}
+
+fn installed(ids: &[(&str, &str)]) -> InstalledAddons {
+    let mut installed = InstalledAddons::default();
+    for (id, version) in ids {
+        installed.addons.insert(
+            id.to_string(),
+            addons::InstalledAddon { version: Version::parse(version).unwrap(), folder: id.to_string() },
+        );
+    }
+    installed
+}
+
+fn write_zip(path: &Path, files: &[(&str, &str)]) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    for (name, content) in files {
+        zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn parses_an_addon_only_manifest() {
+    let catalog = catalog();
+    assert_eq!(catalog.len(), 4);
+    assert_eq!(catalog[1].requires, vec!["hd-models"]);
+    assert_eq!(catalog[2].conflicts, vec!["classic-hud"]);
+    assert_eq!(addons::status(&catalog[0], &installed(&[("hd-models", "1.1.0")])), AddonStatus::Outdated);
+    assert_eq!(addons::status(&catalog[0], &installed(&[("hd-models", "1.2.0")])), AddonStatus::Installed);
+}
+
+#[test]
+fn checks_dependencies_and_incompatibilities() {
+    let catalog = catalog();
+    let result = addons::check_install(&catalog, &installed(&[]), "hd-models-patch");
+    assert!(matches!(result, Err(AddonError::MissingDependencies { missing, .. }) if missing == vec!["HD Models"]));
+    assert!(addons::check_install(&catalog, &installed(&[("hd-models", "1.2.0")]), "hd-models-patch").is_ok());
+
+    // Conflicts are checked in both directions
+    let result = addons::check_install(&catalog, &installed(&[("classic-hud", "1.0.0")]), "minimal-hud");
+    assert!(matches!(result, Err(AddonError::Incompatible { installed, .. }) if installed == vec!["Classic HUD"]));
+    let result = addons::check_install(&catalog, &installed(&[("minimal-hud", "2.0.0")]), "classic-hud");
+    assert!(matches!(result, Err(AddonError::Incompatible { installed, .. }) if installed == vec!["Minimal HUD"]));
+
+    let both = installed(&[("hd-models", "1.2.0"), ("hd-models-patch", "1.0.0")]);
+    assert!(matches!(addons::check_remove(&catalog, &both, "hd-models"), Err(AddonError::RequiredBy { .. })));
+    assert!(addons::check_remove(&catalog, &both, "hd-models-patch").is_ok());
+}
+
+#[test]
+fn installs_into_mo2_and_removes_again() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("profiles").join("Default")).unwrap();
+    fs::create_dir_all(dir.path().join("mods").join("My Mod")).unwrap();
+    let list_path = modlist::modlist_path(&dir.path().join("profiles").join("Default"));
+    fs::write(&list_path, "+My Mod\n").unwrap();
+    let instance = Mo2Instance::parse(dir.path(), "");
+
+    // The archive wraps everything in a folder, which is stripped
+    let archive = dir.path().join("hd-models.zip");
+    write_zip(&archive, &[("HD Models v1.2/gamedata/meshes/wpn_ak74.ogf", "mesh"), ("HD Models v1.2/readme.txt", "hi")]);
+    let catalog = catalog();
+    let mut installed = InstalledAddons::default();
+    let folder = addons::install(&instance, Some("Default"), &catalog[0], &archive, &mut installed).unwrap();
+
+    assert_eq!(folder, dir.path().join("mods").join("HD Models"));
+    assert!(folder.join("gamedata").join("meshes").join("wpn_ak74.ogf").is_file());
+    assert_eq!(InstalledAddons::load(dir.path()).unwrap(), installed);
+    let list = ModList::load(&list_path).unwrap();
+    assert_eq!(list.entries[0].name, "HD Models");
+    assert_eq!(list.entries[0].state, ModState::Enabled);
+
+    addons::remove(&instance, "hd-models", &mut installed).unwrap();
+    assert!(!folder.exists());
+    assert!(dir.path().join("mods").join("My Mod").exists());
+    assert!(InstalledAddons::load(dir.path()).unwrap().addons.is_empty());
+    assert!(ModList::load(&list_path).unwrap().get("HD Models").is_none());
+}
@@ -0,0 +1,123 @@
+use std::{
+    env, fs,
+    io::{Read, Write},
+    net::TcpListener,
+    path::Path,
+    process::{Command, Output},
+    thread,
+};
+
+use anomaly_launcher::{config_location::PORTABLE_MARKER, layers::ENV_PREFIX, saves, Manifest};
+
+/// A portable game folder, so the config stays in it instead of the user's profile.
+fn game_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(PORTABLE_MARKER), "").unwrap();
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_AnomalyLauncher"));
+    command.args(args).current_dir(dir);
+    for (name, _) in env::vars().filter(|(name, _)| name.starts_with(ENV_PREFIX)) {
+        command.env_remove(name);
+    }
+    command.output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim_end().to_string()
+}
+
+/// Serves `body` to a single request and returns its URL.
+fn serve_once(body: String) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/manifest.toml", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 4096];
+        let _ = stream.read(&mut request);
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    url
+}
+
+#[test]
+fn config_set_is_read_back_by_config_get() {
+    let dir = game_dir();
+    let set = run(dir.path(), &["config", "set", "renderer", "DX11"]);
+    assert!(set.status.success(), "{}", String::from_utf8_lossy(&set.stderr));
+
+    let get = run(dir.path(), &["config", "get", "renderer"]);
+    assert_eq!(get.status.code(), Some(0));
+    assert_eq!(stdout(&get), "DX11");
+}
+
+#[test]
+fn unknown_keys_exit_with_a_config_error() {
+    let dir = game_dir();
+    let set = run(dir.path(), &["config", "set", "no_such_key", "1"]);
+    assert_eq!(set.status.code(), Some(3));
+    assert!(!dir.path().join("launcherconfig.toml").exists());
+
+    let get = run(dir.path(), &["config", "get", "no_such_key"]);
+    assert_eq!(get.status.code(), Some(3));
+
+    let json = run(dir.path(), &["--json", "config", "get", "no_such_key"]);
+    let data: serde_json::Value = serde_json::from_str(&stdout(&json)).unwrap();
+    assert_eq!(data["ok"], false);
+    assert_eq!(data["exit_code"], 3);
+}
+
+#[test]
+fn set_overrides_are_not_saved() {
+    let dir = game_dir();
+    run(dir.path(), &["config", "set", "renderer", "DX9"]);
+
+    let overridden = run(dir.path(), &["--set", "renderer=DX8", "config", "get", "renderer"]);
+    assert_eq!(stdout(&overridden), "DX8");
+    // Changing another key must not write the override along with it
+    let set = run(dir.path(), &["--set", "renderer=DX8", "config", "set", "use_avx", "false"]);
+    assert!(set.status.success(), "{}", String::from_utf8_lossy(&set.stderr));
+
+    assert_eq!(stdout(&run(dir.path(), &["config", "get", "renderer"])), "DX9");
+    let file = fs::read_to_string(dir.path().join("launcherconfig.toml")).unwrap();
+    assert!(!file.contains("DX8"));
+}
+
+#[test]
+fn update_stops_before_breaking_saves_without_allow_save_break() {
+    let dir = game_dir();
+    let save_dir = saves::saves_dir(dir.path());
+    fs::create_dir_all(&save_dir).unwrap();
+    fs::write(save_dir.join(format!("quicksave.{}", saves::SAVE_EXTENSION)), "save").unwrap();
+
+    let manifest = Manifest::parse("[[patch]]\nversion = \"1.1.0\"\nurl = \"patch-1.1.0.zip\"\nbreaks_saves = true\n").unwrap();
+    let url = serve_once(toml::to_string(&manifest).unwrap());
+    run(dir.path(), &["config", "set", "version", "1.0.0"]);
+    run(dir.path(), &["config", "set", "update_url", &url]);
+
+    let update = run(dir.path(), &["update"]);
+    assert_eq!(update.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&update.stderr);
+    assert!(stderr.contains("--allow-save-break"), "{}", stderr);
+    assert!(stderr.contains("quicksave"), "{}", stderr);
+    assert!(!dir.path().join("updates").join("patch-1.1.0.zip").exists());
+    assert_eq!(stdout(&run(dir.path(), &["config", "get", "version"])), "1.0.0");
+}
+
+#[test]
+fn configs_from_newer_launchers_are_not_overwritten() {
+    let dir = game_dir();
+    let path = dir.path().join("launcherconfig.toml");
+    let newer = "schema_version = 99\nrenderer = \"DX9\"\nfuture_setting = true\n";
+    fs::write(&path, newer).unwrap();
+
+    // Still readable, but changes are refused
+    assert_eq!(stdout(&run(dir.path(), &["config", "get", "renderer"])), "DX9");
+    let set = run(dir.path(), &["config", "set", "renderer", "DX11"]);
+    assert_eq!(set.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&set.stderr).contains("newer launcher"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+}
@@ -0,0 +1,125 @@
+use std::fs;
+
+use anomaly_launcher::{
+    layers::{self, MODPACK_DEFAULTS_FILE},
+    AppConfigError, Layer, Renderer, RunnerKind,
+};
+
+fn user(content: &str) -> toml::Table {
+    toml::from_str(content).unwrap()
+}
+
+#[test]
+fn later_layers_win() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(MODPACK_DEFAULTS_FILE), "renderer = \"DX9\"\ndebug = false\nupdate_url = \"https://example.com/patches.toml\"\n").unwrap();
+    let env = [
+        ("ANOMALY_LAUNCHER_RUNNER__KIND".to_string(), "Proton".to_string()),
+        ("ANOMALY_LAUNCHER_DEBUG".to_string(), "true".to_string()),
+        ("PATH".to_string(), "/usr/bin".to_string()),
+    ];
+    let cli = [("renderer".to_string(), "DX8".to_string())];
+
+    let (config, layers) = layers::resolve(dir.path(), user("renderer = \"DX11\"\nprefetch_sounds = true\n"), env, &cli).unwrap();
+    assert_eq!(config.launch.renderer, Renderer::DX8);
+    assert!(config.launch.debug);
+    assert!(config.launch.prefetch_sounds);
+    assert_eq!(config.runner.kind, RunnerKind::Proton);
+    assert_eq!(config.update_url.as_deref(), Some("https://example.com/patches.toml"));
+
+    assert_eq!(layers.origin("renderer"), Some(Layer::CommandLine));
+    assert_eq!(layers.origin("debug"), Some(Layer::Environment));
+    assert_eq!(layers.origin("runner.kind"), Some(Layer::Environment));
+    assert_eq!(layers.origin("prefetch_sounds"), Some(Layer::User));
+    assert_eq!(layers.origin("update_url"), Some(Layer::Modpack));
+    assert_eq!(layers.origin("shadow_map"), Some(Layer::Default));
+}
+
+#[test]
+fn only_user_values_are_written_back() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(MODPACK_DEFAULTS_FILE), "renderer = \"DX9\"\n").unwrap();
+    let env = [("ANOMALY_LAUNCHER_DEBUG".to_string(), "false".to_string())];
+    let (mut config, layers) = layers::resolve(dir.path(), user("use_avx = false\n"), env, &[]).unwrap();
+
+    config.launch.shadow_map = anomaly_launcher::ShadowMapSize::Size4096;
+    let written = layers.user_table(&toml::Table::try_from(&config).unwrap());
+    assert_eq!(written.get("use_avx"), Some(&toml::Value::Boolean(false)));
+    assert_eq!(written.get("shadow_map").and_then(|v| v.as_str()), Some("Size4096"));
+    // Neither the modpack default nor the environment override is copied into the user file
+    assert!(!written.contains_key("renderer"));
+    assert!(!written.contains_key("debug"));
+}
+
+#[test]
+fn invalid_override_names_its_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = [("ANOMALY_LAUNCHER_RENDERER".to_string(), "Vulkan".to_string())];
+    match layers::resolve(dir.path(), toml::Table::new(), env, &[]) {
+        Err(AppConfigError::BadOverride { source, .. }) => assert_eq!(source, "ANOMALY_LAUNCHER_RENDERER"),
+        other => panic!("expected an override error, got {:?}", other.map(|(config, _)| config)),
+    }
+
+    fs::write(dir.path().join(MODPACK_DEFAULTS_FILE), "shadow_map = \"huge\"\n").unwrap();
+    let err = layers::resolve(dir.path(), toml::Table::new(), [], &[]).unwrap_err();
+    assert!(err.to_string().contains(MODPACK_DEFAULTS_FILE));
+}
+
+#[test]
+fn set_value_rejects_unknown_keys() {
+    let mut config = anomaly_launcher::AppConfig::default();
+    assert!(matches!(config.set_value("renderr", "DX11"), Err(AppConfigError::BadOverride { .. })));
+    assert!(config.set_value("runner.kind.name", "Wine").is_err());
+
+    config.set_value("renderer", "DX11").unwrap();
+    config.set_value("update_url", "https://example.com/patches.toml").unwrap();
+    config.set_value("runner.env.DXVK_HUD", "fps").unwrap();
+    assert_eq!(config.launch.renderer, Renderer::DX11);
+    assert_eq!(config.runner.env.get("DXVK_HUD").map(String::as_str), Some("fps"));
+}
+
+#[test]
+fn set_value_only_selects_existing_profiles() {
+    let mut config = anomaly_launcher::AppConfig::default();
+    config.create_profile("Debugging").unwrap();
+    assert!(matches!(config.set_value("profile", "Debuging"), Err(AppConfigError::BadOverride { .. })));
+    assert_eq!(config.profile, None);
+
+    config.set_value("profile", "Debugging").unwrap();
+    assert_eq!(config.profile_name(), "Debugging");
+    config.set_value("profile", "Default").unwrap();
+    assert_eq!(config.profile, None);
+
+    // A stale selection from the file doesn't block other changes
+    config.profile = Some("Removed".to_string());
+    config.set_value("renderer", "DX9").unwrap();
+}
+
+#[test]
+fn misspelled_override_keys_are_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = [("ANOMALY_LAUNCHER_RENDRER".to_string(), "DX9".to_string())];
+    match layers::resolve(dir.path(), toml::Table::new(), env, &[]) {
+        Err(AppConfigError::BadOverride { source, .. }) => assert_eq!(source, "ANOMALY_LAUNCHER_RENDRER"),
+        other => panic!("expected an override error, got {:?}", other.map(|(config, _)| config)),
+    }
+
+    let cli = [("shadowmap".to_string(), "2048".to_string())];
+    match layers::resolve(dir.path(), toml::Table::new(), [], &cli) {
+        Err(AppConfigError::BadOverride { source, .. }) => assert_eq!(source, "--set shadowmap"),
+        other => panic!("expected an override error, got {:?}", other.map(|(config, _)| config)),
+    }
+}
+
+#[test]
+fn env_overrides_keep_the_case_of_named_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = [
+        ("ANOMALY_LAUNCHER_RUNNER__ENV__DXVK_HUD".to_string(), "fps".to_string()),
+        ("ANOMALY_LAUNCHER_PROFILES__Debugging__RENDERER".to_string(), "DX9".to_string()),
+    ];
+    let (config, layers) = layers::resolve(dir.path(), toml::Table::new(), env, &[]).unwrap();
+    assert_eq!(config.runner.env.get("DXVK_HUD").map(String::as_str), Some("fps"));
+    assert_eq!(config.profiles["Debugging"].renderer, Renderer::DX9);
+    assert_eq!(layers.origin("runner.env.DXVK_HUD"), Some(Layer::Environment));
+}
@@ -0,0 +1,50 @@
+use std::fs;
+
+use anomaly_launcher::{
+    app_config::CONFIG_FILE,
+    config_location::{ConfigLocation, PORTABLE_MARKER},
+};
+
+#[test]
+fn installs_get_their_own_config() {
+    let user_dir = tempfile::tempdir().unwrap();
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+
+    let location = ConfigLocation::resolve(first.path(), Some(user_dir.path()));
+    assert!(!location.is_portable());
+    assert!(location.path().starts_with(user_dir.path()));
+    assert_eq!(location.path().file_name().unwrap(), CONFIG_FILE);
+    assert_eq!(location, ConfigLocation::resolve(first.path(), Some(user_dir.path())));
+    assert_ne!(location, ConfigLocation::resolve(second.path(), Some(user_dir.path())));
+}
+
+#[test]
+fn marker_keeps_the_config_in_the_game_folder() {
+    let user_dir = tempfile::tempdir().unwrap();
+    let root = tempfile::tempdir().unwrap();
+    fs::write(root.path().join(PORTABLE_MARKER), "").unwrap();
+
+    let location = ConfigLocation::resolve(root.path(), Some(user_dir.path()));
+    assert!(location.is_portable());
+    assert_eq!(location.path(), root.path().join(CONFIG_FILE));
+    // Without a user config directory there is nowhere else to go
+    fs::remove_file(root.path().join(PORTABLE_MARKER)).unwrap();
+    assert!(ConfigLocation::resolve(root.path(), None).is_portable());
+}
+
+#[test]
+fn legacy_config_is_imported_once() {
+    let user_dir = tempfile::tempdir().unwrap();
+    let root = tempfile::tempdir().unwrap();
+    fs::write(root.path().join(CONFIG_FILE), "renderer = \"DX9\"\n").unwrap();
+
+    let location = ConfigLocation::resolve(root.path(), Some(user_dir.path()));
+    assert!(location.import_legacy(root.path()).unwrap());
+    assert_eq!(fs::read_to_string(location.path()).unwrap(), "renderer = \"DX9\"\n");
+    assert!(root.path().join(CONFIG_FILE).exists());
+
+    fs::write(location.path(), "renderer = \"DX11\"\n").unwrap();
+    assert!(!location.import_legacy(root.path()).unwrap());
+    assert_eq!(fs::read_to_string(location.path()).unwrap(), "renderer = \"DX11\"\n");
+}
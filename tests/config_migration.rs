@@ -0,0 +1,63 @@
+use anomaly_launcher::{migrations::SCHEMA_VERSION, AppConfig, Renderer, ShadowMapSize};
+
+#[test]
+fn legacy_config_is_migrated() {
+    let legacy = "renderer = \"DirectX 11\"\nuse_avx = false\nshadow_map = 4096\ndebug = false\nprefetch_sounds = true\nversion = \"1.2.0\"\n";
+    let (config, migrated_from) = AppConfig::parse(legacy).unwrap();
+    assert_eq!(migrated_from, Some(0));
+    assert_eq!(config.schema_version, SCHEMA_VERSION);
+    assert_eq!(config.launch.renderer, Renderer::DX11);
+    assert_eq!(config.launch.shadow_map, ShadowMapSize::Size4096);
+    assert!(config.launch.prefetch_sounds);
+    assert_eq!(config.version.as_deref(), Some("1.2.0"));
+    // `use_avx = false` started the AVX build before the executable table was fixed
+    assert!(config.launch.use_avx);
+}
+
+#[test]
+fn legacy_use_avx_keeps_starting_the_same_executable() {
+    let (config, _) = AppConfig::parse("renderer = \"DX11\"\nuse_avx = true\n").unwrap();
+    assert!(!config.launch.use_avx);
+
+    // Only files from before schema versioning had the flag inverted
+    let (config, _) = AppConfig::parse("schema_version = 1\nrenderer = \"DX11\"\nuse_avx = true\n").unwrap();
+    assert!(config.launch.use_avx);
+    let text = toml::to_string(&config).unwrap();
+    let (config, _) = AppConfig::parse(&text).unwrap();
+    assert!(config.launch.use_avx);
+}
+
+#[test]
+fn missing_fields_take_defaults() {
+    let (config, _) = AppConfig::parse("schema_version = 1\nrenderer = \"DX9\"\n").unwrap();
+    let defaults = AppConfig::default();
+    assert_eq!(config.launch.renderer, Renderer::DX9);
+    assert_eq!(config.launch.shadow_map, defaults.launch.shadow_map);
+    assert_eq!(config.launch.debug, defaults.launch.debug);
+    assert_eq!(config.version, None);
+}
+
+#[test]
+fn current_config_is_not_migrated() {
+    let text = toml::to_string(&AppConfig::default()).unwrap();
+    assert!(text.starts_with(&format!("schema_version = {}", SCHEMA_VERSION)));
+    let (_, migrated_from) = AppConfig::parse(&text).unwrap();
+    assert_eq!(migrated_from, None);
+}
+
+#[test]
+fn invalid_values_are_still_rejected() {
+    assert!(AppConfig::parse("renderer = \"Vulkan\"\n").is_err());
+    assert!(AppConfig::parse("renderer = \n").is_err());
+}
+
+#[test]
+fn errors_point_at_the_offending_line() {
+    let err = AppConfig::parse("schema_version = 1\nrenderer = \"DX11\"\nshadow_map = \"Size9999\"\n").unwrap_err();
+    let text = err.to_string();
+    assert!(text.contains("line 3, column 14"), "{}", text);
+    assert!(text.contains("Size9999"), "{}", text);
+
+    let err = AppConfig::parse("schema_version = 1\ndebug = tru\n").unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{}", err);
+}
@@ -0,0 +1,36 @@
+//! `AppConfig::load` and `write` work on the current directory, so this file
+//! keeps to a single test that owns it.
+
+use std::{env, fs};
+
+use anomaly_launcher::{config_location::PORTABLE_MARKER, AppConfig, Renderer};
+
+#[test]
+fn damaged_config_falls_back_to_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    env::set_current_dir(dir.path()).unwrap();
+    fs::write(dir.path().join(PORTABLE_MARKER), "").unwrap();
+
+    let mut config = AppConfig::default();
+    config.launch.renderer = Renderer::DX9;
+    config.write().unwrap();
+    assert!(!dir.path().join("launcherconfig.toml.bak").exists());
+
+    config.launch.renderer = Renderer::DX11;
+    config.write().unwrap();
+    let backup = fs::read_to_string(dir.path().join("launcherconfig.toml.bak")).unwrap();
+    assert!(backup.contains("renderer = \"DX9\""));
+    assert!(!dir.path().join("launcherconfig.toml.tmp").exists());
+
+    // A truncated write must neither load nor replace the good backup
+    fs::write(dir.path().join("launcherconfig.toml"), "renderer = \"DX").unwrap();
+    let loaded = AppConfig::load().unwrap();
+    assert_eq!(loaded.launch.renderer, Renderer::DX9);
+    assert!(dir.path().join("launcherconfig.toml.broken").exists());
+    assert!(AppConfig::parse(&fs::read_to_string(dir.path().join("launcherconfig.toml")).unwrap()).is_ok());
+
+    // Without a usable backup the error is reported
+    fs::write(dir.path().join("launcherconfig.toml.bak"), "debug = ").unwrap();
+    fs::write(dir.path().join("launcherconfig.toml"), "debug = ").unwrap();
+    assert!(AppConfig::load().is_err());
+}
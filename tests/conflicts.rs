@@ -0,0 +1,52 @@
+use std::{fs, path::Path};
+
+use anomaly_launcher::conflicts::{self, ModSource};
+
+fn add_file(mods: &Path, name: &str, path: &str, content: &str) {
+    let file = mods.join(name).join("gamedata").join(path);
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(file, content).unwrap();
+}
+
+#[test]
+fn reports_overwritten_files_with_the_winner() {
+    let dir = tempfile::tempdir().unwrap();
+    add_file(dir.path(), "010-textures", "textures/wpn/ak74.dds", "a");
+    add_file(dir.path(), "020-weapons", "textures/wpn/AK74.dds", "b");
+    add_file(dir.path(), "020-weapons", "configs/items/weapons/w_ak74.ltx", "[wpn_ak74]");
+    add_file(dir.path(), "030-patch", "textures/wpn/ak74.dds", "c");
+
+    let sources = conflicts::folder_sources(dir.path());
+    assert_eq!(sources.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["010-textures", "020-weapons", "030-patch"]);
+
+    let report = conflicts::scan(&sources).unwrap();
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(report.files[0].mods, vec!["010-textures", "020-weapons", "030-patch"]);
+    assert_eq!(report.files[0].winner(), "030-patch");
+    assert!(report.sections.is_empty());
+}
+
+#[test]
+fn reports_dltx_sections_patched_by_several_mods() {
+    let dir = tempfile::tempdir().unwrap();
+    add_file(dir.path(), "Recoil", "configs/items/weapons/mod_system_recoil.ltx", "![wpn_ak74]\ncam_dispersion = 0.5\n\n![wpn_abakan] ; tweak\n");
+    add_file(dir.path(), "Ammo", "configs/items/weapons/mod_system_ammo.ltx", "![wpn_ak74]\nammo_mag_size = 45\n@[ammo_new]:ammo_base\n");
+
+    let sources: Vec<ModSource> = ["Recoil", "Ammo"]
+        .iter()
+        .map(|name| ModSource { name: name.to_string(), gamedata: dir.path().join(name).join("gamedata") })
+        .collect();
+    let report = conflicts::scan(&sources).unwrap();
+    assert!(report.files.is_empty());
+    assert_eq!(report.sections.len(), 1);
+    assert_eq!(report.sections[0].section, "wpn_ak74");
+    // mod_system_recoil.ltx sorts after mod_system_ammo.ltx, so it is applied last
+    assert_eq!(report.sections[0].winner(), "Recoil");
+    assert!(report.to_string().contains("[wpn_ak74]"));
+}
+
+#[test]
+fn parses_dltx_section_headers() {
+    let sections = conflicts::dltx_sections("[new]\n![changed]:base\n@[either]\n!![removed]\n; [commented]\nkey = [not a section\n");
+    assert_eq!(sections, vec!["new", "changed", "either", "removed"]);
+}
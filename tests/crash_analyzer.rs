@@ -0,0 +1,91 @@
+use std::fs;
+
+use anomaly_launcher::{crash_analyzer, CrashRules};
+
+const SCRIPT_CRASH: &str = r"* [x-ray]: economy: strings[10240 K], smem[62312 K]
+! [LUA] ...y\gamedata\scripts\grok_actor_damage_balancer.script:148: attempt to index local 'wpn' (a nil value)
+stack traceback:
+	...y\gamedata\scripts\grok_actor_damage_balancer.script:148: in function 'actor_on_before_hit'
+	...anomaly\gamedata\scripts\_g.script:2102: in function <...anomaly\gamedata\scripts\_g.script:2099>
+
+FATAL ERROR
+ 
+[error]Expression    : <no expression>
+[error]Function      : CScriptEngine::lua_error
+[error]File          : D:\Anomaly\src\xrServerEntities\script_engine.cpp
+[error]Line          : 73
+[error]Description   : fatal error
+[error]Arguments     : LUA error: ...y\gamedata\scripts\grok_actor_damage_balancer.script:148: attempt to index local 'wpn' (a nil value)
+ 
+
+stack trace:
+
+0023:00A2F1C4 xrCore.dll, xrDebug::fatal()
+";
+
+const TEXTURE_CRASH: &str = r"FATAL ERROR
+ 
+[error]Expression    : fatal error
+[error]Function      : CResourceManager::_CreateTexture
+[error]File          : D:\Anomaly\src\Layers\xrRender\ResourceManager.cpp
+[error]Line          : 412
+[error]Description   : Can't find texture 'wpn\wpn_ak74_hud'
+";
+
+#[test]
+fn parses_error_fields_and_script_stack() {
+    let report = crash_analyzer::parse_log(SCRIPT_CRASH).unwrap();
+    assert_eq!(report.function.as_deref(), Some("CScriptEngine::lua_error"));
+    assert_eq!(report.file.as_deref(), Some(r"D:\Anomaly\src\xrServerEntities\script_engine.cpp"));
+    assert_eq!(report.line, Some(73));
+    assert_eq!(report.description.as_deref(), Some("fatal error"));
+    assert_eq!(report.script, Some(("grok_actor_damage_balancer.script".to_string(), 148)));
+    assert_eq!(report.script_stack.len(), 2);
+    assert!(report.script_stack[0].ends_with("in function 'actor_on_before_hit'"));
+}
+
+#[test]
+fn log_without_fatal_error_has_no_report() {
+    assert_eq!(crash_analyzer::parse_log("* [x-ray]: loading\n* phase time: 12 ms\n"), None);
+}
+
+#[test]
+fn builtin_rules_explain_common_crashes() {
+    let rules = CrashRules::builtin();
+
+    let script = rules.diagnose(&crash_analyzer::parse_log(SCRIPT_CRASH).unwrap()).unwrap();
+    assert_eq!(script.rule, "Script error");
+    assert!(script.cause.contains("grok_actor_damage_balancer.script at line 148"));
+
+    let texture = rules.diagnose(&crash_analyzer::parse_log(TEXTURE_CRASH).unwrap()).unwrap();
+    assert_eq!(texture.rule, "Missing texture");
+    assert!(texture.cause.contains(r"wpn\wpn_ak74_hud"));
+}
+
+#[test]
+fn modpack_rules_take_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join(crash_analyzer::CRASH_RULES_FILE),
+        r#"
+[[rule]]
+name = "Damage balancer"
+field = "stack"
+pattern = 'grok_actor_damage_balancer\.script:(\d+)'
+cause = "Known bug in the damage balancer (line $1)"
+fix = "Update to 1.2.1"
+"#,
+    )
+    .unwrap();
+
+    let rules = CrashRules::load(dir.path()).unwrap();
+    let diagnosis = rules.diagnose(&crash_analyzer::parse_log(SCRIPT_CRASH).unwrap()).unwrap();
+    assert_eq!(diagnosis.rule, "Damage balancer");
+    assert_eq!(diagnosis.cause, "Known bug in the damage balancer (line 148)");
+}
+
+#[test]
+fn invalid_pattern_is_reported() {
+    let err = CrashRules::parse("[[rule]]\nname = \"Broken\"\npattern = '('\ncause = \"\"\n").err().unwrap();
+    assert!(err.to_string().contains("Broken"));
+}
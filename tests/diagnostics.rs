@@ -0,0 +1,76 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use anomaly_launcher::{
+    config_location::PORTABLE_MARKER,
+    diagnostics::{self, DIAGNOSTICS_DIR},
+    mo2::MO2_EXECUTABLE,
+    AppConfig,
+};
+use zip::ZipArchive;
+
+fn write_aged(path: &Path, content: &str, age_secs: u64) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+    let file = File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(age_secs)).unwrap();
+}
+
+#[test]
+fn fatal_error_section_without_a_stack_trace() {
+    let lines = ["* Loading", "FATAL ERROR", "[error]Expression : fatal error", "[error]Function : CScriptEngine::lua_error"];
+    assert_eq!(diagnostics::fatal_error_section(&lines), Some(1..4));
+    assert_eq!(diagnostics::fatal_error_section(&["* Loading", "* Done"]), None);
+}
+
+#[test]
+fn fatal_error_section_ends_after_the_stack_trace_frames() {
+    let lines = [
+        "FATAL ERROR",
+        "[error]Expression : !m_error_code",
+        "stack trace:",
+        "",
+        "0023:00A4D2B1 xrCore.dll",
+        "0023:00A4D2B2 xrGame.dll",
+        "",
+        "* [x-ray]: economy: strings[32 KB]",
+    ];
+    assert_eq!(diagnostics::fatal_error_section(&lines), Some(0..6));
+
+    // The log was cut off inside the trace
+    assert_eq!(diagnostics::fatal_error_section(&lines[..5]), Some(0..5));
+}
+
+#[test]
+fn bundle_holds_the_newest_files_and_skips_missing_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join(PORTABLE_MARKER), "").unwrap();
+    let logs = diagnostics::logs_dir(root);
+    for (i, name) in ["oldest", "older", "old", "new", "newest"].iter().enumerate() {
+        write_aged(&logs.join(format!("{}.log", name)), "* Loading", 500 - i as u64 * 100);
+    }
+    write_aged(&logs.join("crash.mdmp"), "dump", 10);
+
+    let mo2 = root.join("MO2");
+    fs::create_dir_all(mo2.join("my mods").join("Dead Reckoning Core")).unwrap();
+    fs::write(mo2.join(MO2_EXECUTABLE), "").unwrap();
+    fs::write(mo2.join("ModOrganizer.ini"), "[Settings]\nmod_directory=%BASE_DIR%/my mods\n").unwrap();
+    fs::create_dir_all(root.join("mods").join("Not Used")).unwrap();
+
+    let bundle = diagnostics::create_bundle(root, &AppConfig::default()).unwrap();
+    assert!(bundle.starts_with(root.join(DIAGNOSTICS_DIR)));
+    let mut archive = ZipArchive::new(File::open(&bundle).unwrap()).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    // No user.ltx, config or session log were ever written
+    assert_eq!(names, ["dumps/crash.mdmp", "logs/new.log", "logs/newest.log", "logs/old.log", "mods.txt", "summary.txt"]);
+
+    let mut mods = String::new();
+    archive.by_name("mods.txt").unwrap().read_to_string(&mut mods).unwrap();
+    assert_eq!(mods, "Dead Reckoning Core");
+}
@@ -0,0 +1,83 @@
+use std::fs;
+
+use anomaly_launcher::{game, ExecutableEntry, Game, Renderer};
+use tempfile::TempDir;
+
+const RENDERERS: [(Renderer, &str); 4] = [
+    (Renderer::DX8, "DX8"),
+    (Renderer::DX9, "DX9"),
+    (Renderer::DX10, "DX10"),
+    (Renderer::DX11, "DX11"),
+];
+
+fn game_dir(files: &[&str]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for file in files {
+        let path = game::join_relative(dir.path(), file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"").unwrap();
+    }
+    dir
+}
+
+#[test]
+fn default_table_covers_every_renderer_and_avx_combination() {
+    for (renderer, name) in RENDERERS {
+        assert_eq!(game::executable_path(&[], renderer, false), format!("bin/Anomaly{}.exe", name));
+        assert_eq!(game::executable_path(&[], renderer, true), format!("bin/Anomaly{}AVX.exe", name));
+    }
+}
+
+#[test]
+fn resolves_installed_executables_for_every_combination() {
+    let all: Vec<String> = RENDERERS
+        .iter()
+        .flat_map(|(_, name)| [format!("bin/Anomaly{}.exe", name), format!("bin/Anomaly{}AVX.exe", name)])
+        .collect();
+    let dir = game_dir(&all.iter().map(String::as_str).collect::<Vec<_>>());
+
+    for (renderer, name) in RENDERERS {
+        let regular = Game::resolve(dir.path(), &[], renderer, false);
+        assert_eq!(regular.path(), dir.path().join("bin").join(format!("Anomaly{}.exe", name)));
+        let avx = Game::resolve(dir.path(), &[], renderer, true);
+        assert_eq!(avx.path(), dir.path().join("bin").join(format!("Anomaly{}AVX.exe", name)));
+    }
+}
+
+#[test]
+fn falls_back_to_regular_build_when_avx_build_is_missing() {
+    let dir = game_dir(&["bin/AnomalyDX11.exe"]);
+    let game = Game::resolve(dir.path(), &[], Renderer::DX11, true);
+    assert_eq!(game.path(), dir.path().join("bin").join("AnomalyDX11.exe"));
+}
+
+#[test]
+fn keeps_avx_path_when_no_build_is_installed() {
+    let dir = game_dir(&[]);
+    let game = Game::resolve(dir.path(), &[], Renderer::DX9, true);
+    assert_eq!(game.path(), dir.path().join("bin").join("AnomalyDX9AVX.exe"));
+    assert!(!game.path().exists());
+}
+
+#[test]
+fn config_overrides_replace_only_matching_entries() {
+    let overrides = vec![ExecutableEntry {
+        renderer: Renderer::DX11,
+        avx: true,
+        path: "bin\\custom\\DeadReckoningDX11AVX.exe".to_string(),
+    }];
+    let dir = game_dir(&["bin/custom/DeadReckoningDX11AVX.exe", "bin/AnomalyDX10AVX.exe"]);
+
+    let custom = Game::resolve(dir.path(), &overrides, Renderer::DX11, true);
+    assert_eq!(custom.path(), dir.path().join("bin").join("custom").join("DeadReckoningDX11AVX.exe"));
+
+    let untouched = Game::resolve(dir.path(), &overrides, Renderer::DX10, true);
+    assert_eq!(untouched.path(), dir.path().join("bin").join("AnomalyDX10AVX.exe"));
+}
+
+#[test]
+fn joins_windows_separators_portably() {
+    let root = std::path::Path::new("game");
+    assert_eq!(game::join_relative(root, "bin\\AnomalyDX11.exe"), root.join("bin").join("AnomalyDX11.exe"));
+    assert_eq!(game::join_relative(root, "./bin//AnomalyDX11.exe"), root.join("bin").join("AnomalyDX11.exe"));
+}
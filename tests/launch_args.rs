@@ -0,0 +1,60 @@
+use anomaly_launcher::{launch_args, AppConfig, LaunchFlags, LaunchSettings, ShadowMapSize};
+
+#[test]
+fn split_args_groups_quoted_words() {
+    assert_eq!(
+        launch_args::split_args(r#"-fov 90  -ltx "C:\Games\Anomaly\my user.ltx" -tag 'a b'"#),
+        vec!["-fov", "90", "-ltx", r"C:\Games\Anomaly\my user.ltx", "-tag", "a b"]
+    );
+    assert_eq!(launch_args::split_args(r#"-empty "" -open "rest of line"#), vec!["-empty", "", "-open", "rest of line"]);
+    assert!(launch_args::split_args("   ").is_empty());
+}
+
+#[test]
+fn join_args_quotes_only_when_needed() {
+    let args = ["bin/AnomalyDX11.exe", "-dbg", "C:\\My Games\\user.ltx", ""];
+    assert_eq!(launch_args::join_args(&args), r#"bin/AnomalyDX11.exe -dbg "C:\My Games\user.ltx" """#);
+    assert_eq!(launch_args::split_args(&launch_args::join_args(&args)), args);
+
+    let quoted = ["-tag", r#"say "hi""#, "it's", r"C:\My Games\", r#"a\"b"#];
+    assert_eq!(launch_args::join_args(&quoted), r#"-tag "say \"hi\"" "it's" "C:\My Games\\" "a\\\"b""#);
+    assert_eq!(launch_args::split_args(&launch_args::join_args(&quoted)), quoted);
+}
+
+#[test]
+fn launch_args_include_flags_then_extra_args() {
+    let settings = LaunchSettings {
+        shadow_map: ShadowMapSize::Size4096,
+        debug: false,
+        prefetch_sounds: true,
+        flags: LaunchFlags { nointro: true, gamedata_on: true, ..Default::default() },
+        extra_args: "-custom \"two words\"".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(
+        settings.launch_args(),
+        vec!["-smap4096", "-prefetch_sounds", "-nointro", "-gamedata_on", "-custom", "two words"]
+    );
+}
+
+#[test]
+fn flags_edited_through_list_mut_show_up_in_list_and_args() {
+    let mut flags = LaunchFlags::default();
+    for (arg, _, enabled) in flags.list_mut() {
+        *enabled = arg == "-no_staging" || arg == "-ss_tga";
+    }
+    assert_eq!(flags, LaunchFlags { no_staging: true, ss_tga: true, ..Default::default() });
+    let set: Vec<&str> = flags.list().filter(|(_, _, enabled)| *enabled).map(|(arg, _, _)| arg).collect();
+    assert_eq!(set, vec!["-no_staging", "-ss_tga"]);
+    assert_eq!(flags.args(), vec!["-no_staging", "-ss_tga"]);
+}
+
+#[test]
+fn old_configs_without_flags_still_load() {
+    let config: AppConfig = toml::from_str(
+        "renderer = \"DX11\"\nuse_avx = false\nshadow_map = \"Size2048\"\ndebug = true\nprefetch_sounds = false\n",
+    )
+    .unwrap();
+    assert_eq!(config.launch.flags, LaunchFlags::default());
+    assert_eq!(config.launch_args(), vec!["-smap2048", "-dbg"]);
+}
@@ -0,0 +1,75 @@
+use anomaly_launcher::{manifest, Manifest, ManifestPatch};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::SigningKey;
+use semver::Version;
+
+#[test]
+fn relative_urls_resolve_against_the_manifest() {
+    let content = "[[patch]]\nversion = \"1.1.0\"\nurl = \"patch-1.1.0.zip\"\n\n[[patch]]\nversion = \"1.2.0\"\nurl = \"https://cdn.example.com/patch-1.2.0.zip\"\n";
+    let mut manifest = Manifest::parse(content).unwrap();
+    manifest.resolve_urls("https://example.com/dead-reckoning/manifest.toml");
+    assert_eq!(manifest.patches[0].url, "https://example.com/dead-reckoning/patch-1.1.0.zip");
+    assert_eq!(manifest.patches[1].url, "https://cdn.example.com/patch-1.2.0.zip");
+
+    let mut legacy = Manifest::parse("patch-1.1.0.zip\n").unwrap();
+    legacy.resolve_urls("https://example.com/updates.txt");
+    assert_eq!(legacy.patches[0].url, "https://example.com/patch-1.1.0.zip");
+}
+
+#[test]
+fn a_broken_toml_manifest_is_an_error_not_an_empty_url_list() {
+    // `sha256` without quotes
+    let typo = "[[patch]]\nversion = \"1.1.0\"\nurl = \"patch-1.1.0.zip\"\nsha256 = 9f86d081\n";
+    assert!(Manifest::parse(typo).is_err());
+    assert!(Manifest::parse("[[ addon ]]\nid = \"hud\"\n").is_err());
+
+    let legacy = Manifest::parse("# Dead Reckoning\nhttps://example.com/patch-1.1.0.zip\n").unwrap();
+    assert_eq!(legacy.patches[0].version, Version::new(1, 1, 0));
+    assert!(Manifest::parse("").unwrap().patches.is_empty());
+}
+
+#[test]
+fn upsert_replaces_the_version_and_keeps_the_list_sorted() {
+    let patch = |version: &str, url: &str| ManifestPatch {
+        version: Version::parse(version).unwrap(),
+        url: url.to_string(),
+        sha256: None,
+        signature: None,
+        breaks_saves: false,
+    };
+    let mut manifest = Manifest::default();
+    manifest.upsert(patch("1.2.0", "patch-1.2.0.zip"));
+    manifest.upsert(patch("1.1.0", "patch-1.1.0.zip"));
+    manifest.upsert(ManifestPatch { breaks_saves: true, ..patch("1.2.0", "rebuilt/patch-1.2.0.zip") });
+
+    let versions: Vec<String> = manifest.patches.iter().map(|patch| patch.version.to_string()).collect();
+    assert_eq!(versions, ["1.1.0", "1.2.0"]);
+    assert_eq!(manifest.patches[1].url, "rebuilt/patch-1.2.0.zip");
+
+    let reparsed = Manifest::parse(&manifest.to_toml().unwrap()).unwrap();
+    assert!(reparsed.patches[1].breaks_saves);
+    assert!(!reparsed.patches[0].breaks_saves);
+}
+
+#[test]
+fn signatures_verify_only_with_the_matching_digest_and_key() {
+    let secret = SigningKey::from_bytes(&[7; 32]);
+    let public = BASE64.encode(secret.verifying_key().to_bytes());
+    let other = BASE64.encode(SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes());
+    let digest = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    let signature = manifest::sign_digest(&BASE64.encode(secret.to_bytes()), digest).unwrap();
+    manifest::verify_digest(&public, digest, &signature).unwrap();
+    assert!(manifest::verify_digest(&other, digest, &signature).is_err());
+    assert!(manifest::verify_digest(&public, &digest.replace('9', "8"), &signature).is_err());
+    assert!(manifest::verify_digest(&public, digest, "not base64").is_err());
+}
+
+#[test]
+fn addons_with_unsafe_ids_are_dropped() {
+    let addon = |id: &str| format!("[[addon]]\nid = '{}'\nname = \"Addon\"\nversion = \"1.0.0\"\nurl = \"addon.zip\"\n\n", id);
+    let content: String = ["hd-models", "../../bin", "..", "mods/x", "..\\..\\bin", "C:evil", ""].iter().map(|id| addon(id)).collect();
+    let manifest = Manifest::parse(&content).unwrap();
+    let ids: Vec<&str> = manifest.addons.iter().map(|addon| addon.id.as_str()).collect();
+    assert_eq!(ids, ["hd-models"]);
+}
@@ -0,0 +1,74 @@
+use std::fs;
+
+use anomaly_launcher::{mo2::MO2_EXECUTABLE, Game, Mo2Config, Mo2Instance, Renderer, RunnerConfig, RunnerKind};
+
+const INI: &str = r#"[General]
+gameName=Stalker Anomaly
+selected_profile=@ByteArray(Dead Reckoning)
+
+[customExecutables]
+1\arguments=
+1\binary=C:/Games/Anomaly/bin/AnomalyDX11AVX.exe
+1\title=Anomaly (DX11-AVX)
+2\arguments="-nointro -dbg"
+2\binary=C:/Games/Anomaly/bin/AnomalyDX9.exe
+2\title=Anomaly (DX9)
+size=2
+
+[Settings]
+mod_directory=%BASE_DIR%/mods
+"#;
+
+fn instance_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let mo2 = dir.path().join("MO2");
+    fs::create_dir_all(mo2.join("profiles").join("Dead Reckoning")).unwrap();
+    fs::create_dir_all(mo2.join("profiles").join("Default")).unwrap();
+    fs::write(mo2.join(MO2_EXECUTABLE), "").unwrap();
+    fs::write(mo2.join("ModOrganizer.ini"), INI).unwrap();
+    dir
+}
+
+#[test]
+fn parses_mod_organizer_ini() {
+    let dir = tempfile::tempdir().unwrap();
+    let instance = Mo2Instance::parse(dir.path(), INI);
+    assert_eq!(instance.game_name.as_deref(), Some("Stalker Anomaly"));
+    assert_eq!(instance.selected_profile.as_deref(), Some("Dead Reckoning"));
+    assert_eq!(instance.mods_dir, dir.path().join("mods"));
+    assert_eq!(instance.profiles_dir, dir.path().join("profiles"));
+    assert_eq!(instance.executables.len(), 2);
+    assert_eq!(instance.executables[1].title, "Anomaly (DX9)");
+    assert_eq!(instance.executables[1].arguments, "-nointro -dbg");
+}
+
+#[test]
+fn detects_instance_next_to_the_game() {
+    let root = instance_dir();
+    let instance = Mo2Instance::detect(root.path(), &Mo2Config::default()).unwrap();
+    assert_eq!(instance.dir, root.path().join("MO2"));
+    assert_eq!(instance.profiles(), vec!["Dead Reckoning", "Default"]);
+    assert_eq!(instance.resolve_profile(Some("Default")).as_deref(), Some("Default"));
+    assert_eq!(instance.resolve_profile(Some("Missing")).as_deref(), Some("Dead Reckoning"));
+
+    let elsewhere = tempfile::tempdir().unwrap();
+    assert!(Mo2Instance::detect(elsewhere.path(), &Mo2Config::default()).is_none());
+}
+
+#[test]
+fn launches_through_mod_organizer() {
+    let root = instance_dir();
+    let instance = Mo2Instance::detect(root.path(), &Mo2Config::default()).unwrap();
+    let native = RunnerConfig { kind: RunnerKind::Native, ..Default::default() };
+    let game = Game::resolve(root.path(), &[], Renderer::DX11, false).with_runner(native).with_mo2(&instance, None);
+    assert_eq!(game.mo2_profile(), Some("Dead Reckoning"));
+
+    let args = vec!["-smap2048".to_string(), "-ltx".to_string(), "my user.ltx".to_string()];
+    let command = game.command(&args);
+    assert_eq!(command.get_program(), instance.executable());
+    let command_args: Vec<String> = command.get_args().map(|arg| arg.to_string_lossy().to_string()).collect();
+    assert_eq!(
+        command_args,
+        vec!["-p", "Dead Reckoning", "run", "-a", "-smap2048 -ltx \"my user.ltx\"", &game.path().to_string_lossy()]
+    );
+}
@@ -0,0 +1,61 @@
+use std::fs;
+
+use anomaly_launcher::{modlist, Deviation, ModList, ModState};
+
+const REFERENCE: &str = "# This file was automatically generated by Mod Organizer.\r\n+Dead Reckoning Patch\r\n+Weapons Overhaul\r\n+Better Textures\r\n-Optional Reshade\r\n*DLC: Base\r\n";
+
+#[test]
+fn parses_mod_states_in_priority_order() {
+    let list = ModList::parse(REFERENCE);
+    assert_eq!(list.entries.len(), 5);
+    assert_eq!(list.entries[0].name, "Dead Reckoning Patch");
+    assert_eq!(list.get("Optional Reshade").unwrap().state, ModState::Disabled);
+    assert_eq!(list.get("DLC: Base").unwrap().state, ModState::Unmanaged);
+
+    // MO2 shows the last line as priority 0
+    let (priority, entry) = list.by_priority().next().unwrap();
+    assert_eq!((priority, entry.name.as_str()), (0, "DLC: Base"));
+    assert_eq!(list.to_string(), REFERENCE);
+}
+
+#[test]
+fn matching_list_has_no_deviations() {
+    let reference = ModList::parse(REFERENCE);
+    assert!(modlist::compare(&reference, &reference).is_empty());
+}
+
+#[test]
+fn reports_deviations_from_reference() {
+    let reference = ModList::parse(REFERENCE);
+    let active = ModList::parse("+Better Textures\n+Dead Reckoning Patch\n+My Tweaks\n-Weapons Overhaul\n");
+    let deviations = modlist::compare(&active, &reference);
+    assert!(deviations.contains(&Deviation::Disabled("Weapons Overhaul".to_string())));
+    assert!(deviations.contains(&Deviation::Enabled("My Tweaks".to_string())));
+    assert!(deviations.contains(&Deviation::Moved("Better Textures".to_string())) || deviations.contains(&Deviation::Moved("Dead Reckoning Patch".to_string())));
+    assert!(!deviations.iter().any(|d| d.name() == "Optional Reshade"));
+
+    let missing = ModList::parse("+Dead Reckoning Patch\n+Better Textures\n");
+    assert_eq!(modlist::compare(&missing, &reference), vec![Deviation::Missing("Weapons Overhaul".to_string())]);
+}
+
+#[test]
+fn restore_keeps_added_mods_disabled() {
+    let reference = ModList::parse(REFERENCE);
+    let active = ModList::parse("+My Tweaks\n-Dead Reckoning Patch\n");
+    let restored = modlist::restore(&active, &reference);
+    assert!(modlist::compare(&restored, &reference).is_empty());
+    assert_eq!(restored.entries.last().unwrap().name, "My Tweaks");
+    assert_eq!(restored.entries.last().unwrap().state, ModState::Disabled);
+}
+
+#[test]
+fn save_keeps_the_original_list_as_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("modlist.txt");
+    fs::write(&path, "+My Tweaks\n").unwrap();
+
+    ModList::parse(REFERENCE).save(&path).unwrap();
+    ModList::parse("+Other\n").save(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), ModList::parse("+Other\n").to_string());
+    assert_eq!(fs::read_to_string(dir.path().join("modlist.txt.bak")).unwrap(), "+My Tweaks\n");
+}
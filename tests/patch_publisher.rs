@@ -0,0 +1,115 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+    process::{Command, Output},
+};
+
+use anomaly_launcher::{
+    manifest::{self, PatchContents, PATCH_INFO_FILE},
+    Manifest,
+};
+use zip::ZipArchive;
+
+fn publisher(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_patch-publisher")).args(args).output().unwrap()
+}
+
+fn write(root: &Path, name: &str, content: &str) {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn keygen_writes_an_owner_only_key_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = dir.path().join("publisher.key");
+    let key_arg = key.to_str().unwrap();
+    assert!(publisher(&["keygen", "--out", key_arg]).status.success());
+    let secret = fs::read_to_string(&key).unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&key).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    let again = publisher(&["keygen", "--out", key_arg]);
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already exists"));
+    assert_eq!(fs::read_to_string(&key).unwrap(), secret);
+}
+
+#[test]
+fn build_packs_changed_files_and_signs_the_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old, new, dist) = (dir.path().join("old"), dir.path().join("new"), dir.path().join("dist"));
+    write(&old, "gamedata/configs/system.ltx", "old settings");
+    write(&old, "gamedata/scripts/same.script", "unchanged");
+    write(&old, "gamedata/scripts/removed.script", "gone");
+    write(&new, "gamedata/configs/system.ltx", "new settings");
+    write(&new, "gamedata/scripts/same.script", "unchanged");
+    write(&new, "gamedata/textures/new.dds", "added");
+
+    let key = dir.path().join("publisher.key");
+    let keygen = publisher(&["keygen", "--out", key.to_str().unwrap()]);
+    let stdout = String::from_utf8_lossy(&keygen.stdout);
+    let public_key = stdout.lines().last().unwrap().trim().to_string();
+
+    let manifest_path = dist.join("manifest.toml");
+    let build = publisher(&[
+        "build",
+        "--old", old.to_str().unwrap(),
+        "--new", new.to_str().unwrap(),
+        "--version", "1.1.0",
+        "--key", key.to_str().unwrap(),
+        "--out", dist.to_str().unwrap(),
+        "--manifest", manifest_path.to_str().unwrap(),
+    ]);
+    assert!(build.status.success(), "{}", String::from_utf8_lossy(&build.stderr));
+
+    let archive_path = dist.join("patch-1.1.0.zip");
+    let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(names, [PATCH_INFO_FILE, "gamedata/configs/system.ltx", "gamedata/textures/new.dds"]);
+
+    let mut info = String::new();
+    archive.by_name(PATCH_INFO_FILE).unwrap().read_to_string(&mut info).unwrap();
+    let contents: PatchContents = toml::from_str(&info).unwrap();
+    assert_eq!(contents.deleted, ["gamedata/scripts/removed.script"]);
+    for (name, hash) in &contents.files {
+        assert_eq!(hash, &manifest::sha256_file(&new.join(name)).unwrap());
+    }
+
+    let published = Manifest::parse(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+    let patch = &published.patches[0];
+    assert_eq!(patch.url, "patch-1.1.0.zip");
+    assert_eq!(patch.sha256.as_deref(), Some(manifest::sha256_file(&archive_path).unwrap().as_str()));
+    manifest::verify_digest(&public_key, patch.sha256.as_deref().unwrap(), patch.signature.as_deref().unwrap()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn build_rejects_symlinks_instead_of_following_them() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old, new) = (dir.path().join("old"), dir.path().join("new"));
+    write(&old, "gamedata/configs/system.ltx", "old settings");
+    write(&new, "gamedata/configs/system.ltx", "new settings");
+    // Would recurse forever if followed
+    std::os::unix::fs::symlink(&new, new.join("gamedata").join("loop")).unwrap();
+
+    let dist = dir.path().join("dist");
+    let build = publisher(&[
+        "build",
+        "--old", old.to_str().unwrap(),
+        "--new", new.to_str().unwrap(),
+        "--version", "1.1.0",
+        "--out", dist.to_str().unwrap(),
+        "--manifest", dist.join("manifest.toml").to_str().unwrap(),
+    ]);
+    assert!(!build.status.success());
+    assert!(String::from_utf8_lossy(&build.stderr).contains("symbolic link"));
+    assert!(!dist.join("manifest.toml").exists());
+}
@@ -0,0 +1,58 @@
+use anomaly_launcher::{AppConfig, ProfileError, Renderer};
+
+#[test]
+fn profiles_round_trip_next_to_top_level_settings() {
+    let mut config = AppConfig::default();
+    config.launch.renderer = Renderer::DX9;
+    config.create_profile("Debugging").unwrap();
+    config.select_profile("Debugging").unwrap();
+    config.active_mut().renderer = Renderer::DX11;
+    config.active_mut().flags.nointro = true;
+
+    let text = toml::to_string(&config).unwrap();
+    assert!(text.contains("renderer = \"DX9\""));
+    assert!(text.contains("[profiles.Debugging]"));
+
+    let loaded: AppConfig = toml::from_str(&text).unwrap();
+    assert_eq!(loaded.profile_name(), "Debugging");
+    assert_eq!(loaded.active().renderer, Renderer::DX11);
+    assert!(loaded.active().flags.nointro);
+    assert_eq!(loaded.launch.renderer, Renderer::DX9);
+}
+
+#[test]
+fn duplicate_rename_and_delete() {
+    let mut config = AppConfig::default();
+    config.active_mut().debug = false;
+    config.duplicate_profile("Default", "Performance").unwrap();
+    config.select_profile("Performance").unwrap();
+    assert!(!config.active().debug);
+
+    config.rename_profile("Performance", "Fast").unwrap();
+    assert_eq!(config.profile_name(), "Fast");
+    assert_eq!(config.profile_names(), vec!["Default", "Fast"]);
+
+    config.delete_profile("Fast").unwrap();
+    assert_eq!(config.profile_name(), "Default");
+    assert_eq!(config.profile, None);
+}
+
+#[test]
+fn invalid_profile_operations_are_rejected() {
+    let mut config = AppConfig::default();
+    config.create_profile("A").unwrap();
+    assert_eq!(config.create_profile("A"), Err(ProfileError::AlreadyExists("A".to_string())));
+    assert_eq!(config.create_profile("default"), Err(ProfileError::AlreadyExists("default".to_string())));
+    assert_eq!(config.create_profile(" "), Err(ProfileError::InvalidName(" ".to_string())));
+    assert_eq!(config.select_profile("B"), Err(ProfileError::NotFound("B".to_string())));
+    assert_eq!(config.delete_profile("Default"), Err(ProfileError::InvalidName("Default".to_string())));
+    assert_eq!(config.rename_profile("Default", "C"), Err(ProfileError::InvalidName("Default".to_string())));
+}
+
+#[test]
+fn unknown_selected_profile_falls_back_to_default() {
+    let mut config = AppConfig { profile: Some("Removed".to_string()), ..Default::default() };
+    assert_eq!(config.profile_name(), "Default");
+    config.active_mut().debug = false;
+    assert!(!config.launch.debug);
+}
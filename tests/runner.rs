@@ -0,0 +1,86 @@
+#![cfg(unix)]
+
+use std::{collections::BTreeMap, fs, os::unix::fs::PermissionsExt, path::Path};
+
+use anomaly_launcher::{Game, Renderer, RunnerConfig, RunnerKind};
+use tempfile::TempDir;
+
+/// Writes a runner script that records its arguments and environment into `record`.
+fn stub_runner(dir: &Path, record: &Path) -> String {
+    let script = dir.join("stub-runner.sh");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\n{{\n  for arg in \"$@\"; do echo \"arg=$arg\"; done\n  echo \"WINEPREFIX=$WINEPREFIX\"\n  echo \"WINEDLLOVERRIDES=$WINEDLLOVERRIDES\"\n  echo \"STEAM_COMPAT_DATA_PATH=$STEAM_COMPAT_DATA_PATH\"\n  echo \"PROTON_USE_WINED3D=$PROTON_USE_WINED3D\"\n  echo \"CUSTOM=$CUSTOM\"\n}} > '{}'\n",
+            record.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    script.display().to_string()
+}
+
+fn game_dir() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("bin")).unwrap();
+    fs::write(dir.path().join("bin").join("AnomalyDX11.exe"), b"").unwrap();
+    dir
+}
+
+fn run(dir: &TempDir, runner: RunnerConfig) -> String {
+    let game = Game::resolve(dir.path(), &[], Renderer::DX11, false).with_runner(runner);
+    let status = game.command(&["-smap2048".to_string(), "-dbg".to_string()]).status().unwrap();
+    assert!(status.success());
+    fs::read_to_string(dir.path().join("record.txt")).unwrap()
+}
+
+#[test]
+fn wine_runner_passes_executable_prefix_and_environment() {
+    let dir = game_dir();
+    let runner = RunnerConfig {
+        kind: RunnerKind::Wine,
+        binary: Some(stub_runner(dir.path(), &dir.path().join("record.txt"))),
+        prefix: Some(dir.path().join("prefix")),
+        dxvk: true,
+        env: BTreeMap::from([("CUSTOM".to_string(), "value".to_string())]),
+    };
+
+    let record = run(&dir, runner);
+    let exe = dir.path().join("bin").join("AnomalyDX11.exe");
+    assert!(record.starts_with(&format!("arg={}\narg=-smap2048\narg=-dbg\n", exe.display())));
+    assert!(record.contains(&format!("WINEPREFIX={}\n", dir.path().join("prefix").display())));
+    assert!(record.contains("WINEDLLOVERRIDES=d3d9,d3d10core,d3d11,dxgi=n,b\n"));
+    assert!(record.contains("CUSTOM=value\n"));
+}
+
+#[test]
+fn wine_runner_forces_builtin_d3d_without_dxvk() {
+    let dir = game_dir();
+    let runner = RunnerConfig {
+        kind: RunnerKind::Wine,
+        binary: Some(stub_runner(dir.path(), &dir.path().join("record.txt"))),
+        dxvk: false,
+        ..Default::default()
+    };
+
+    let record = run(&dir, runner);
+    assert!(record.contains("WINEDLLOVERRIDES=d3d9,d3d10core,d3d11,dxgi=b\n"));
+}
+
+#[test]
+fn proton_runner_uses_run_verb_and_compat_data_path() {
+    let dir = game_dir();
+    let runner = RunnerConfig {
+        kind: RunnerKind::Proton,
+        binary: Some(stub_runner(dir.path(), &dir.path().join("record.txt"))),
+        prefix: Some(dir.path().join("compatdata")),
+        dxvk: false,
+        env: BTreeMap::new(),
+    };
+
+    let record = run(&dir, runner);
+    let exe = dir.path().join("bin").join("AnomalyDX11.exe");
+    assert!(record.starts_with(&format!("arg=run\narg={}\narg=-smap2048\n", exe.display())));
+    assert!(record.contains(&format!("STEAM_COMPAT_DATA_PATH={}\n", dir.path().join("compatdata").display())));
+    assert!(record.contains("PROTON_USE_WINED3D=1\n"));
+}
@@ -0,0 +1,71 @@
+use std::{fs, path::Path};
+
+use anomaly_launcher::{
+    save_backup::{self, BackupReason, BACKUPS_DIR, SAVES_DIR},
+    SaveBackup, SaveBackupConfig,
+};
+
+fn write_save(root: &Path, name: &str, content: &str) {
+    let path = root.join(SAVES_DIR).join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn fake_backup(root: &Path, name: &str) {
+    let dir = root.join(BACKUPS_DIR);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(name), "").unwrap();
+}
+
+#[test]
+fn backs_up_and_restores_the_saves() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(save_backup::create(dir.path(), Some("1.2.0"), BackupReason::Manual).unwrap().is_none());
+
+    write_save(dir.path(), "autosave.scop", "first");
+    write_save(dir.path(), "sub/quick.scop", "quick");
+    let backup = save_backup::create(dir.path(), Some("1.2.0"), BackupReason::Manual).unwrap().unwrap();
+    assert_eq!(backup.version.as_deref(), Some("1.2.0"));
+    assert_eq!(backup.reason, BackupReason::Manual);
+    assert_eq!(save_backup::list(dir.path()), vec![backup.clone()]);
+
+    write_save(dir.path(), "autosave.scop", "second");
+    write_save(dir.path(), "new.scop", "new");
+    save_backup::restore(dir.path(), &backup.path).unwrap();
+
+    let saves = dir.path().join(SAVES_DIR);
+    assert_eq!(fs::read_to_string(saves.join("autosave.scop")).unwrap(), "first");
+    assert_eq!(fs::read_to_string(saves.join("sub").join("quick.scop")).unwrap(), "quick");
+    assert!(!saves.join("new.scop").exists());
+}
+
+#[test]
+fn reads_backup_details_from_the_file_name() {
+    let backup = SaveBackup::from_path(Path::new("saves_20261018-143000_1.2.0-beta.1_update.zip")).unwrap();
+    assert_eq!(backup.created.format("%Y-%m-%d %H:%M:%S").to_string(), "2026-10-18 14:30:00");
+    assert_eq!(backup.version.as_deref(), Some("1.2.0-beta.1"));
+    assert_eq!(backup.reason, BackupReason::Update);
+
+    assert_eq!(SaveBackup::from_path(Path::new("saves_20261018-143000_unknown_manual.zip")).unwrap().version, None);
+    assert!(SaveBackup::from_path(Path::new("notes.zip")).is_none());
+}
+
+#[test]
+fn prunes_by_count_and_age_but_keeps_the_newest() {
+    let dir = tempfile::tempdir().unwrap();
+    for day in 1..=4 {
+        fake_backup(dir.path(), &format!("saves_2020010{}-120000_1.0.0_manual.zip", day));
+    }
+
+    let config = SaveBackupConfig { keep: 3, ..SaveBackupConfig::default() };
+    let deleted = save_backup::prune(dir.path(), &config).unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert!(deleted[0].ends_with("saves_20200101-120000_1.0.0_manual.zip"));
+
+    // All of them are years old, but the newest one stays
+    let config = SaveBackupConfig { keep: 0, max_age_days: Some(30), ..SaveBackupConfig::default() };
+    save_backup::prune(dir.path(), &config).unwrap();
+    let left = save_backup::list(dir.path());
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].name(), "saves_20200104-120000_1.0.0_manual.zip");
+}
@@ -0,0 +1,117 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use anomaly_launcher::{
+    saves::{self, SAVE_VERSIONS_FILE},
+    Manifest,
+};
+use zip::{write::FileOptions, ZipWriter};
+
+fn write_save(root: &Path, name: &str, age_secs: u64) {
+    let dir = saves::saves_dir(root);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(format!("{}.scop", name)), "save").unwrap();
+    fs::write(dir.join(format!("{}.dds", name)), "thumbnail").unwrap();
+    let file = File::options().write(true).open(dir.join(format!("{}.scop", name))).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(age_secs)).unwrap();
+}
+
+#[test]
+fn notes_the_version_of_new_and_changed_saves() {
+    let dir = tempfile::tempdir().unwrap();
+    write_save(dir.path(), "old", 300);
+    saves::track(dir.path(), Some("1.0.0")).unwrap();
+    assert!(saves::saves_dir(dir.path()).join(SAVE_VERSIONS_FILE).exists());
+
+    write_save(dir.path(), "quicksave", 100);
+    saves::track(dir.path(), Some("1.1.0")).unwrap();
+
+    let list = saves::list(dir.path()).unwrap();
+    assert_eq!(list.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["quicksave", "old"]);
+    // Saves from before tracking started have no known version
+    assert_eq!(list[1].version, None);
+    assert_eq!(list[0].version.as_deref(), Some("1.1.0"));
+    assert_eq!(list[0].size, "save".len() as u64 + "thumbnail".len() as u64);
+    assert!(list[0].thumbnail.is_some());
+
+    // Overwritten by the game after an update
+    write_save(dir.path(), "quicksave", 10);
+    assert_eq!(saves::list(dir.path()).unwrap()[0].version, None);
+    saves::track(dir.path(), Some("1.2.0")).unwrap();
+    assert_eq!(saves::list(dir.path()).unwrap()[0].version.as_deref(), Some("1.2.0"));
+}
+
+#[test]
+fn exports_and_imports_a_save_with_its_version() {
+    let game = tempfile::tempdir().unwrap();
+    let other = tempfile::tempdir().unwrap();
+    fs::create_dir_all(saves::saves_dir(game.path())).unwrap();
+    saves::track(game.path(), Some("1.0.0")).unwrap();
+    write_save(game.path(), "before the swamps", 60);
+    saves::track(game.path(), Some("1.3.0")).unwrap();
+
+    let save = saves::list(game.path()).unwrap().remove(0);
+    let archive = other.path().join("export.zip");
+    saves::export(&save, &archive).unwrap();
+
+    let name = saves::import(other.path(), &archive).unwrap();
+    assert_eq!(name, "before the swamps");
+    let imported = saves::list(other.path()).unwrap().remove(0);
+    assert_eq!(imported.version.as_deref(), Some("1.3.0"));
+    assert!(imported.thumbnail.is_some());
+
+    // Never overwrites an existing save
+    assert!(saves::import(other.path(), &archive).is_err());
+
+    saves::delete(&imported).unwrap();
+    assert!(saves::list(other.path()).unwrap().is_empty());
+    assert!(!saves::saves_dir(other.path()).join("before the swamps.dds").exists());
+}
+
+#[test]
+fn flags_a_latest_save_older_than_a_save_breaking_patch() {
+    let dir = tempfile::tempdir().unwrap();
+    write_save(dir.path(), "quicksave", 3600);
+    assert!(saves::outdated_latest_save(dir.path()).is_none());
+
+    saves::record_break(dir.path(), "1.3.0").unwrap();
+    let (save, record) = saves::outdated_latest_save(dir.path()).unwrap();
+    assert_eq!(save.name, "quicksave");
+    assert_eq!(record.version, "1.3.0");
+    assert!(Manifest::parse("[[patch]]\nversion = \"1.3.0\"\nurl = \"p.zip\"\nbreaks_saves = true\n").unwrap().patches[0].breaks_saves);
+
+    // A new game started after the update
+    write_save(dir.path(), "new game", 0);
+    let file = File::options().write(true).open(saves::saves_dir(dir.path()).join("new game.scop")).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+    assert!(saves::outdated_latest_save(dir.path()).is_none());
+}
+
+#[test]
+fn import_ignores_entries_outside_the_saves_folder() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("game");
+    let archive = dir.path().join("crafted.zip");
+    let write_zip = |entries: &[&str]| {
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        for name in entries {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(b"save").unwrap();
+        }
+        zip.finish().unwrap();
+    };
+
+    write_zip(&["..\\..\\evil.scop", "../evil.scop", "nested/evil.scop", "C:\\evil.scop"]);
+    assert!(saves::import(&root, &archive).is_err());
+
+    write_zip(&["..\\..\\evil.scop", "ok.scop", "..\\..\\ok.dds"]);
+    assert_eq!(saves::import(&root, &archive).unwrap(), "ok");
+    let mut files: Vec<String> = fs::read_dir(saves::saves_dir(&root)).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().to_string()).collect();
+    files.retain(|file| file != SAVE_VERSIONS_FILE);
+    assert_eq!(files, ["ok.scop"]);
+    assert!(!dir.path().join("evil.scop").exists());
+}
@@ -0,0 +1,104 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process::Command,
+    time::{Duration, SystemTime},
+};
+
+use anomaly_launcher::{session, Session, SessionRecord};
+use chrono::{Local, TimeZone};
+
+fn record(exit_code: Option<i32>, runtime_secs: u64) -> SessionRecord {
+    SessionRecord {
+        executable: PathBuf::from("bin/AnomalyDX11.exe"),
+        program: PathBuf::from("bin/AnomalyDX11.exe"),
+        started_at: Local.with_ymd_and_hms(2026, 10, 18, 14, 30, 5).unwrap(),
+        runtime: Duration::from_secs(runtime_secs),
+        exit_code,
+        crash_files: None,
+    }
+}
+
+#[test]
+fn only_a_zero_exit_code_is_a_clean_exit() {
+    assert!(!record(Some(0), 60).is_abnormal());
+    assert!(record(Some(1), 60).is_abnormal());
+    assert!(record(Some(-1073741819), 60).is_abnormal());
+    // Killed by a signal
+    assert!(record(None, 60).is_abnormal());
+}
+
+#[test]
+fn crash_files_decide_when_the_exit_code_is_not_the_games() {
+    let clean = SessionRecord { crash_files: Some(Vec::new()), ..record(Some(1), 60) };
+    assert!(!clean.is_abnormal());
+    assert_eq!(clean.summary(), "exit code 1 after 1m 00s");
+
+    let crashed = SessionRecord { crash_files: Some(vec![PathBuf::from("appdata/logs/crash.mdmp")]), ..record(Some(0), 60) };
+    assert!(crashed.is_abnormal());
+    assert_eq!(crashed.summary(), "crash report written after 1m 00s");
+}
+
+#[test]
+fn summaries_use_the_largest_units() {
+    assert_eq!(session::format_duration(Duration::from_secs(42)), "42s");
+    assert_eq!(session::format_duration(Duration::from_secs(65)), "1m 05s");
+    assert_eq!(session::format_duration(Duration::from_secs(3900)), "1h 05m");
+    assert_eq!(record(Some(0), 3900).summary(), "exit code 0 after 1h 05m");
+    assert_eq!(record(None, 5).summary(), "terminated after 5s");
+}
+
+#[test]
+fn sessions_are_appended_to_the_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join(session::SESSION_LOG);
+    record(Some(0), 3900).append_to_log(&log).unwrap();
+    record(None, 5).append_to_log(&log).unwrap();
+
+    let content = fs::read_to_string(&log).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], format!("2026-10-18 14:30:05 runtime=3900s exit_code=0 {}", PathBuf::from("bin/AnomalyDX11.exe").display()));
+    assert!(lines[1].contains("runtime=5s exit_code=none"));
+}
+
+#[test]
+fn the_spawned_program_is_logged_when_it_is_not_the_game() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join(session::SESSION_LOG);
+    let record = SessionRecord { program: PathBuf::from("ModOrganizer.exe"), ..record(Some(0), 5) };
+    record.append_to_log(&log).unwrap();
+
+    let content = fs::read_to_string(&log).unwrap();
+    assert!(content.trim_end().ends_with(" via ModOrganizer.exe"));
+}
+
+#[cfg(unix)]
+#[test]
+fn new_fatal_error_logs_mark_the_session_as_crashed() {
+    let dir = tempfile::tempdir().unwrap();
+    let logs = dir.path().join("appdata").join("logs");
+    fs::create_dir_all(&logs).unwrap();
+    let stale = logs.join("old.log");
+    fs::write(&stale, "FATAL ERROR\n").unwrap();
+    let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options().write(true).open(&stale).unwrap().set_modified(hour_ago).unwrap();
+
+    let write_log = format!("printf 'FATAL ERROR\\n\\n[error]Expression    : 0\\n' > '{}'", logs.join("xray_player.log").display());
+    let run = |script: &str| {
+        let child = Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+        Session::new(child, PathBuf::from("bin/AnomalyDX11.exe"), PathBuf::from("sh"))
+            .with_crash_dirs(vec![logs.clone()])
+            .wait()
+            .unwrap()
+    };
+
+    let clean = run("exit 0");
+    assert_eq!(clean.crash_files, Some(Vec::new()));
+    assert!(!clean.is_abnormal());
+
+    let crashed = run(&write_log);
+    assert_eq!(crashed.exit_code, Some(0));
+    assert_eq!(crashed.crash_files, Some(vec![logs.join("xray_player.log")]));
+    assert!(crashed.is_abnormal());
+}
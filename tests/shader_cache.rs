@@ -0,0 +1,86 @@
+use std::{fs, path::Path};
+
+use anomaly_launcher::{
+    mo2::MO2_EXECUTABLE,
+    shader_cache::{self, SHADER_CACHE_DIR},
+    Mo2Config, Renderer, ShaderCacheConfig, ShaderCacheError,
+};
+
+fn write_cache(root: &Path, file: &str, size: usize) {
+    let path = root.join(SHADER_CACHE_DIR).join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0u8; size]).unwrap();
+}
+
+#[test]
+fn reports_and_clears_the_cache_per_renderer() {
+    let dir = tempfile::tempdir().unwrap();
+    let mo2 = Mo2Config::default();
+    assert!(matches!(shader_cache::usage(dir.path(), &mo2), Err(ShaderCacheError::NotFound)));
+
+    write_cache(dir.path(), "r2/models_model.vs", 100);
+    write_cache(dir.path(), "r4/deffer_base.ps", 300);
+    write_cache(dir.path(), "r4/combine/combine_1.ps", 200);
+    write_cache(dir.path(), "shaders.log", 10);
+
+    let usage = shader_cache::usage(dir.path(), &mo2).unwrap();
+    assert_eq!(usage.size(Renderer::DX9), 100);
+    assert_eq!(usage.size(Renderer::DX11), 500);
+    assert_eq!(usage.size(Renderer::DX8), 0);
+    assert_eq!(usage.other, 10);
+    assert_eq!(usage.total(), 610);
+
+    assert_eq!(shader_cache::clear(dir.path(), &mo2, Some(Renderer::DX11)).unwrap(), 500);
+    assert!(!dir.path().join(SHADER_CACHE_DIR).join("r4").exists());
+    assert_eq!(shader_cache::usage(dir.path(), &mo2).unwrap().total(), 110);
+
+    assert_eq!(shader_cache::clear(dir.path(), &mo2, None).unwrap(), 110);
+    // The folder itself stays for the game to fill again
+    assert!(dir.path().join(SHADER_CACHE_DIR).is_dir());
+}
+
+#[test]
+fn clears_the_cache_when_the_renderer_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let mo2 = Mo2Config::default();
+    let config = ShaderCacheConfig::default();
+    write_cache(dir.path(), "r3/deffer_base.ps", 100);
+
+    // The first launch only remembers the renderer
+    assert!(!shader_cache::prepare_launch(dir.path(), &mo2, Renderer::DX10, &config).unwrap());
+    assert!(!shader_cache::prepare_launch(dir.path(), &mo2, Renderer::DX10, &config).unwrap());
+    assert_eq!(shader_cache::usage(dir.path(), &mo2).unwrap().total(), 100);
+
+    let keep = ShaderCacheConfig { clear_on_renderer_change: false, ..config.clone() };
+    assert!(!shader_cache::prepare_launch(dir.path(), &mo2, Renderer::DX9, &keep).unwrap());
+    assert!(shader_cache::prepare_launch(dir.path(), &mo2, Renderer::DX11, &config).unwrap());
+    assert_eq!(shader_cache::usage(dir.path(), &mo2).unwrap().total(), 0);
+
+    // The game never ran, so there is nothing to clear or remember
+    let fresh = tempfile::tempdir().unwrap();
+    assert!(!shader_cache::prepare_launch(fresh.path(), &mo2, Renderer::DX11, &config).unwrap());
+    assert!(!fresh.path().join("appdata").exists());
+
+    assert!(shader_cache::is_shader_path("gamedata\\Shaders\\r3\\deffer_base.ps"));
+    assert!(!shader_cache::is_shader_path("gamedata/configs/shaders.ltx"));
+}
+
+#[test]
+fn under_mo2_the_marker_stays_with_the_overwrite_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("Anomaly");
+    let instance = dir.path().join("MO2");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&instance).unwrap();
+    fs::write(instance.join(MO2_EXECUTABLE), "").unwrap();
+    fs::write(instance.join("ModOrganizer.ini"), "[General]\n").unwrap();
+    let overwrite = instance.join("overwrite");
+    write_cache(&overwrite, "r4/deffer_base.ps", 100);
+
+    let mo2 = Mo2Config::default();
+    let config = ShaderCacheConfig::default();
+    assert!(!shader_cache::prepare_launch(&root, &mo2, Renderer::DX11, &config).unwrap());
+    assert!(!root.join(SHADER_CACHE_DIR).exists());
+    assert!(shader_cache::prepare_launch(&root, &mo2, Renderer::DX9, &config).unwrap());
+    assert_eq!(shader_cache::usage(&root, &mo2).unwrap().total(), 0);
+}
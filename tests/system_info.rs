@@ -0,0 +1,24 @@
+use anomaly_launcher::{system_info::CpuFeatures, LaunchSettings};
+
+fn cpu(avx: bool) -> CpuFeatures {
+    CpuFeatures { brand: Some("Test CPU".to_string()), sse4_2: true, avx, avx2: avx, avx512f: false }
+}
+
+#[test]
+fn the_avx_build_is_the_default_only_when_supported() {
+    assert!(LaunchSettings::for_cpu(&cpu(true)).use_avx);
+    assert!(!LaunchSettings::for_cpu(&cpu(false)).use_avx);
+    assert_eq!(cpu(true).list()[1], ("AVX", true));
+}
+
+#[test]
+fn launching_the_avx_build_without_avx_is_blocked() {
+    let avx_build = LaunchSettings::for_cpu(&cpu(true));
+    assert!(!avx_build.avx_unsupported(&cpu(true)));
+    // Settings copied from another machine
+    assert!(avx_build.avx_unsupported(&cpu(false)));
+
+    let regular_build = LaunchSettings::for_cpu(&cpu(false));
+    assert!(!regular_build.avx_unsupported(&cpu(false)));
+    assert!(!regular_build.avx_unsupported(&cpu(true)));
+}
@@ -0,0 +1,48 @@
+use std::{env, fs, io::Write, path::Path};
+
+use anomaly_launcher::{manifest::PATCH_INFO_FILE, AppConfig, Updater, UpdaterError};
+use zip::{write::FileOptions, ZipWriter};
+
+fn write_patch(path: &Path, info: &str, files: &[(&str, &str)]) {
+    let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+    zip.start_file(PATCH_INFO_FILE, FileOptions::default()).unwrap();
+    zip.write_all(info.as_bytes()).unwrap();
+    for (name, content) in files {
+        zip.start_file(*name, FileOptions::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+// The updater extracts into the current directory, so this is the only test in the file
+#[test]
+fn a_file_with_a_wrong_hash_never_replaces_the_game_file() {
+    let dir = tempfile::tempdir().unwrap();
+    env::set_current_dir(dir.path()).unwrap();
+    fs::create_dir_all("gamedata/configs").unwrap();
+    fs::write("gamedata/configs/system.ltx", "original").unwrap();
+    let updater = Updater::new(AppConfig::default()).unwrap();
+
+    // sha256 of "patched"
+    let good = "[files]\n\"gamedata/configs/system.ltx\" = \"d7017ebcd65455e76e953d5b42fa96c3df28c7c3b616c7f069ed930fb4fae5fd\"\n";
+    let patch = dir.path().join("patch-1.1.0.zip");
+    write_patch(&patch, good, &[("gamedata/configs/system.ltx", "tampered")]);
+    assert!(matches!(updater.apply_patch(&patch, &mut |_| {}), Err(UpdaterError::VerificationError(_))));
+    assert_eq!(fs::read_to_string("gamedata/configs/system.ltx").unwrap(), "original");
+    assert!(!Path::new("gamedata/configs/system.ltx.partial").exists());
+
+    // A bad file late in the archive leaves the earlier ones and the deletions untouched
+    fs::write("gamedata/configs/old.ltx", "old").unwrap();
+    let with_deletion = format!("deleted = [\"gamedata/configs/old.ltx\"]\n{}", good);
+    write_patch(&patch, &with_deletion, &[("gamedata/configs/new.ltx", "new"), ("gamedata/configs/system.ltx", "tampered")]);
+    assert!(updater.apply_patch(&patch, &mut |_| {}).is_err());
+    assert!(!Path::new("gamedata/configs/new.ltx").exists());
+    assert!(!Path::new("gamedata/configs/new.ltx.partial").exists());
+    assert!(Path::new("gamedata/configs/old.ltx").exists());
+
+    write_patch(&patch, &with_deletion, &[("gamedata/configs/new.ltx", "new"), ("gamedata/configs/system.ltx", "patched")]);
+    updater.apply_patch(&patch, &mut |_| {}).unwrap();
+    assert_eq!(fs::read_to_string("gamedata/configs/system.ltx").unwrap(), "patched");
+    assert_eq!(fs::read_to_string("gamedata/configs/new.ltx").unwrap(), "new");
+    assert!(!Path::new("gamedata/configs/old.ltx").exists());
+}
@@ -0,0 +1,58 @@
+use std::fs;
+
+use anomaly_launcher::{user_ltx, GraphicsSettings, ScreenMode, UserLtx};
+
+const SAMPLE: &str = "; custom comment\r\nbind forward kW\r\nfov 67.5\r\nrs_screenmode fullscreen\r\nrs_v_sync off\r\nsome_addon_key 1 2 3\r\nvid_mode 1920x1080\r\n";
+
+#[test]
+fn reads_curated_settings() {
+    let settings = GraphicsSettings::from_ltx(&UserLtx::parse(SAMPLE));
+    assert_eq!(settings.resolution, (1920, 1080));
+    assert_eq!(settings.screen_mode, ScreenMode::Fullscreen);
+    assert!(!settings.vsync);
+    assert_eq!(settings.fov, 67.5);
+}
+
+#[test]
+fn unchanged_settings_leave_the_file_untouched() {
+    let mut ltx = UserLtx::parse(SAMPLE);
+    GraphicsSettings::from_ltx(&ltx).apply(&mut ltx);
+    assert_eq!(ltx.to_string(), SAMPLE);
+}
+
+#[test]
+fn edits_keep_comments_unknown_keys_and_line_endings() {
+    let mut ltx = UserLtx::parse(SAMPLE);
+    let mut settings = GraphicsSettings::from_ltx(&ltx);
+    settings.resolution = (2560, 1440);
+    settings.vsync = true;
+    settings.fps_limit = 144;
+    settings.apply(&mut ltx);
+
+    assert_eq!(
+        ltx.to_string(),
+        "; custom comment\r\nbind forward kW\r\nfov 67.5\r\nrs_screenmode fullscreen\r\nrs_v_sync on\r\nsome_addon_key 1 2 3\r\nvid_mode 2560x1440\r\nr__framelimit 144\r\n"
+    );
+}
+
+#[test]
+fn save_keeps_a_backup_and_non_utf8_bytes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("user.ltx");
+    let original = b"g_game_difficulty gd_master\nsave_name \xcf\xf0\xe8\xe2\xe5\xf2\nvid_mode 1280x720\n".to_vec();
+    fs::write(&path, &original).unwrap();
+
+    let mut ltx = UserLtx::load(&path).unwrap();
+    ltx.set("vid_mode", "1920x1080");
+    ltx.save(&path).unwrap();
+
+    assert_eq!(fs::read(user_ltx::backup_path(&path)).unwrap(), original);
+    let saved = fs::read(&path).unwrap();
+    assert!(saved.windows(6).any(|w| w == b"\xcf\xf0\xe8\xe2\xe5\xf2"));
+    assert!(saved.ends_with(b"vid_mode 1920x1080\n"));
+
+    // Later saves keep the original backup
+    ltx.set("vid_mode", "2560x1440");
+    ltx.save(&path).unwrap();
+    assert_eq!(fs::read(user_ltx::backup_path(&path)).unwrap(), original);
+}